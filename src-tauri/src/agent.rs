@@ -1,12 +1,31 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::process::{Child, Command, Stdio};
+use std::process::{Child, ChildStdin, Command, Stdio};
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::io::{BufRead, BufReader};
 use std::thread;
 use tauri::Emitter;
 
+pub mod accounting;
+pub mod attachments;
+pub mod backends;
+pub mod budget;
+pub mod handoff;
+pub mod model;
+pub mod notes;
+pub mod persistence;
+pub mod policy;
+pub mod prompt;
+pub mod records;
+pub mod retry;
+pub mod scheduler;
+pub mod settings;
+pub mod termination;
+pub mod usage;
+
+use backends::{AgentBackend, LaunchSpec, PromptMode, ResumeStrategy};
+
 // Set to true to see all verbose debug messages, false for production filtering
 const AGENT_DEBUG: bool = false;
 
@@ -24,27 +43,77 @@ pub struct AgentMessage {
 pub struct AgentProcess {
     pub id: String,
     pub task_id: String,
-    pub status: String, // "running", "completed", "failed", "killed"
+    #[serde(default)]
+    pub project_id: Option<String>, // project of the task, for project-wide budgets and accounting
+    pub status: String, // "queued", "starting", "running", "awaiting_input", "paused", "stalled", "completed", "failed", "killed", "cancelled", "interrupted"
     pub start_time: String,
     pub end_time: Option<String>,
+    #[serde(default)]
     pub messages: Vec<AgentMessage>,
+    #[serde(default)]
     pub raw_output: Vec<String>,
     pub session_id: Option<String>,
     pub total_cost_usd: Option<f64>,
     pub num_turns: Option<i32>,
+    #[serde(default)]
     pub worktree_path: String,
     #[serde(default)]
     pub kind: AgentKind,
+    #[serde(default)]
+    pub parent_process_id: Option<String>, // process this one continues, if any
+    #[serde(default)]
+    pub policy: Option<policy::AgentPolicy>, // policy the process was launched with
+    #[serde(default)]
+    pub usage: usage::TokenUsage,
+    #[serde(default)]
+    pub budget: Option<budget::RunBudget>, // limits enforced on this run, if any
+    #[serde(default)]
+    pub exit_reason: Option<String>, // how the child ended, e.g. "terminated by SIGINT"
+    #[serde(default)]
+    pub queue_position: Option<usize>, // 1-based place in the run queue while "queued"
+    #[serde(default)]
+    pub prompt: String, // message the run was started with, without replayed context
+    #[serde(default)]
+    pub last_output_at: Option<String>, // epoch seconds of the last stdout/stderr line
+    #[serde(default)]
+    pub retry_of: Option<String>, // original run when this is a retry (after a failure or stall)
+    #[serde(default = "first_attempt")]
+    pub attempt: u32, // 1 for the original run
+    #[serde(default = "first_attempt")]
+    pub max_attempts: u32, // from the task's retry policy
+    #[serde(default)]
+    pub model: Option<String>, // requested model, replaced by the one the CLI reports using
+    #[serde(default)]
+    pub reasoning_effort: Option<String>, // "low", "medium" or "high" if one was requested
+    #[serde(default)]
+    pub attachments: attachments::Attachments, // files and images referenced by `prompt`
+    #[serde(default)]
+    pub paused_secs: u64, // time spent paused in earlier pauses, left out of the run time
+    #[serde(default)]
+    pub paused_at: Option<u64>, // when the current pause started
+    #[serde(skip)]
+    pub resumed_at: Option<u64>, // when a pause last ended; restarts the stall clock
 }
 
+fn first_attempt() -> u32 { 1 }
+
+/// Name of the backend that ran a process (e.g. "claude", "codex"); see `backends`
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub enum AgentKind {
-    Claude,
-    Codex,
+#[serde(transparent)]
+pub struct AgentKind(String);
+
+impl AgentKind {
+    pub fn new(name: &str) -> Self {
+        AgentKind(name.trim().to_lowercase())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
 impl Default for AgentKind {
-    fn default() -> Self { AgentKind::Claude }
+    fn default() -> Self { AgentKind::new("claude") }
 }
 
 // Store for active child processes
@@ -55,6 +124,14 @@ fn get_child_processes() -> &'static ChildProcessMap {
     CHILD_PROCESSES.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
 }
 
+// Open stdin pipes of processes that take answers (e.g. permission prompts) while running
+type ChildStdinMap = Arc<Mutex<HashMap<String, ChildStdin>>>;
+static CHILD_STDINS: std::sync::OnceLock<ChildStdinMap> = std::sync::OnceLock::new();
+
+fn get_child_stdins() -> &'static ChildStdinMap {
+    CHILD_STDINS.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
+}
+
 // Global process storage
 type ProcessMap = Arc<Mutex<HashMap<String, AgentProcess>>>;
 static PROCESSES: std::sync::OnceLock<ProcessMap> = std::sync::OnceLock::new();
@@ -145,7 +222,9 @@ fn split_json_objects(line: &str) -> Vec<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::split_json_objects;
+    use super::{active_secs, parse_codex_output, split_json_objects, AgentProcess};
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use super::backends::{AgentBackend, CodexBackend};
 
     #[test]
     fn splits_multiple_json_objects_on_one_line() {
@@ -156,6 +235,61 @@ mod tests {
         assert_eq!(objs[1], "{\"b\":2}");
         assert_eq!(objs[2], "{\"c\":3}");
     }
+
+    const CODEX_TOKEN_COUNT: &str = r#"{"id":"0","msg":{"type":"token_count","input_tokens":0,"output_tokens":0,"total_tokens":0,"info":{"total_token_usage":{"input_tokens":12800,"cached_input_tokens":3456,"output_tokens":640,"reasoning_output_tokens":320,"total_tokens":13440},"last_token_usage":{"input_tokens":6400,"cached_input_tokens":3456,"output_tokens":320,"reasoning_output_tokens":160,"total_tokens":6720},"model_context_window":272000},"rate_limits":{"primary":{"used_percent":4.0,"window_minutes":300,"resets_in_seconds":15000}}}}"#;
+
+    fn codex_run() -> AgentProcess {
+        serde_json::from_value(serde_json::json!({
+            "id": "p1", "task_id": "t1", "status": "running", "start_time": "0", "kind": "codex",
+        })).unwrap()
+    }
+
+    #[test]
+    fn paused_time_is_left_out_of_the_run_time() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let mut proc = codex_run();
+        proc.start_time = (now - 600).to_string();
+        proc.paused_secs = 200;
+        assert!((400..=401).contains(&active_secs(&proc)));
+
+        // Still paused: the current pause does not count either
+        proc.status = "paused".to_string();
+        proc.paused_at = Some(now - 300);
+        assert!((100..=101).contains(&active_secs(&proc)));
+
+        let budget = super::budget::AgentBudget { max_minutes: Some(5), ..Default::default() };
+        let run_budget = super::budget::RunBudget { limits: budget, ..Default::default() };
+        let processes = std::collections::HashMap::new();
+        assert!(run_budget.exceeded_by(&proc, &processes, active_secs(&proc)).is_none());
+    }
+
+    #[test]
+    fn codex_nested_token_usage_is_recorded() {
+        let message = parse_codex_output(CODEX_TOKEN_COUNT).expect("token_count is kept");
+        assert_eq!(message.message_type, "token_count");
+        assert!(message.content.contains("12800 input"));
+
+        let mut proc = codex_run();
+        CodexBackend::default().apply_message(&mut proc, &message);
+        assert_eq!(proc.usage.input_tokens, 12800 - 3456);
+        assert_eq!(proc.usage.cached_tokens, 3456);
+        assert_eq!(proc.usage.output_tokens, 640);
+    }
+
+    #[test]
+    fn codex_turn_completed_usage_is_recorded() {
+        let line = r#"{"type":"turn.completed","usage":{"input_tokens":24763,"cached_input_tokens":24448,"output_tokens":122}}"#;
+        let message = parse_codex_output(line).expect("turn.completed is kept");
+        assert_eq!(message.message_type, "token_count");
+
+        let mut proc = codex_run();
+        CodexBackend::default().apply_message(&mut proc, &message);
+        CodexBackend::default().apply_message(&mut proc, &message);
+        assert_eq!(proc.usage.input_tokens, 2 * (24763 - 24448));
+        assert_eq!(proc.usage.cached_tokens, 2 * 24448);
+        assert_eq!(proc.usage.output_tokens, 2 * 122);
+        assert_eq!(proc.usage.turns, 2);
+    }
 }
 
 /// Parses Codex CLI JSONL events into AgentMessage based on actual Codex output format
@@ -210,6 +344,22 @@ fn parse_codex_output(line: &str) -> Option<AgentMessage> {
                         metadata: Some(json),
                     })
                 }
+                "session_configured" => {
+                    let session_id = msg.get("session_id").and_then(|v| v.as_str()).unwrap_or("unknown");
+                    let model = msg.get("model").and_then(|v| v.as_str()).unwrap_or("unknown");
+                    Some(AgentMessage {
+                        id: generate_message_id(),
+                        sender: "system".to_string(),
+                        content: format!("Codex session configured ({}, model: {})", session_id, model),
+                        timestamp: get_timestamp(),
+                        message_type: "session_configured".to_string(),
+                        metadata: Some(json),
+                    })
+                }
+                "agent_message_delta" | "agent_reasoning_delta" => {
+                    // Streamed to the UI by the runner; the finished message follows
+                    None
+                }
                 "agent_reasoning_section_break" => {
                     // Skip these as they're just formatting breaks - don't display in UI
                     None
@@ -241,9 +391,11 @@ fn parse_codex_output(line: &str) -> Option<AgentMessage> {
                     })
                 }
                 "token_count" => {
-                    let input_tokens = msg.get("input_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
-                    let output_tokens = msg.get("output_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
-                    let total_tokens = msg.get("total_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
+                    // Newer CLIs nest a running total under info and leave the top-level counts empty
+                    let usage = msg.pointer("/info/total_token_usage").unwrap_or(msg);
+                    let input_tokens = usage.get("input_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
+                    let output_tokens = usage.get("output_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
+                    let total_tokens = usage.get("total_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
                     
                     // Filter out zero-value token counts (just noise)
                     if input_tokens == 0 && output_tokens == 0 && total_tokens == 0 {
@@ -497,6 +649,19 @@ fn parse_codex_output(line: &str) -> Option<AgentMessage> {
                     }
                 }
             }
+        } else if json.get("type").and_then(|v| v.as_str()) == Some("turn.completed") {
+            // `codex exec --json` reports each turn's usage when the turn ends
+            let usage = json.get("usage").unwrap_or(&serde_json::Value::Null);
+            let input_tokens = usage.get("input_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
+            let output_tokens = usage.get("output_tokens").and_then(|v| v.as_u64()).unwrap_or(0);
+            Some(AgentMessage {
+                id: generate_message_id(),
+                sender: "system".to_string(),
+                content: format!("💰 Token usage: {} input, {} output, {} total", input_tokens, output_tokens, input_tokens + output_tokens),
+                timestamp: get_timestamp(),
+                message_type: "token_count".to_string(),
+                metadata: Some(json),
+            })
         } else { 
             // Handle other JSON structures as raw data
             Some(AgentMessage {
@@ -649,6 +814,24 @@ fn parse_claude_output(line: &str) -> Option<AgentMessage> {
                     return None;
                 }
             },
+            "control_request" => {
+                // Tool permission prompt (only sent with --permission-prompt-tool stdio)
+                let request = json.get("request")?;
+                if request.get("subtype").and_then(|v| v.as_str()) != Some("can_use_tool") {
+                    return None;
+                }
+                let tool_name = request.get("tool_name").and_then(|v| v.as_str()).unwrap_or("unknown");
+                let tool_input = request.get("input").unwrap_or(&serde_json::Value::Null);
+
+                Some(AgentMessage {
+                    id: generate_message_id(),
+                    sender: "agent".to_string(),
+                    content: format!("Permission requested: {} - {}", tool_name, tool_input),
+                    timestamp: get_timestamp(),
+                    message_type: "permission_request".to_string(),
+                    metadata: Some(json.clone()),
+                })
+            },
             "result" => {
                 // Final result with cost and session info
                 let subtype = json.get("subtype").and_then(|v| v.as_str()).unwrap_or("success");
@@ -699,868 +882,960 @@ fn parse_claude_output(line: &str) -> Option<AgentMessage> {
     }
 }
 
-/// Spawns a new Claude Code process
-pub fn spawn_claude_process(
-    app: tauri::AppHandle,
-    task_id: String,
-    initial_message: String,
-    worktree_path: String,
-    context: Option<String>
-) -> Result<String, String> {
-    let process_id = generate_process_id();
-    println!("Spawning Claude Code process {} for task {}", process_id, task_id);
+/// Copies the current environment onto a command so PATH lookups behave like the app's
+fn inherit_env(cmd: &mut Command) {
+    for (key, value) in std::env::vars() {
+        cmd.env(key, value);
+    }
+}
 
-    // Construct the full message with context if provided
-    let full_message = if let Some(ctx) = context {
-        format!("Previous conversation:\n{}\n\nNew message: {}", ctx, initial_message)
+/// Creates a command for a candidate program. `.cmd` shims are run through cmd.exe
+/// to avoid Rust's .cmd escaping guard.
+fn candidate_command(candidate: &backends::CommandCandidate) -> Command {
+    let mut cmd = if candidate.program.ends_with(".cmd") {
+        let mut c = Command::new("cmd");
+        c.arg("/C").arg(&candidate.program);
+        c
     } else {
-        initial_message.clone()
+        Command::new(&candidate.program)
     };
+    cmd.args(&candidate.prefix_args);
+    cmd
+}
 
-    // Try multiple Claude command variations (similar to VS Code code.cmd issue)
-    let claude_commands = ["claude", "claude.exe", "claude.cmd"];
-    let mut cmd = None;
+/// Finds the first working candidate for a backend and builds the full command
+fn build_agent_command(backend: &dyn AgentBackend, spec: &LaunchSpec) -> Result<Command, String> {
+    let worktree_path = spec.worktree_path.as_str();
+    let probe_args = backend.probe_args();
+    for candidate in backend.command_candidates() {
+        let probe = match &probe_args {
+            Some(args) => {
+                let mut test_cmd = candidate_command(&candidate);
+                test_cmd.args(args)
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .current_dir(worktree_path);
+                inherit_env(&mut test_cmd);
+
+                println!("Testing {} command: {} {:?}", backend.display_name(), candidate.program, candidate.prefix_args);
+                test_cmd.status()
+            }
+            // No probe: trust the configured command and let spawn report failures
+            None => Ok(std::process::ExitStatus::default()),
+        };
 
-    for command in &claude_commands {
-        let mut test_cmd = Command::new(command);
-        test_cmd.arg("--version")
-               .stdout(Stdio::null())
-               .stderr(Stdio::null())
-               .current_dir(&worktree_path); // Set working directory
+        match probe {
+            Ok(status) if status.success() => {
+                println!("Found working {} command: {}", backend.display_name(), candidate.program);
 
-        // Inherit environment variables to ensure PATH is available
-        for (key, value) in std::env::vars() {
-            test_cmd.env(key, value);
-        }
+                // Sanitize prompt for cmd.exe: avoid literal newlines which can break argument parsing
+                let mut spec = spec.clone();
+                if candidate.program.ends_with(".cmd") && backend.prompt_mode() == PromptMode::Argv {
+                    spec.prompt = spec.prompt.replace("\r\n", " ").replace('\n', " ");
+                }
 
-        println!("Testing Claude command: {}", command);
-        match test_cmd.status() {
-            Ok(status) if status.success() => {
-                println!("Found working Claude command: {}", command);
-                // let mut working_cmd = Command::new(command);
-                // working_cmd.arg("-p")
-                //           .arg(&full_message)
-                //           .arg("--output-format")
-                //           .arg("stream-json")
-                //           .arg("--verbose")
-                //           .arg("--dangerously-skip-permissions")
-                //           .arg("--add-dir")
-                //           .arg(&worktree_path)
-                //           .stdout(Stdio::piped())
-                //           .stderr(Stdio::piped())
-                //           .current_dir(&worktree_path); // Set working directory
-                let mut working_cmd = if command.ends_with(".cmd") {
-                    // Important: call via cmd.exe to avoid Rust's .cmd escaping guard
-                    let mut c = Command::new("cmd");
-                    // Sanitize prompt for cmd.exe: avoid literal newlines which can break argument parsing
-                    let prompt_arg = full_message.replace("\r\n", " ").replace('\n', " ");
-                    c.arg("/C")
-                    .arg(command) // "claude.cmd"
-                    .arg("-p").arg(prompt_arg)
-                    .arg("--output-format").arg("stream-json")
-                    .arg("--verbose")
-                    .arg("--permission-mode").arg("acceptEdits")
-                    .arg("--dangerously-skip-permissions")
-                    .arg("--allowedTools").arg("Read,Write,Edit,MultiEdit,Bash")
-                    .arg("--add-dir").arg(&worktree_path)
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .current_dir(&worktree_path);
-                    c
-                } else {
-                    let mut c = Command::new(command);
-                    c.arg("-p").arg(&full_message)
-                    .arg("--output-format").arg("stream-json")
-                    .arg("--verbose")
-                    .arg("--permission-mode").arg("acceptEdits")
-                    .arg("--dangerously-skip-permissions")
-                    .arg("--allowedTools").arg("Read,Write,Edit,MultiEdit,Bash")
-                    .arg("--add-dir").arg(&worktree_path)
+                let mut cmd = candidate_command(&candidate);
+                cmd.args(backend.build_args(&spec))
                     .stdout(Stdio::piped())
                     .stderr(Stdio::piped())
-                    .current_dir(&worktree_path);
-                    c
-                };
-
-                // Inherit environment variables
-                for (key, value) in std::env::vars() {
-                    working_cmd.env(key, value);
+                    .current_dir(worktree_path);
+                if spec.uses_stdin(backend) {
+                    cmd.stdin(Stdio::piped());
                 }
-
-                cmd = Some(working_cmd);
-                break;
+                inherit_env(&mut cmd);
+                cmd.envs(backend.launch_env(&spec));
+                termination::isolate(&mut cmd);
+                return Ok(cmd);
             }
             Ok(status) => {
-                println!("Command {} exists but failed with status: {}", command, status);
+                println!("Command {} exists but failed with status: {}", candidate.program, status);
             }
             Err(e) => {
-                println!("Command {} not found or failed: {:?}", command, e);
+                println!("Command {} not found or failed: {:?}", candidate.program, e);
             }
         }
     }
 
-    let mut cmd = cmd.ok_or_else(|| {
-        let attempted = claude_commands.join(", ");
-        format!("Claude Code CLI not found. Tried commands: {}. Please ensure Claude Code is installed and in PATH.", attempted)
-    })?;
+    Err(backend.not_found_message())
+}
 
-    println!("Claude Code command: {:?}", cmd);
+/// Writes the current registry entry of a process to disk
+fn persist_process(app: &tauri::AppHandle, process_id: &str) {
+    let snapshot = get_process_by_id(process_id);
+    if let Some(proc) = snapshot {
+        if let Err(e) = persistence::save_process(app, &proc) {
+            println!("Failed to persist process {}: {}", process_id, e);
+        }
+        if let Err(e) = accounting::record_process(app, &proc) {
+            println!("Failed to record usage of process {}: {}", process_id, e);
+        }
+    }
+}
 
-    // Create initial process entry with user message
-    let process = AgentProcess {
-        id: process_id.clone(),
-        task_id: task_id.clone(),
-        status: "starting".to_string(),
-        start_time: get_timestamp(),
-        end_time: None,
-        messages: vec![
-            AgentMessage {
-                id: generate_message_id(),
-                sender: "user".to_string(),
-                content: format!("Task: {}", initial_message),
-                timestamp: get_timestamp(),
-                message_type: "text".to_string(),
-                metadata: Some(serde_json::json!({
-                    "task_id": task_id,
-                    "worktree_path": worktree_path
-                })),
+/// Appends a message to the stored transcript of a process
+fn persist_message(app: &tauri::AppHandle, task_id: &str, process_id: &str, message: &AgentMessage) {
+    if let Err(e) = persistence::append_message(app, task_id, process_id, message) {
+        println!("Failed to persist message for process {}: {}", process_id, e);
+    }
+}
+
+/// Statuses of a process whose child is still expected to be alive
+fn is_live_status(status: &str) -> bool {
+    matches!(status, "running" | "awaiting_input" | "paused" | "stalled")
+}
+
+/// Moves a live process to another live status (e.g. `running` -> `awaiting_input`)
+/// and emits the change. Finished and paused processes are left alone.
+fn set_live_status(app: &tauri::AppHandle, process_id: &str, status: &str) {
+    let task_id = {
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        match map.get_mut(process_id) {
+            Some(proc) if is_live_status(&proc.status) && proc.status != "paused" && proc.status != status => {
+                proc.status = status.to_string();
+                proc.task_id.clone()
             }
-        ],
-        raw_output: Vec::new(),
-        session_id: None,
-        total_cost_usd: None,
-        num_turns: None,
-        worktree_path: worktree_path.clone(),
-        kind: AgentKind::Claude,
+            _ => return,
+        }
     };
+    emit_process_status(app, process_id, &task_id, status);
+}
 
-    // Store process before spawning
+/// Seconds since a process started (start_time is epoch seconds)
+fn elapsed_secs(start_time: &str) -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    start_time.parse::<u64>().map(|start| now.saturating_sub(start)).unwrap_or(0)
+}
+
+/// Seconds a process has been running, leaving out the time it spent paused
+fn active_secs(proc: &AgentProcess) -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let current_pause = proc.paused_at.map(|at| now.saturating_sub(at)).unwrap_or(0);
+    elapsed_secs(&proc.start_time).saturating_sub(proc.paused_secs + current_pause)
+}
+
+/// Kills a live process that has crossed its budget and records a
+/// `budget_exceeded` system message explaining which limit was hit
+fn enforce_budget(app: &tauri::AppHandle, process_id: &str) {
+    let exceeded = {
+        let processes = get_processes();
+        let map = processes.lock().unwrap();
+        map.get(process_id)
+            .filter(|proc| is_live_status(&proc.status))
+            .and_then(|proc| {
+                let reason = proc.budget.as_ref()?.exceeded_by(proc, &map, active_secs(proc))?;
+                Some((proc.task_id.clone(), reason))
+            })
+    };
+    let Some((task_id, reason)) = exceeded else { return; };
+
+    println!("Process {} exceeded its budget: {}", process_id, reason);
+    if let Err(e) = kill_process(app, process_id) {
+        println!("Failed to kill process {} over budget: {}", process_id, e);
+    }
+
+    let message = AgentMessage {
+        id: generate_message_id(),
+        sender: "system".to_string(),
+        content: format!("Budget exceeded, process stopped: {}", reason),
+        timestamp: get_timestamp(),
+        message_type: "budget_exceeded".to_string(),
+        metadata: Some(serde_json::json!({ "reason": reason })),
+    };
     {
         let processes = get_processes();
         let mut map = processes.lock().unwrap();
-        map.insert(process_id.clone(), process.clone());
-        
-        // Emit process creation event
-        let status_payload = serde_json::json!({
-            "process_id": process_id,
-            "task_id": task_id,
-            "status": "starting"
-        });
-        
-        match app.emit("agent_process_status", status_payload.clone()) {
-            Ok(_) => println!("✅ Emitted agent_process_status event: {} starting", process_id),
-            Err(e) => println!("❌ Failed to emit process status event: {:?}", e)
+        if let Some(proc) = map.get_mut(process_id) {
+            proc.messages.push(message.clone());
+        }
+    }
+    persist_message(app, &task_id, process_id, &message);
+    emit_message_update(app, process_id, &task_id, &message);
+}
+
+/// Marks a running process `stalled` once it has produced no output for
+/// `timeout_secs`, and optionally kills and restarts it. A restart is not
+/// restarted again.
+fn detect_stall(app: &tauri::AppHandle, process_id: &str, timeout_secs: u64, retry: bool) {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let stalled = {
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        let Some(proc) = map.get_mut(process_id).filter(|proc| proc.status == "running") else { return; };
+        let last_activity = proc.last_output_at.as_deref().unwrap_or(&proc.start_time)
+            .parse::<u64>().unwrap_or(now)
+            .max(proc.resumed_at.unwrap_or(0));
+        let idle = now.saturating_sub(last_activity);
+        if idle < timeout_secs {
+            return;
+        }
+
+        proc.status = "stalled".to_string();
+        let message = AgentMessage {
+            id: generate_message_id(),
+            sender: "system".to_string(),
+            content: format!("No output for {}s; the run looks stalled", idle),
+            timestamp: get_timestamp(),
+            message_type: "stalled".to_string(),
+            metadata: Some(serde_json::json!({ "idle_secs": idle })),
         };
-        
-        // Also broadcast to HTTP clients
-        crate::web::broadcast_to_http("agent_process_status", status_payload);
+        proc.messages.push(message.clone());
+        (proc.task_id.clone(), message, proc.retry_of.is_none())
+    };
+    let (task_id, message, can_retry) = stalled;
+
+    println!("Process {} stalled: {}", process_id, message.content);
+    persist_message(app, &task_id, process_id, &message);
+    emit_message_update(app, process_id, &task_id, &message);
+    emit_process_status(app, process_id, &task_id, "stalled");
+
+    if retry && can_retry {
+        if let Err(e) = kill_process(app, process_id) {
+            println!("Failed to kill stalled process {}: {}", process_id, e);
+            return;
+        }
+        match retry_process(app, process_id, "The previous run stopped responding and was restarted. Continue the task from where you left off.") {
+            Ok(new_id) => println!("Stalled process {} restarted as {}", process_id, new_id),
+            Err(e) => println!("Failed to restart stalled process {}: {}", process_id, e),
+        }
     }
+}
 
-    // Create a temporary config file to set permissions
-    // COMMENTED OUT: Temporarily disabled
-    // let config_dir = format!("{}/.claude", worktree_path);
-    // std::fs::create_dir_all(&config_dir).ok();
-    // let config_path = format!("{}/settings.local.json", config_dir);
-    // let config_content = serde_json::json!({
-    //     "permissionMode": "acceptEdits",
-    //     "allowedTools": ["Read", "Write", "Edit", "MultiEdit", "Bash"]
-    // });
-    // if let Ok(config_str) = serde_json::to_string_pretty(&config_content) {
-    //     std::fs::write(&config_path, config_str).ok();
-    //     println!("Created Claude config at: {}", config_path);
-    // }
-
-    // Spawn the actual Claude Code process
-    match cmd.spawn() {
-        Ok(mut child) => {
-            println!("Claude Code process spawned successfully with PID: {:?}", child.id());
-
-            // Take ownership of stdout and stderr
-            let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
-            let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
-
-            // Store the child process
-            {
-                let child_processes = get_child_processes();
-                let mut map = child_processes.lock().unwrap();
-                map.insert(process_id.clone(), child);
-            }
+/// Starts a run again after it failed or stalled, resuming its session when the
+/// backend allows it (with `resume_prompt`) and otherwise replaying recent
+/// messages in front of the original prompt
+fn retry_process(app: &tauri::AppHandle, process_id: &str, resume_prompt: &str) -> Result<String, String> {
+    let parent = get_process_by_id(process_id).ok_or_else(|| "Process not found".to_string())?;
+    let backend = resolve_backend_with_settings(app, parent.kind.as_str());
+    let mut continuation = Continuation::from_parent(&parent, backend.as_ref());
+    continuation.retry_of = Some(parent.retry_of.clone().unwrap_or_else(|| parent.id.clone()));
+    continuation.attempt = parent.attempt.max(1) + 1;
+    continuation.attachments = parent.attachments.clone();
+    let prompt = if continuation.session_id.is_some() {
+        resume_prompt.to_string()
+    } else {
+        parent.prompt.clone()
+    };
+    // Keep the model the failed attempt ran with
+    let choice = model::ModelChoice::new(parent.model.clone(), parent.reasoning_effort.clone());
+    spawn_agent_process(app.clone(), backend, parent.task_id.clone(), prompt, parent.worktree_path.clone(), continuation, choice)
+}
 
-            // Update process status to running
-            {
-                let processes = get_processes();
-                let mut map = processes.lock().unwrap();
-                if let Some(proc) = map.get_mut(&process_id) {
-                    proc.status = "running".to_string();
-                    
-                    // Emit process status update event
-                    let status_payload = serde_json::json!({
-                        "process_id": process_id,
-                        "task_id": proc.task_id,
-                        "status": "running"
-                    });
-                    
-                    match app.emit("agent_process_status", status_payload.clone()) {
-                        Ok(_) => println!("✅ Emitted agent_process_status event: {} running", process_id),
-                        Err(e) => println!("❌ Failed to emit process status event: {:?}", e)
-                    };
-                    
-                    // Also broadcast to HTTP clients
-                    crate::web::broadcast_to_http("agent_process_status", status_payload);
-                }
-            }
+/// Schedules the next attempt of a failed run when the task's retry policy
+/// allows it: attempts are left and the error output matches a retryable pattern
+fn schedule_retry(app: &tauri::AppHandle, process_id: &str) {
+    let Some(proc) = get_process_by_id(process_id) else { return; };
+    if proc.status != "failed" || proc.attempt >= proc.max_attempts {
+        return;
+    }
+    // A failed handoff summary falls back to the transcript instead
+    if handoff::is_pending(process_id) {
+        return;
+    }
+    let policy = retry::resolve_retry_policy(app, &proc.task_id);
+    if !policy.matches(&proc) {
+        println!("Process {} failed with an error that is not retryable", process_id);
+        return;
+    }
 
-            // Spawn thread to read stdout (JSON messages)
-            let process_id_stdout = process_id.clone();
-            let processes_stdout = get_processes().clone();
-            let app_handle_stdout = app.clone();
-            thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines() {
-                    match line {
-                        Ok(line_content) => {
-                            println!("Claude Code stdout: {}", line_content);
-
-                            // Store raw output
-                            {
-                                let mut map = processes_stdout.lock().unwrap();
-                                if let Some(proc) = map.get_mut(&process_id_stdout) {
-                                    proc.raw_output.push(line_content.clone());
-                                }
-                            }
+    let next_attempt = proc.attempt + 1;
+    let delay = policy.backoff_for(next_attempt);
+    let message = AgentMessage {
+        id: generate_message_id(),
+        sender: "system".to_string(),
+        content: format!("Attempt {} of {} failed; retrying in {}s", proc.attempt, proc.max_attempts, delay),
+        timestamp: get_timestamp(),
+        message_type: "retry_scheduled".to_string(),
+        metadata: Some(serde_json::json!({
+            "next_attempt": next_attempt,
+            "max_attempts": proc.max_attempts,
+            "delay_secs": delay
+        })),
+    };
+    {
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        if let Some(proc) = map.get_mut(process_id) {
+            proc.messages.push(message.clone());
+        }
+    }
+    persist_message(app, &proc.task_id, process_id, &message);
+    emit_message_update(app, process_id, &proc.task_id, &message);
+
+    let app = app.clone();
+    let process_id = process_id.to_string();
+    thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_secs(delay));
+        match retry_process(&app, &process_id, "The previous attempt failed and was restarted. Continue the task from where you left off.") {
+            Ok(new_id) => println!("Process {} retried as {} (attempt {})", process_id, new_id, next_attempt),
+            Err(e) => println!("Failed to retry process {}: {}", process_id, e),
+        }
+    });
+}
 
-                            // Parse and store structured message
-                            match parse_claude_output(&line_content) {
-                                Some(message) => {
-                                    println!("Parsed message: {:?}", message);
-                                    let mut map = processes_stdout.lock().unwrap();
-                                    if let Some(proc) = map.get_mut(&process_id_stdout) {
-                                    // Update session info from system events
-                                    if message.message_type == "init" || message.message_type == "system" {
-                                        if let Some(metadata) = &message.metadata {
-                                            if let Some(session_id) = metadata.get("session_id").and_then(|v| v.as_str()) {
-                                                proc.session_id = Some(session_id.to_string());
-                                            }
-                                        }
-                                    }
-
-                                    // Update cost and turns from result events
-                                    if message.message_type == "result" {
-                                        if let Some(metadata) = &message.metadata {
-                                            if let Some(cost) = metadata.get("total_cost_usd").and_then(|v| v.as_f64()) {
-                                                proc.total_cost_usd = Some(cost);
-                                            }
-                                            if let Some(turns) = metadata.get("num_turns").and_then(|v| v.as_i64()) {
-                                                proc.num_turns = Some(turns as i32);
-                                            }
-                                        }
-                                        proc.status = "completed".to_string();
-                                        proc.end_time = Some(get_timestamp());
-                                    }
-
-                                        proc.messages.push(message.clone());
-                                        println!("Message stored. Total messages: {}", proc.messages.len());
-                                        
-                                        // Emit Tauri event for real-time updates
-                                        let message_payload = serde_json::json!({
-                                            "process_id": process_id_stdout,
-                                            "task_id": proc.task_id,
-                                            "message": message
-                                        });
-                                        
-                                        match app_handle_stdout.emit("agent_message_update", message_payload.clone()) {
-                                            Ok(_) => println!("✅ Emitted agent_message_update event for process {}", process_id_stdout),
-                                            Err(e) => println!("❌ Failed to emit event: {:?}", e)
-                                        };
-                                        
-                                        // Also broadcast to HTTP clients
-                                        crate::web::broadcast_to_http("agent_message_update", message_payload);
-                                    }
-                                },
-                                None => {
-                                    println!("Failed to parse message: {}", line_content);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            println!("Error reading Claude Code stdout: {}", e);
-                            break;
-                        }
-                    }
-                }
+/// Emits an agent_message_update event to the webview and HTTP clients
+fn emit_message_update(app: &tauri::AppHandle, process_id: &str, task_id: &str, message: &AgentMessage) {
+    let message_payload = serde_json::json!({
+        "process_id": process_id,
+        "task_id": task_id,
+        "message": message
+    });
+
+    match app.emit("agent_message_update", message_payload.clone()) {
+        Ok(_) => println!("✅ Emitted agent_message_update event for process {}", process_id),
+        Err(e) => println!("❌ Failed to emit event: {:?}", e)
+    };
 
-                println!("Claude Code stdout reader thread finished for process {}", process_id_stdout);
-            });
+    // Also broadcast to HTTP clients
+    crate::web::broadcast_to_http("agent_message_update", message_payload);
+}
 
-            // Spawn thread to read stderr (error messages)
-            let process_id_stderr = process_id.clone();
-            let processes_stderr = get_processes().clone();
-            thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines() {
-                    match line {
-                        Ok(line_content) => {
-                            println!("Claude Code stderr: {}", line_content);
-
-                            // Store error as a message
-                            let error_message = AgentMessage {
-                                id: generate_message_id(),
-                                sender: "system".to_string(),
-                                content: line_content,
-                                timestamp: get_timestamp(),
-                                message_type: "error".to_string(),
-                                metadata: None,
-                            };
+/// Emits an agent_message_delta event carrying a piece of streamed assistant
+/// text. Deltas are not persisted; the finished message reuses `message_id`.
+fn emit_message_delta(app: &tauri::AppHandle, process_id: &str, task_id: &str, message_id: &str, delta: &str) {
+    let delta_payload = serde_json::json!({
+        "process_id": process_id,
+        "task_id": task_id,
+        "message_id": message_id,
+        "delta": delta
+    });
+
+    if let Err(e) = app.emit("agent_message_delta", delta_payload.clone()) {
+        println!("❌ Failed to emit message delta: {:?}", e);
+    }
 
-                            let mut map = processes_stderr.lock().unwrap();
-                            if let Some(proc) = map.get_mut(&process_id_stderr) {
-                                proc.messages.push(error_message);
-                            }
-                        }
-                        Err(e) => {
-                            println!("Error reading Claude Code stderr: {}", e);
-                            break;
-                        }
-                    }
-                }
+    // Also broadcast to HTTP clients
+    crate::web::broadcast_to_http("agent_message_delta", delta_payload);
+}
 
-                println!("Claude Code stderr reader thread finished for process {}", process_id_stderr);
-            });
+/// Persists a status change and emits an agent_process_status event to the webview and HTTP clients
+fn emit_process_status(app: &tauri::AppHandle, process_id: &str, task_id: &str, status: &str) {
+    persist_process(app, process_id);
+
+    let (exit_reason, queue_position) = get_process_by_id(process_id)
+        .map(|proc| (proc.exit_reason, proc.queue_position))
+        .unwrap_or_default();
+    let status_payload = serde_json::json!({
+        "process_id": process_id,
+        "task_id": task_id,
+        "status": status,
+        "exit_reason": exit_reason,
+        "queue_position": queue_position
+    });
+
+    match app.emit("agent_process_status", status_payload.clone()) {
+        Ok(_) => println!("✅ Emitted agent_process_status event: {} {} for task {}", process_id, status, task_id),
+        Err(e) => println!("❌ Failed to emit process status event: {:?}", e)
+    };
 
-            // Spawn thread to monitor process completion
-            let process_id_monitor = process_id.clone();
-            let processes_monitor = get_processes().clone();
-            let child_processes_monitor = get_child_processes().clone();
-            let app_handle_monitor = app.clone();
-            thread::spawn(move || {
-                // Wait a bit for the process to potentially finish
-                std::thread::sleep(std::time::Duration::from_secs(1));
-
-                // Check if child process is still alive
-                let mut should_wait = true;
-                while should_wait {
-                    {
-                        let mut child_map = child_processes_monitor.lock().unwrap();
-                        if let Some(child) = child_map.get_mut(&process_id_monitor) {
-                            match child.try_wait() {
-                                Ok(Some(status)) => {
-                                    println!("Claude Code process {} finished with status: {}", process_id_monitor, status);
-                                    should_wait = false;
-
-                                    // Update process status
-                                    let mut proc_map = processes_monitor.lock().unwrap();
-                                    if let Some(proc) = proc_map.get_mut(&process_id_monitor) {
-                                        if proc.status == "running" {
-                                            proc.status = if status.success() { "completed".to_string() } else { "failed".to_string() };
-                                            proc.end_time = Some(get_timestamp());
-                                        }
-                                    }
-
-                                    // Emit status update event with task_id
-                                    let final_status = if status.success() { "completed" } else { "failed" };
-                                    let task_id = proc_map.get(&process_id_monitor)
-                                        .map(|p| p.task_id.clone())
-                                        .unwrap_or_else(|| "unknown".to_string());
-
-                                    let status_payload = serde_json::json!({
-                                        "process_id": process_id_monitor,
-                                        "task_id": task_id,
-                                        "status": final_status
-                                    });
-
-                                    match app_handle_monitor.emit("agent_process_status", status_payload.clone()) {
-                                        Ok(_) => println!("✅ Emitted agent_process_status event: {} {} for task {}", process_id_monitor, final_status, task_id),
-                                        Err(e) => println!("❌ Failed to emit completion status event: {:?}", e)
-                                    };
-
-                                    // Also broadcast to HTTP clients
-                                    crate::web::broadcast_to_http("agent_process_status", status_payload);
-
-                                    // Remove from child processes
-                                    child_map.remove(&process_id_monitor);
-                                }
-                                Ok(None) => {
-                                    // Process still running, wait a bit more
-                                    std::thread::sleep(std::time::Duration::from_millis(500));
-                                }
-                                Err(e) => {
-                                    println!("Error checking process status: {}", e);
-                                    should_wait = false;
-                                }
-                            }
-                        } else {
-                            should_wait = false;
-                        }
-                    }
-                }
+    // Also broadcast to HTTP clients
+    crate::web::broadcast_to_http("agent_process_status", status_payload);
+}
 
-            println!("Process monitor thread finished for process {}", process_id_monitor);
-            });
+/// How a new process carries on from an earlier one
+#[derive(Debug, Clone, Default)]
+pub struct Continuation {
+    pub parent_process_id: Option<String>,
+    /// Session id passed to the CLI's native resume
+    pub session_id: Option<String>,
+    /// Text replay of recent messages, used when there is no session to resume
+    pub context: Option<String>,
+    /// Original run and attempt number, when this is a retry
+    pub retry_of: Option<String>,
+    pub attempt: u32,
+    /// Files and images referenced by the new message
+    pub attachments: attachments::Attachments,
+}
 
-            println!("Process {} started successfully with monitoring threads", process_id);
-            Ok(process_id)
+impl Continuation {
+    /// Builds the continuation of `parent` for a run on `backend`. Native resume is
+    /// only possible when the same backend recorded a session id; otherwise the
+    /// last 20 messages are replayed as text.
+    fn from_parent(parent: &AgentProcess, backend: &dyn AgentBackend) -> Self {
+        let same_backend = parent.kind.as_str().eq_ignore_ascii_case(backend.name());
+        if backend.resume_strategy() == ResumeStrategy::NativeSession && same_backend {
+            if let Some(session_id) = parent.session_id.clone() {
+                println!("Resuming {} session {} from process {}", backend.display_name(), session_id, parent.id);
+                return Continuation {
+                    parent_process_id: Some(parent.id.clone()),
+                    session_id: Some(session_id),
+                    context: None,
+                    retry_of: None,
+                    attempt: 0,
+                    attachments: attachments::Attachments::default(),
+                };
+            }
         }
-        Err(e) => {
-            // Log detailed error information
-            println!("Failed to spawn Claude Code process: {:?}", e);
-            println!("Error kind: {:?}", e.kind());
-            println!("Current working directory: {:?}", std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("unknown")));
-            println!("Environment PATH: {:?}", std::env::var("PATH").unwrap_or_else(|_| "not found".to_string()));
 
-            // Mark process as failed
-            let processes = get_processes();
-            let mut map = processes.lock().unwrap();
-            if let Some(proc) = map.get_mut(&process_id) {
-                proc.status = "failed".to_string();
-                proc.end_time = Some(get_timestamp());
-            }
-            Err(format!("Failed to spawn Claude Code process: {}", e))
+        // Limit context to last 20 messages to avoid huge prompts
+        let take_last = 20usize;
+        let start = parent.messages.len().saturating_sub(take_last);
+        let context_messages: Vec<String> = parent.messages.iter()
+            .skip(start)
+            .map(|msg| format!("{}: {}", msg.sender, msg.content))
+            .collect();
+        Continuation {
+            parent_process_id: Some(parent.id.clone()),
+            session_id: None,
+            context: Some(context_messages.join("\n")),
+            retry_of: None,
+            attempt: 0,
+            attachments: attachments::Attachments::default(),
         }
     }
 }
 
-/// Spawns a new Codex (ChatGPT Code) process
-pub fn spawn_codex_process(
+/// Resolves a profile (e.g. "claude", "codex") to a backend using the latest settings
+fn resolve_backend_with_settings(app: &tauri::AppHandle, profile: &str) -> Arc<dyn AgentBackend> {
+    // Pick up the latest command/args overrides before resolving the backend
+    backends::apply_settings(&settings::load_agent_settings(app));
+    backends::resolve_backend(profile)
+}
+
+/// Spawns a new agent process for the given profile (e.g. "claude", "codex")
+pub fn spawn_agent_for_profile(
+    app: tauri::AppHandle,
+    profile: &str,
+    task_id: String,
+    initial_message: String,
+    worktree_path: String,
+    choice: model::ModelChoice,
+) -> Result<String, String> {
+    let backend = resolve_backend_with_settings(&app, profile);
+    spawn_agent_process(app, backend, task_id, initial_message, worktree_path, Continuation::default(), choice)
+}
+
+/// Registers a new agent run using the given backend. It starts right away when
+/// the concurrency limits allow it, otherwise it is queued (`queued` status) and
+/// started by the scheduler once a slot frees up. Fields left unset in `choice`
+/// are taken from the task.
+pub fn spawn_agent_process(
     app: tauri::AppHandle,
+    backend: Arc<dyn AgentBackend>,
     task_id: String,
     initial_message: String,
     worktree_path: String,
-    context: Option<String>,
+    continuation: Continuation,
+    choice: model::ModelChoice,
 ) -> Result<String, String> {
+    let choice = model::resolve_model_choice(&app, &task_id, choice);
+    choice.validate()?;
     let process_id = generate_process_id();
-    println!("Spawning Codex process {} for task {}", process_id, task_id);
+    let agent_name = backend.display_name().to_string();
+    println!("Spawning {} process {} for task {}", agent_name, process_id, task_id);
 
-    // Try codex.cmd directly first, then fallback to npx if needed
-    let mut cmd = None;
-    
-    // First try direct codex.cmd command
-    println!("Testing codex.cmd availability...");
-    let mut codex_test = Command::new("cmd");
-    codex_test.arg("/C")
-        .arg("codex.cmd")
-        .arg("--version")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .current_dir(&worktree_path);
-    
-    for (key, value) in std::env::vars() {
-        codex_test.env(key, value);
-    }
-    
-    match codex_test.status() {
-        Ok(status) if status.success() => {
-            println!("Found codex.cmd, using direct command: codex.cmd exec");
-            let mut working_cmd = Command::new("cmd");
-            working_cmd
-                .arg("/C")
-                .arg("codex.cmd")
-                .arg("exec")
-                .arg("--json")
-                .arg("--skip-git-repo-check")
-                .arg("--dangerously-bypass-approvals-and-sandbox")
-                .arg("--sandbox").arg("danger-full-access")
-                .stdin(Stdio::piped())  // We'll pass prompt via stdin
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .current_dir(&worktree_path);
-
-            for (key, value) in std::env::vars() {
-                working_cmd.env(key, value);
-            }
+    // Construct the full message with context if provided
+    let attachments = continuation.attachments.clone();
+    let message = attachments.expand(&initial_message, &worktree_path, backend.images_in_prompt());
+    let full_message = if let Some(ctx) = &continuation.context {
+        format!("Previous conversation:\n{}\n\nNew message: {}", ctx, message)
+    } else {
+        message
+    };
 
-            cmd = Some(working_cmd);
-        }
-        Ok(status) => {
-            println!("codex.cmd exists but failed with status: {}", status);
-        }
-        Err(e) => {
-            println!("codex.cmd not found: {:?}, trying npx fallback...", e);
-        }
-    }
-    
-    // Fallback to npx approach if codex.cmd is not available
-    if cmd.is_none() {
-        println!("Testing npx availability for Codex fallback...");
-        let mut npx_test = Command::new("npx");
-        npx_test.arg("--version")
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .current_dir(&worktree_path);
-        
-        for (key, value) in std::env::vars() {
-            npx_test.env(key, value);
-        }
-        
-        match npx_test.status() {
-            Ok(status) if status.success() => {
-                println!("Found npx, using fallback Codex command: npx -y @openai/codex exec");
-                let mut working_cmd = Command::new("npx");
-                working_cmd
-                    .arg("-y")
-                    .arg("@openai/codex")
-                    .arg("exec")
-                    .arg("--json")
-                    .arg("--skip-git-repo-check")
-                    .arg("--dangerously-bypass-approvals-and-sandbox")
-                    .arg("--sandbox").arg("danger-full-access")
-                    .stdin(Stdio::piped())  // We'll pass prompt via stdin
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .current_dir(&worktree_path);
-
-                for (key, value) in std::env::vars() {
-                    working_cmd.env(key, value);
-                }
-
-                cmd = Some(working_cmd);
-            }
-            Ok(status) => {
-                println!("npx exists but failed with status: {}", status);
-            }
-            Err(e) => {
-                println!("npx not found: {:?}, trying other direct codex commands...", e);
-            }
-        }
-    }
-    
-    // Final fallback to other direct codex commands if both codex.cmd and npx fail
-    if cmd.is_none() {
-        let codex_commands = ["codex", "codex.exe"];
-        
-        for command in &codex_commands {
-            let mut test_cmd = Command::new(command);
-            test_cmd.arg("--version")
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .current_dir(&worktree_path);
-
-            for (key, value) in std::env::vars() {
-                test_cmd.env(key, value);
-            }
-
-            println!("Testing fallback Codex command: {}", command);
-            match test_cmd.status() {
-                Ok(status) if status.success() => {
-                    println!("Found working fallback Codex command: {}", command);
-                    let mut working_cmd = Command::new(command);
-                    working_cmd
-                        .arg("exec")
-                        .arg("--json")
-                        .arg("--skip-git-repo-check")
-                        .arg("--dangerously-bypass-approvals-and-sandbox")
-                        .arg("--sandbox").arg("danger-full-access")
-                        .stdin(Stdio::piped())  // Pass prompt via stdin
-                        .stdout(Stdio::piped())
-                        .stderr(Stdio::piped())
-                        .current_dir(&worktree_path);
-
-                    for (key, value) in std::env::vars() {
-                        working_cmd.env(key, value);
-                    }
-
-                    cmd = Some(working_cmd);
-                    break;
-                }
-                Ok(status) => {
-                    println!("Command {} exists but failed with status: {}", command, status);
-                }
-                Err(e) => {
-                    println!("Error testing command {}: {:?}", command, e);
-                }
-            }
-        }
-    }
-
-    let Some(mut cmd) = cmd else {
-        return Err(format!(
-            "Codex CLI not found. Tried codex.cmd exec, npx -y @openai/codex exec, and direct codex commands. Ensure Codex is installed (npm install -g @openai/codex) or available in PATH."
-        ));
+    let policy = policy::resolve_policy(&app, &task_id);
+    println!("{} policy for task {}: {}", agent_name, task_id, policy.summary());
+    let spec = LaunchSpec {
+        prompt: full_message.clone(),
+        worktree_path: worktree_path.clone(),
+        resume_session_id: continuation.session_id.clone(),
+        route_permissions: backend.supports_permission_prompts() && policy.routes_permissions(),
+        policy: policy.clone(),
+        model: choice.model.clone(),
+        reasoning_effort: choice.reasoning_effort.clone(),
+        images: attachments.images.clone(),
     };
 
-    // Create process record (reuse structure; messages will still parse as text if not JSON)
+    // Create initial process entry
+    let mut first_message = backend.initial_message(&task_id, &initial_message, &worktree_path);
+    attachments.annotate(&mut first_message.metadata);
+    let mut messages = vec![first_message];
+    if policy.routes_permissions() && !backend.supports_permission_prompts() {
+        messages.push(AgentMessage {
+            id: generate_message_id(),
+            sender: "system".to_string(),
+            content: format!(
+                "{} cannot send permission prompts to the board; it runs without asking, limited by the {} sandbox",
+                agent_name,
+                policy.sandbox.as_str()
+            ),
+            timestamp: get_timestamp(),
+            message_type: "system_status".to_string(),
+            metadata: None,
+        });
+    }
+    let project_id = records::project_of(&app, &task_id);
+    let run_budget = budget::resolve_budget(&app, &task_id, project_id.as_deref(), &get_all_processes());
     let process = AgentProcess {
         id: process_id.clone(),
         task_id: task_id.clone(),
-        status: "starting".to_string(),
+        project_id: project_id.clone(),
+        status: "queued".to_string(),
         start_time: get_timestamp(),
         end_time: None,
-        messages: vec![AgentMessage {
-            id: generate_message_id(),
-            sender: "system".to_string(),
-            content: format!("Starting Codex agent for task: {}", task_id),
-            timestamp: get_timestamp(),
-            message_type: "text".to_string(),
-            metadata: Some(serde_json::json!({
-                "task_id": task_id,
-                "worktree_path": worktree_path
-            })),
-        }],
+        messages,
         raw_output: Vec::new(),
         session_id: None,
         total_cost_usd: None,
         num_turns: None,
         worktree_path: worktree_path.clone(),
-        kind: AgentKind::Codex,
+        kind: AgentKind::new(backend.name()),
+        parent_process_id: continuation.parent_process_id.clone(),
+        policy: Some(policy),
+        usage: usage::TokenUsage::default(),
+        budget: run_budget,
+        exit_reason: None,
+        queue_position: None,
+        prompt: initial_message.clone(),
+        last_output_at: None,
+        retry_of: continuation.retry_of.clone(),
+        attempt: continuation.attempt.max(1),
+        max_attempts: retry::resolve_retry_policy(&app, &task_id).max_attempts.max(1),
+        model: choice.model,
+        reasoning_effort: choice.reasoning_effort,
+        attachments,
+        paused_secs: 0,
+        paused_at: None,
+        resumed_at: None,
     };
 
+    // Store process before spawning
+    if let Err(e) = persistence::save_messages(&app, &task_id, &process_id, &process.messages) {
+        println!("Failed to persist initial messages for process {}: {}", process_id, e);
+    }
     {
         let processes = get_processes();
         let mut map = processes.lock().unwrap();
-        map.insert(process_id.clone(), process.clone());
+        map.insert(process_id.clone(), process);
+    }
 
-        let status_payload = serde_json::json!({
-            "process_id": process_id,
-            "task_id": task_id,
-            "status": "starting"
-        });
-        
-        match app.emit("agent_process_status", status_payload.clone()) {
-            Ok(_) => println!("✅ Emitted agent_process_status event: {} starting", process_id),
-            Err(e) => println!("⚠️ Failed to emit process status event: {:?}", e)
-        };
-        
-        // Also broadcast to HTTP clients
-        crate::web::broadcast_to_http("agent_process_status", status_payload);
+    let run = scheduler::QueuedRun { process_id: process_id.clone(), task_id, project_id, backend, spec };
+    match scheduler::submit(&scheduler::load_limits(&app), run) {
+        Some(run) => {
+            if let Err(e) = launch_run(&app, run) {
+                // The failed run gave its slot back; others may fit now
+                start_queued_runs(&app);
+                return Err(e);
+            }
+        }
+        None => {
+            println!("Concurrency limit reached, {} process {} queued", agent_name, process_id);
+            sync_queue_positions(&app);
+        }
     }
+    Ok(process_id)
+}
 
-    match cmd.spawn() {
-        Ok(mut child) => {
-            println!("Codex process spawned successfully with PID: {:?}", child.id());
+/// Marks a run that could not be started as failed, records why and frees its slot
+fn fail_launch(app: &tauri::AppHandle, process_id: &str, task_id: &str, error: &str) {
+    let message = AgentMessage {
+        id: generate_message_id(),
+        sender: "system".to_string(),
+        content: error.to_string(),
+        timestamp: get_timestamp(),
+        message_type: "error".to_string(),
+        metadata: None,
+    };
+    {
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        if let Some(proc) = map.get_mut(process_id) {
+            proc.status = "failed".to_string();
+            proc.end_time = Some(get_timestamp());
+            proc.messages.push(message.clone());
+        }
+    }
+    persist_message(app, task_id, process_id, &message);
+    emit_process_status(app, process_id, task_id, "failed");
+    scheduler::release(process_id);
+    // No exit monitor runs for a launch that failed; a handoff waiting on it
+    // continues from the transcript
+    complete_handoff(app, process_id);
+}
 
-            // Write the prompt to stdin and close it
-            if let Some(stdin) = child.stdin.take() {
-                use std::io::Write;
-                let mut stdin_writer = stdin;
-                let full_message = if let Some(ctx) = context {
-                    format!("Previous conversation:\n{}\n\nNew message: {}", ctx, initial_message)
-                } else {
-                    initial_message.clone()
-                };
-                if let Err(e) = stdin_writer.write_all(full_message.as_bytes()) {
-                    println!("Failed to write prompt to Codex stdin: {}", e);
-                }
-                if let Err(e) = stdin_writer.write_all(b"\n") {
-                    println!("Failed to write newline to Codex stdin: {}", e);
+/// Fails a launch that went wrong after the CLI was spawned, stopping its
+/// process group so nothing keeps running without a record
+fn abandon_launch(app: &tauri::AppHandle, process_id: &str, task_id: &str, mut child: Child, error: &str) {
+    termination::stop_group(child.id());
+    let _ = child.wait();
+    get_child_stdins().lock().unwrap().remove(process_id);
+    fail_launch(app, process_id, task_id, error);
+}
+
+/// Frees the slot of a finished run and starts whatever the queue allows
+fn finish_run(app: &tauri::AppHandle, process_id: &str) {
+    if scheduler::release(process_id) {
+        start_queued_runs(app);
+    }
+}
+
+/// Starts queued runs while the concurrency limits allow it
+fn start_queued_runs(app: &tauri::AppHandle) {
+    let mut started = false;
+    while let Some(run) = scheduler::next_ready(&scheduler::load_limits(app)) {
+        let process_id = run.process_id.clone();
+        println!("Starting queued process {}", process_id);
+        if let Err(e) = launch_run(app, run) {
+            println!("Queued process {} failed to start: {}", process_id, e);
+        }
+        started = true;
+    }
+    if started {
+        sync_queue_positions(app);
+    }
+}
+
+/// Writes each queued process's position (1 = next) and announces changes
+fn sync_queue_positions(app: &tauri::AppHandle) {
+    let queued = scheduler::queued();
+    let mut changed = Vec::new();
+    {
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        for (index, (process_id, task_id)) in queued.iter().enumerate() {
+            if let Some(proc) = map.get_mut(process_id) {
+                if proc.queue_position != Some(index + 1) {
+                    proc.queue_position = Some(index + 1);
+                    changed.push((process_id.clone(), task_id.clone()));
                 }
-                // stdin_writer is dropped here, closing the pipe
             }
+        }
+    }
+    for (process_id, task_id) in changed {
+        emit_process_status(app, &process_id, &task_id, "queued");
+    }
+}
 
-            let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
-            let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+/// Moves a queued process to `position` (1 = next to start)
+pub fn move_queued_process(app: &tauri::AppHandle, process_id: &str, position: usize) -> Result<(), String> {
+    scheduler::move_to(process_id, position.saturating_sub(1))?;
+    sync_queue_positions(app);
+    Ok(())
+}
 
-            {
-                let child_processes = get_child_processes();
-                let mut map = child_processes.lock().unwrap();
-                map.insert(process_id.clone(), child);
-            }
+/// Spawns the CLI for a run that holds a slot and wires up the stdout/stderr
+/// readers and the exit monitor. On failure the run is marked failed and its
+/// slot is freed.
+fn launch_run(app: &tauri::AppHandle, run: scheduler::QueuedRun) -> Result<(), String> {
+    let scheduler::QueuedRun { process_id, task_id, backend, spec, .. } = run;
+    let app = app.clone();
+    let agent_name = backend.display_name().to_string();
 
-            {
-                let processes = get_processes();
-                let mut map = processes.lock().unwrap();
-                if let Some(proc) = map.get_mut(&process_id) {
-                    proc.status = "running".to_string();
-                    let status_payload = serde_json::json!({
-                        "process_id": process_id,
-                        "task_id": proc.task_id,
-                        "status": "running"
-                    });
-                    
-                    match app.emit("agent_process_status", status_payload.clone()) {
-                        Ok(_) => println!("✅ Emitted agent_process_status event: running"),
-                        Err(e) => println!("⚠️ Failed to emit process status event: {:?}", e)
-                    };
-                    
-                    // Also broadcast to HTTP clients
-                    crate::web::broadcast_to_http("agent_process_status", status_payload);
-                }
+    // Budgets and elapsed time count from the actual start, not the time queued
+    {
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        if let Some(proc) = map.get_mut(&process_id) {
+            proc.status = "starting".to_string();
+            proc.start_time = get_timestamp();
+            proc.queue_position = None;
+        }
+    }
+    emit_process_status(&app, &process_id, &task_id, "starting");
+
+    let mut cmd = match build_agent_command(backend.as_ref(), &spec) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            fail_launch(&app, &process_id, &task_id, &e);
+            return Err(e);
+        }
+    };
+    println!("{} command: {:?}", agent_name, cmd);
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            // Log detailed error information
+            println!("Failed to spawn {} process: {:?}", agent_name, e);
+            println!("Error kind: {:?}", e.kind());
+            println!("Current working directory: {:?}", std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("unknown")));
+            println!("Environment PATH: {:?}", std::env::var("PATH").unwrap_or_else(|_| "not found".to_string()));
+
+            let error = format!("Failed to spawn {} process: {}", agent_name, e);
+            fail_launch(&app, &process_id, &task_id, &error);
+            return Err(error);
+        }
+    };
+    println!("{} process spawned successfully with PID: {:?}", agent_name, child.id());
+
+    // Write the prompt to stdin; close it unless permission answers still have to go through it
+    if spec.uses_stdin(backend.as_ref()) {
+        if let Some(mut stdin_writer) = child.stdin.take() {
+            use std::io::Write;
+            if let Err(e) = stdin_writer.write_all(backend.stdin_prompt(&spec).as_bytes()) {
+                println!("Failed to write prompt to {} stdin: {}", agent_name, e);
             }
+            if spec.route_permissions {
+                let _ = stdin_writer.flush();
+                get_child_stdins().lock().unwrap().insert(process_id.clone(), stdin_writer);
+            }
+            // Otherwise stdin_writer is dropped here, closing the pipe
+        }
+    }
 
-            // stdout reader (handle possible multi-line JSON)
-            let process_id_stdout = process_id.clone();
-            let processes_stdout = get_processes().clone();
-            let app_handle_stdout = app.clone();
-            thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                let mut buf = String::new();
-                for line in reader.lines() {
-                    if let Ok(line_content) = line {
-                        println!("Codex stdout: {}", line_content);
-                        let trimmed = line_content.trim();
-                        // append to buffer to allow multi-line JSON
-                        if !trimmed.is_empty() {
-                            if !buf.is_empty() { buf.push('\n'); }
-                            buf.push_str(trimmed);
-                        }
+    // Take ownership of stdout and stderr
+    let (stdout, stderr) = match (child.stdout.take(), child.stderr.take()) {
+        (Some(stdout), Some(stderr)) => (stdout, stderr),
+        _ => {
+            let error = format!("Failed to capture the output of the {} process", agent_name);
+            abandon_launch(&app, &process_id, &task_id, child, &error);
+            return Err(error);
+        }
+    };
 
-                        // Store raw output
-                        {
-                            let mut map = processes_stdout.lock().unwrap();
-                            if let Some(proc) = map.get_mut(&process_id_stdout) {
+    // Store the child process
+    {
+        let child_processes = get_child_processes();
+        let mut map = child_processes.lock().unwrap();
+        map.insert(process_id.clone(), child);
+    }
+
+    // Update process status to running
+    {
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        if let Some(proc) = map.get_mut(&process_id) {
+            proc.status = "running".to_string();
+        }
+    }
+    emit_process_status(&app, &process_id, &task_id, "running");
+
+    // Spawn thread to read stdout
+    let process_id_stdout = process_id.clone();
+    let processes_stdout = get_processes().clone();
+    let app_handle_stdout = app.clone();
+    let backend_stdout = backend.clone();
+    let agent_name_stdout = agent_name.clone();
+    thread::spawn(move || {
+        let reader = BufReader::new(stdout);
+        let mut buffer = String::new();
+        // Id the current run of text deltas is streamed under
+        let mut streaming_id: Option<String> = None;
+        for line in reader.lines() {
+            match line {
+                Ok(line_content) => {
+                    println!("{} stdout: {}", agent_name_stdout, line_content);
+
+                    // Store raw output
+                    let was_stalled = {
+                        let mut map = processes_stdout.lock().unwrap();
+                        match map.get_mut(&process_id_stdout) {
+                            Some(proc) => {
                                 proc.raw_output.push(line_content.clone());
+                                proc.last_output_at = Some(get_timestamp());
+                                proc.status == "stalled"
                             }
+                            None => false,
                         }
+                    };
+                    if was_stalled {
+                        set_live_status(&app_handle_stdout, &process_id_stdout, "running");
+                    }
 
-                        // Handle multiple JSON objects on the same line
-                        let json_objects = split_json_objects(trimmed);
-                        let objects_found = !json_objects.is_empty();
-                        
-                        for json_str in json_objects {
-                            if let Some(message) = parse_codex_output(&json_str) {
-                                {
-                                    let mut map = processes_stdout.lock().unwrap();
-                                    if let Some(proc) = map.get_mut(&process_id_stdout) {
-                                        proc.messages.push(message.clone());
-                                        println!("Codex message stored. Total messages: {}", proc.messages.len());
-                                        
-                                        // Emit Tauri event for real-time updates (fixed to match Claude pattern)
-                                        let message_payload = serde_json::json!({
-                                            "process_id": process_id_stdout,
-                                            "task_id": proc.task_id,
-                                            "message": message
-                                        });
-                                        
-                                        match app_handle_stdout.emit("agent_message_update", message_payload.clone()) {
-                                            Ok(_) => println!("✅ Emitted Codex agent_message_update event for process {}", process_id_stdout),
-                                            Err(e) => println!("❌ Failed to emit Codex event: {:?}", e)
-                                        };
-                                        
-                                        // Also broadcast to HTTP clients
-                                        crate::web::broadcast_to_http("agent_message_update", message_payload);
-                                    }
-                                }
+                    // Stream partial text; the finished message is stored when it arrives
+                    if let Some(delta) = backend_stdout.parse_delta(&line_content) {
+                        if !delta.is_empty() {
+                            let task_id = processes_stdout.lock().unwrap()
+                                .get(&process_id_stdout)
+                                .map(|proc| proc.task_id.clone());
+                            if let Some(task_id) = task_id {
+                                let message_id = streaming_id.get_or_insert_with(generate_message_id).clone();
+                                emit_message_delta(&app_handle_stdout, &process_id_stdout, &task_id, &message_id, &delta);
                             }
                         }
-                        
-                        // If no JSON objects found, try accumulated buffer
-                        if !objects_found && !buf.is_empty() {
-                            if let Some(message) = parse_codex_output(&buf) {
-                                buf.clear();
-                                {
-                                    let mut map = processes_stdout.lock().unwrap();
-                                    if let Some(proc) = map.get_mut(&process_id_stdout) {
-                                        proc.messages.push(message.clone());
-                                        println!("Codex message stored from buffer. Total messages: {}", proc.messages.len());
-                                        
-                                        // Emit Tauri event for real-time updates (fixed to match Claude pattern)
-                                        let message_payload = serde_json::json!({
-                                            "process_id": process_id_stdout,
-                                            "task_id": proc.task_id,
-                                            "message": message
-                                        });
-                                        
-                                        match app_handle_stdout.emit("agent_message_update", message_payload.clone()) {
-                                            Ok(_) => println!("✅ Emitted Codex agent_message_update event from buffer for process {}", process_id_stdout),
-                                            Err(e) => println!("❌ Failed to emit Codex buffer event: {:?}", e)
-                                        };
-                                        
-                                        // Also broadcast to HTTP clients
-                                        crate::web::broadcast_to_http("agent_message_update", message_payload);
-                                    }
+                        if backend_stdout.ends_stream(&line_content) {
+                            streaming_id = None;
+                        }
+                        continue;
+                    }
+
+                    // Parse and store structured messages
+                    for mut message in backend_stdout.parse_line(&line_content, &mut buffer) {
+                        // Any other message closes the stream, so later text starts a new one
+                        let streamed_id = streaming_id.take();
+                        if backend_stdout.completes_stream(&message) {
+                            if let Some(message_id) = streamed_id {
+                                message.id = message_id;
+                            }
+                        }
+                        let task_id = {
+                            let mut map = processes_stdout.lock().unwrap();
+                            match map.get_mut(&process_id_stdout) {
+                                Some(proc) => {
+                                    backend_stdout.apply_message(proc, &message);
+                                    proc.messages.push(message.clone());
+                                    println!("Message stored. Total messages: {}", proc.messages.len());
+                                    proc.task_id.clone()
                                 }
+                                None => continue,
                             }
+                        };
+
+                        // Write through to disk before notifying the UI
+                        persist_message(&app_handle_stdout, &task_id, &process_id_stdout, &message);
+                        emit_message_update(&app_handle_stdout, &process_id_stdout, &task_id, &message);
+
+                        // Learnings the agent wants added to the project notes wait for review
+                        if let Some(notice) = notes::collect_proposals(&app_handle_stdout, &task_id, &process_id_stdout, &message) {
+                            if let Some(proc) = processes_stdout.lock().unwrap().get_mut(&process_id_stdout) {
+                                proc.messages.push(notice.clone());
+                            }
+                            persist_message(&app_handle_stdout, &task_id, &process_id_stdout, &notice);
+                            emit_message_update(&app_handle_stdout, &process_id_stdout, &task_id, &notice);
+                        }
+
+                        // The agent is blocked until the board answers
+                        if message.message_type == "permission_request" {
+                            set_live_status(&app_handle_stdout, &process_id_stdout, "awaiting_input");
                         }
+                        if backend_stdout.ends_turn(&message) {
+                            get_child_stdins().lock().unwrap().remove(&process_id_stdout);
+                        }
+                        enforce_budget(&app_handle_stdout, &process_id_stdout);
                     }
                 }
-            });
+                Err(e) => {
+                    println!("Error reading {} stdout: {}", agent_name_stdout, e);
+                    break;
+                }
+            }
+        }
 
-            // stderr reader
-            let process_id_stderr = process_id.clone();
-            let processes_stderr = get_processes().clone();
-            thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines() {
-                    if let Ok(line_content) = line {
-                        println!("Codex stderr: {}", line_content);
-                        let error_message = AgentMessage {
-                            id: generate_message_id(),
-                            sender: "system".to_string(),
-                            content: line_content,
-                            timestamp: get_timestamp(),
-                            message_type: "error".to_string(),
-                            metadata: None,
-                        };
+        println!("{} stdout reader thread finished for process {}", agent_name_stdout, process_id_stdout);
+    });
+
+    // Spawn thread to read stderr (error messages)
+    let process_id_stderr = process_id.clone();
+    let processes_stderr = get_processes().clone();
+    let app_handle_stderr = app.clone();
+    let agent_name_stderr = agent_name.clone();
+    thread::spawn(move || {
+        let reader = BufReader::new(stderr);
+        for line in reader.lines() {
+            match line {
+                Ok(line_content) => {
+                    println!("{} stderr: {}", agent_name_stderr, line_content);
+
+                    // Store error as a message
+                    let error_message = AgentMessage {
+                        id: generate_message_id(),
+                        sender: "system".to_string(),
+                        content: line_content,
+                        timestamp: get_timestamp(),
+                        message_type: "error".to_string(),
+                        metadata: None,
+                    };
+
+                    let (task_id, was_stalled) = {
                         let mut map = processes_stderr.lock().unwrap();
-                        if let Some(proc) = map.get_mut(&process_id_stderr) {
-                            proc.messages.push(error_message);
+                        match map.get_mut(&process_id_stderr) {
+                            Some(proc) => {
+                                proc.messages.push(error_message.clone());
+                                proc.last_output_at = Some(get_timestamp());
+                                (proc.task_id.clone(), proc.status == "stalled")
+                            }
+                            None => continue,
                         }
+                    };
+                    persist_message(&app_handle_stderr, &task_id, &process_id_stderr, &error_message);
+                    if was_stalled {
+                        set_live_status(&app_handle_stderr, &process_id_stderr, "running");
                     }
                 }
-            });
+                Err(e) => {
+                    println!("Error reading {} stderr: {}", agent_name_stderr, e);
+                    break;
+                }
+            }
+        }
 
-            // monitor completion
-            let process_id_monitor = process_id.clone();
-            let processes_monitor = get_processes().clone();
-            let child_processes_monitor = get_child_processes().clone();
-            let app_handle_monitor = app.clone();
-            thread::spawn(move || {
-                std::thread::sleep(std::time::Duration::from_secs(1));
-                let mut should_wait = true;
-                while should_wait {
-                    {
-                        let mut child_map = child_processes_monitor.lock().unwrap();
-                        if let Some(child) = child_map.get_mut(&process_id_monitor) {
-                            match child.try_wait() {
-                                Ok(Some(status)) => {
-                                    println!("Codex process {} finished with status: {}", process_id_monitor, status);
-                                    should_wait = false;
-                                    let mut proc_map = processes_monitor.lock().unwrap();
-                                    if let Some(proc) = proc_map.get_mut(&process_id_monitor) {
-                                        if proc.status == "running" {
-                                            proc.status = if status.success() { "completed".to_string() } else { "failed".to_string() };
-                                            proc.end_time = Some(get_timestamp());
-                                        }
-                                    }
-                                    // Emit status update event with task_id
-                                    let final_status = if status.success() { "completed" } else { "failed" };
-                                    let task_id = proc_map.get(&process_id_monitor)
-                                        .map(|p| p.task_id.clone())
-                                        .unwrap_or_else(|| "unknown".to_string());
-
-                                    let status_payload = serde_json::json!({
-                                        "process_id": process_id_monitor,
-                                        "task_id": task_id,
-                                        "status": final_status
-                                    });
-
-                                    match app_handle_monitor.emit("agent_process_status", status_payload.clone()) {
-                                        Ok(_) => println!("✅ Emitted Codex agent_process_status event: {} {} for task {}", process_id_monitor, final_status, task_id),
-                                        Err(e) => println!("❌ Failed to emit Codex completion status event: {:?}", e)
-                                    };
-
-                                    // Also broadcast to HTTP clients
-                                    crate::web::broadcast_to_http("agent_process_status", status_payload);
-                                    child_map.remove(&process_id_monitor);
-                                }
-                                Ok(None) => {
-                                    std::thread::sleep(std::time::Duration::from_millis(500));
-                                }
-                                Err(e) => {
-                                    println!("Error checking process status: {}", e);
-                                    should_wait = false;
-                                }
-                            }
-                        } else {
+        println!("{} stderr reader thread finished for process {}", agent_name_stderr, process_id_stderr);
+    });
+
+    // Spawn thread to monitor process completion
+    let process_id_monitor = process_id.clone();
+    let processes_monitor = get_processes().clone();
+    let child_processes_monitor = get_child_processes().clone();
+    let app_handle_monitor = app.clone();
+    let stall_settings = settings::load_agent_settings(&app);
+    let stall_timeout = stall_settings.stall_timeout_secs.filter(|secs| *secs > 0);
+    let retry_on_stall = stall_settings.retry_on_stall;
+    thread::spawn(move || {
+        // Wait a bit for the process to potentially finish
+        std::thread::sleep(std::time::Duration::from_secs(1));
+
+        let mut should_wait = true;
+        while should_wait {
+            let finished = {
+                let mut child_map = child_processes_monitor.lock().unwrap();
+                match child_map.get_mut(&process_id_monitor) {
+                    Some(child) => match child.try_wait() {
+                        Ok(Some(status)) => {
+                            println!("{} process {} finished with status: {}", agent_name, process_id_monitor, status);
+                            child_map.remove(&process_id_monitor);
+                            Some(status)
+                        }
+                        Ok(None) => None,
+                        Err(e) => {
+                            println!("Error checking process status: {}", e);
                             should_wait = false;
+                            None
                         }
+                    },
+                    None => {
+                        should_wait = false;
+                        None
                     }
                 }
-            });
+            };
+
+            match finished {
+                Some(status) => {
+                    should_wait = false;
+
+                    // Update process status; a killed process keeps its status
+                    get_child_stdins().lock().unwrap().remove(&process_id_monitor);
+                    let natural_status = if status.success() { "completed" } else { "failed" };
+                    let (task_id, final_status) = {
+                        let mut proc_map = processes_monitor.lock().unwrap();
+                        match proc_map.get_mut(&process_id_monitor) {
+                            Some(proc) => {
+                                if is_live_status(&proc.status) {
+                                    proc.status = natural_status.to_string();
+                                    proc.end_time = Some(get_timestamp());
+                                }
+                                proc.exit_reason = Some(termination::exit_reason(&status));
+                                (proc.task_id.clone(), proc.status.clone())
+                            }
+                            None => ("unknown".to_string(), natural_status.to_string()),
+                        }
+                    };
 
-            Ok(process_id)
-        }
-        Err(e) => {
-            println!("Failed to spawn Codex process: {:?}", e);
-            let processes = get_processes();
-            let mut map = processes.lock().unwrap();
-            if let Some(proc) = map.get_mut(&process_id) {
-                proc.status = "failed".to_string();
-                proc.end_time = Some(get_timestamp());
+                    emit_process_status(&app_handle_monitor, &process_id_monitor, &task_id, &final_status);
+                    finish_run(&app_handle_monitor, &process_id_monitor);
+                    schedule_retry(&app_handle_monitor, &process_id_monitor);
+                    complete_handoff(&app_handle_monitor, &process_id_monitor);
+                }
+                None if should_wait => {
+                    // Process still running, check the time budget and output stalls, then wait a bit more
+                    enforce_budget(&app_handle_monitor, &process_id_monitor);
+                    if let Some(timeout) = stall_timeout {
+                        detect_stall(&app_handle_monitor, &process_id_monitor, timeout, retry_on_stall);
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(500));
+                }
+                None => {}
             }
-            Err(format!("Failed to spawn Codex process: {}", e))
         }
-    }
+
+        println!("Process monitor thread finished for process {}", process_id_monitor);
+    });
+
+    println!("Process {} started successfully with monitoring threads", process_id);
+    Ok(())
 }
 
+
 /// Gets all processes
 pub fn get_all_processes() -> HashMap<String, AgentProcess> {
     let processes = get_processes();
@@ -1568,6 +1843,12 @@ pub fn get_all_processes() -> HashMap<String, AgentProcess> {
     map.clone()
 }
 
+/// Token and cost usage of a project (or all projects) over a range such as
+/// `today`, `7d`, `30d` or `all`
+pub fn get_usage_summary(app: &tauri::AppHandle, project_id: Option<&str>, range: &str) -> accounting::UsageSummary {
+    accounting::usage_summary(app, &get_all_processes(), project_id, range)
+}
+
 /// Gets a specific process by ID
 pub fn get_process_by_id(process_id: &str) -> Option<AgentProcess> {
     let processes = get_processes();
@@ -1584,60 +1865,42 @@ pub fn get_process_messages(process_id: &str) -> Vec<AgentMessage> {
         .unwrap_or_default()
 }
 
-/// Sends a new message to an existing process (spawns new process with context)
+/// Sends a new message to an existing process (spawns a new process that resumes
+/// the session natively, or with replayed context when no session id exists)
 pub fn send_message_to_process(
     app: tauri::AppHandle,
     process_id: &str,
     message: String,
     worktree_path: String,
+    choice: model::ModelChoice,
+    images: Vec<String>,
 ) -> Result<String, String> {
-    let processes = get_processes();
-
-    // Get existing process and its context
-    let (context, agent_kind, task_id) = {
-        let map = processes.lock().unwrap();
-        if let Some(proc) = map.get(process_id) {
-            // Build context from existing messages
-            // Limit context to last 20 messages to avoid huge prompts
-            let take_last = 20usize;
-            let start = proc.messages.len().saturating_sub(take_last);
-            let context_messages: Vec<String> = proc.messages.iter()
-                .skip(start)
-                .map(|msg| format!("{}: {}", msg.sender, msg.content))
-                .collect();
-            let ctx = Some(context_messages.join("\n"));
-            (ctx, proc.kind.clone(), proc.task_id.clone())
-        } else {
-            return Err("Process not found".to_string());
-        }
-    };
-
-    // Spawn new process with context, matching the agent kind used previously
-    let new_process_id = match agent_kind {
-        AgentKind::Claude => spawn_claude_process(
-            app,
-            task_id,
-            message,
-            worktree_path,
-            context,
-        )?,
-        AgentKind::Codex => spawn_codex_process(
-            app,
-            task_id,
-            message,
-            worktree_path,
-            context,
-        )?,
-    };
+    let parent = get_process_by_id(process_id).ok_or_else(|| "Process not found".to_string())?;
+
+    // Spawn new process matching the agent kind used previously
+    let backend = resolve_backend_with_settings(&app, parent.kind.as_str());
+    let mut continuation = Continuation::from_parent(&parent, backend.as_ref());
+    continuation.attachments = attachments::Attachments::from_message(&message, &worktree_path, images);
+    let new_process_id = spawn_agent_process(
+        app.clone(),
+        backend,
+        parent.task_id.clone(),
+        message,
+        worktree_path,
+        continuation,
+        choice,
+    )?;
 
     // Mark old process as completed
     {
+        let processes = get_processes();
         let mut map = processes.lock().unwrap();
         if let Some(proc) = map.get_mut(process_id) {
             proc.status = "completed".to_string();
             proc.end_time = Some(get_timestamp());
         }
     }
+    persist_process(&app, process_id);
 
     Ok(new_process_id)
 }
@@ -1649,75 +1912,302 @@ pub fn send_message_with_profile(
     message: String,
     worktree_path: String,
     profile: &str,
+    choice: model::ModelChoice,
+    images: Vec<String>,
 ) -> Result<String, String> {
-    let processes = get_processes();
+    let parent = get_process_by_id(base_process_id).ok_or_else(|| "Process not found".to_string())?;
+
+    // Resolve the profile through the backend registry; switching agents falls back to text replay
+    let backend = resolve_backend_with_settings(&app, profile);
+    let mut continuation = Continuation::from_parent(&parent, backend.as_ref());
+    continuation.attachments = attachments::Attachments::from_message(&message, &worktree_path, images);
+    spawn_agent_process(
+        app,
+        backend,
+        parent.task_id.clone(),
+        message,
+        worktree_path,
+        continuation,
+        choice,
+    )
+}
 
-    // Build context from base process
-    let (context, task_id) = {
-        let map = processes.lock().unwrap();
-        if let Some(proc) = map.get(base_process_id) {
-            let take_last = 20usize;
-            let start = proc.messages.len().saturating_sub(take_last);
-            let context_messages: Vec<String> = proc.messages.iter()
-                .skip(start)
-                .map(|msg| format!("{}: {}", msg.sender, msg.content))
-                .collect();
-            (Some(context_messages.join("\n")), proc.task_id.clone())
-        } else {
-            return Err("Process not found".to_string());
+/// Continues a conversation on another profile in handoff mode: the outgoing
+/// agent is first asked for a summary (see `handoff`), and the new agent starts
+/// with it once that run ends. Returns the id of the summary run.
+pub fn handoff_to_profile(
+    app: tauri::AppHandle,
+    base_process_id: &str,
+    message: String,
+    worktree_path: String,
+    profile: &str,
+    choice: model::ModelChoice,
+    images: Vec<String>,
+) -> Result<String, String> {
+    let parent = get_process_by_id(base_process_id).ok_or_else(|| "Process not found".to_string())?;
+
+    // The summary comes from the agent that did the work, with its own model
+    let backend = resolve_backend_with_settings(&app, parent.kind.as_str());
+    let continuation = Continuation::from_parent(&parent, backend.as_ref());
+    let summary_choice = model::ModelChoice::new(parent.model.clone(), parent.reasoning_effort.clone());
+    let summary_id = spawn_agent_process(
+        app,
+        backend,
+        parent.task_id.clone(),
+        handoff::SUMMARY_PROMPT.to_string(),
+        worktree_path.clone(),
+        continuation,
+        summary_choice,
+    )?;
+    handoff::register(&summary_id, handoff::PendingHandoff {
+        from_process_id: parent.id.clone(),
+        profile: profile.to_string(),
+        message,
+        worktree_path,
+        choice,
+        images,
+    });
+    println!("Handoff from process {} to {}: waiting for summary run {}", parent.id, profile, summary_id);
+    Ok(summary_id)
+}
+
+/// Starts the incoming agent of a handoff once its summary run has ended. A
+/// killed summary run cancels the handoff; a failed one falls back to a
+/// summary built from the transcript.
+fn complete_handoff(app: &tauri::AppHandle, process_id: &str) {
+    let Some(pending) = handoff::take(process_id) else { return; };
+    let Some(summary_run) = get_process_by_id(process_id) else { return; };
+    if matches!(summary_run.status.as_str(), "killed" | "cancelled") {
+        println!("Handoff summary run {} was stopped; handoff cancelled", process_id);
+        return;
+    }
+    let Some(from) = get_process_by_id(&pending.from_process_id) else { return; };
+    let (summary, from_transcript) = match handoff::summary_of(&summary_run) {
+        Some(summary) => (summary, false),
+        None => (handoff::Handoff::from_transcript(&from), true),
+    };
+
+    let backend = resolve_backend_with_settings(app, &pending.profile);
+    let handoff_message = handoff::handoff_message(&summary, &from, backend.name(), from_transcript);
+    let continuation = Continuation {
+        parent_process_id: Some(process_id.to_string()),
+        context: Some(handoff_message.content.clone()),
+        attachments: attachments::Attachments::from_message(&pending.message, &pending.worktree_path, pending.images),
+        ..Continuation::default()
+    };
+    let new_id = match spawn_agent_process(app.clone(), backend, from.task_id.clone(), pending.message, pending.worktree_path, continuation, pending.choice) {
+        Ok(id) => id,
+        Err(e) => {
+            println!("Failed to start {} after handoff: {}", pending.profile, e);
+            return;
         }
     };
 
-    // Map profile to AgentKind
-    let which = profile.trim().to_lowercase();
-    let new_process_id = match which.as_str() {
-        "codex" | "chat-codex" | "chatgpt-codex" => spawn_codex_process(
-            app,
-            task_id,
-            message,
-            worktree_path,
-            context,
-        )?,
-        _ => spawn_claude_process(
-            app,
-            task_id,
-            message,
-            worktree_path,
-            context,
-        )?,
+    // The summary opens the new run's transcript, ahead of the user's message
+    let messages = {
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        map.get_mut(&new_id).map(|proc| {
+            proc.messages.insert(0, handoff_message.clone());
+            proc.messages.clone()
+        })
     };
+    if let Some(messages) = messages {
+        if let Err(e) = persistence::save_messages(app, &from.task_id, &new_id, &messages) {
+            println!("Failed to persist handoff message for process {}: {}", new_id, e);
+        }
+    }
+    emit_message_update(app, &new_id, &from.task_id, &handoff_message);
+    println!("Handoff from process {} continued as {} process {}", from.id, pending.profile, new_id);
+}
 
-    Ok(new_process_id)
+/// True if the request message already has a `permission_response`
+fn is_permission_answered(proc: &AgentProcess, request_id: &str) -> bool {
+    proc.messages.iter().any(|m| {
+        m.message_type == "permission_response"
+            && m.metadata.as_ref()
+                .and_then(|meta| meta.get("request_message_id"))
+                .and_then(|v| v.as_str()) == Some(request_id)
+    })
 }
 
-/// Kills a running process
-pub fn kill_process(process_id: &str) -> Result<(), String> {
-    // First, try to kill the actual child process
+/// Answers a `permission_request` message of a process waiting for input. The
+/// answer is written to the process's stdin and recorded as a
+/// `permission_response` message; the process returns to `running` once no
+/// requests are left open.
+pub fn respond_to_permission(
+    app: &tauri::AppHandle,
+    process_id: &str,
+    message_id: &str,
+    approved: bool,
+) -> Result<(), String> {
+    let proc = get_process_by_id(process_id).ok_or_else(|| "Process not found".to_string())?;
+    let request = proc.messages.iter()
+        .find(|m| m.id == message_id && m.message_type == "permission_request")
+        .cloned()
+        .ok_or_else(|| "Permission request not found".to_string())?;
+    if is_permission_answered(&proc, message_id) {
+        return Err("Permission request already answered".to_string());
+    }
+
+    let backend = backends::resolve_backend(proc.kind.as_str());
+    let line = backend.permission_response(&request, approved)
+        .ok_or_else(|| format!("{} does not accept permission answers", backend.display_name()))?;
     {
-        let child_processes = get_child_processes();
-        let mut child_map = child_processes.lock().unwrap();
-        if let Some(mut child) = child_map.remove(process_id) {
-            match child.kill() {
-                Ok(_) => println!("Successfully killed child process {}", process_id),
-                Err(e) => println!("Failed to kill child process {}: {}", process_id, e),
+        use std::io::Write;
+        let mut stdins = get_child_stdins().lock().unwrap();
+        let stdin = stdins.get_mut(process_id).ok_or_else(|| "Process is no longer waiting for input".to_string())?;
+        stdin.write_all(line.as_bytes())
+            .and_then(|_| stdin.flush())
+            .map_err(|e| format!("Failed to send answer to process: {}", e))?;
+    }
+
+    let decision = if approved { "approved" } else { "denied" };
+    let response = AgentMessage {
+        id: generate_message_id(),
+        sender: "user".to_string(),
+        content: format!("{}: {}", if approved { "Approved" } else { "Denied" }, request.content),
+        timestamp: get_timestamp(),
+        message_type: "permission_response".to_string(),
+        metadata: Some(serde_json::json!({
+            "request_message_id": message_id,
+            "decision": decision
+        })),
+    };
+    let still_waiting = {
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        match map.get_mut(process_id) {
+            Some(proc) => {
+                proc.messages.push(response.clone());
+                has_open_permission_request(proc)
             }
-            // Wait for the process to actually terminate
-            let _ = child.wait();
+            None => false,
         }
+    };
+    println!("Permission request {} of process {} {}", message_id, process_id, decision);
+
+    persist_message(app, &proc.task_id, process_id, &response);
+    emit_message_update(app, process_id, &proc.task_id, &response);
+    if !still_waiting {
+        set_live_status(app, process_id, "running");
     }
+    Ok(())
+}
 
-    // Update process status
-    let processes = get_processes();
-    let mut map = processes.lock().unwrap();
+/// Whether a process has a permission request nobody has answered yet
+fn has_open_permission_request(proc: &AgentProcess) -> bool {
+    proc.messages.iter()
+        .filter(|m| m.message_type == "permission_request")
+        .any(|m| !is_permission_answered(proc, &m.id))
+}
 
-    if let Some(proc) = map.get_mut(process_id) {
-        proc.status = "killed".to_string();
-        proc.end_time = Some(get_timestamp());
-        println!("Process {} marked as killed", process_id);
-        Ok(())
-    } else {
-        Err("Process not found".to_string())
+/// Freezes a live process and everything it started (SIGSTOP on its process
+/// group) and marks it `paused`
+pub fn pause_process(app: &tauri::AppHandle, process_id: &str) -> Result<(), String> {
+    let proc = get_process_by_id(process_id).ok_or_else(|| "Process not found".to_string())?;
+    if !is_live_status(&proc.status) || proc.status == "paused" {
+        return Err(format!("Process is {}, not running", proc.status));
     }
+    let pid = get_child_processes().lock().unwrap().get(process_id).map(|child| child.id())
+        .ok_or_else(|| "Process has no running child".to_string())?;
+    termination::pause_group(pid, true)?;
+
+    {
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        if let Some(proc) = map.get_mut(process_id) {
+            proc.status = "paused".to_string();
+            proc.paused_at = Some(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
+        }
+    }
+    println!("Process {} paused", process_id);
+    emit_process_status(app, process_id, &proc.task_id, "paused");
+    Ok(())
+}
+
+/// Continues a paused process (SIGCONT on its process group). It returns to
+/// `awaiting_input` if a permission request is still open, otherwise `running`.
+pub fn resume_process(app: &tauri::AppHandle, process_id: &str) -> Result<(), String> {
+    let proc = get_process_by_id(process_id).ok_or_else(|| "Process not found".to_string())?;
+    if proc.status != "paused" {
+        return Err(format!("Process is {}, not paused", proc.status));
+    }
+    let pid = get_child_processes().lock().unwrap().get(process_id).map(|child| child.id())
+        .ok_or_else(|| "Process has no running child".to_string())?;
+    termination::pause_group(pid, false)?;
+
+    let status = if has_open_permission_request(&proc) { "awaiting_input" } else { "running" };
+    {
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        if let Some(proc) = map.get_mut(process_id) {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+            proc.status = status.to_string();
+            proc.paused_secs += proc.paused_at.take().map(|at| now.saturating_sub(at)).unwrap_or(0);
+            proc.resumed_at = Some(now);
+        }
+    }
+    println!("Process {} resumed", process_id);
+    emit_process_status(app, process_id, &proc.task_id, status);
+    Ok(())
+}
+
+/// Kills a running process. A queued process is taken off the queue and
+/// marked `cancelled` instead.
+pub fn kill_process(app: &tauri::AppHandle, process_id: &str) -> Result<(), String> {
+    if scheduler::cancel(process_id) {
+        let task_id = {
+            let processes = get_processes();
+            let mut map = processes.lock().unwrap();
+            let proc = map.get_mut(process_id).ok_or_else(|| "Process not found".to_string())?;
+            proc.status = "cancelled".to_string();
+            proc.end_time = Some(get_timestamp());
+            proc.queue_position = None;
+            proc.task_id.clone()
+        };
+        println!("Queued process {} cancelled", process_id);
+        // A queued summary run never exits, so its handoff is dropped here
+        if handoff::cancel(process_id) {
+            println!("Handoff waiting on process {} cancelled", process_id);
+        }
+        emit_process_status(app, process_id, &task_id, "cancelled");
+        sync_queue_positions(app);
+        return Ok(());
+    }
+
+    get_child_stdins().lock().unwrap().remove(process_id);
+
+    // Update process status first so the exit monitor keeps it as `killed`
+    let task_id = {
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        match map.get_mut(process_id) {
+            Some(proc) => {
+                proc.status = "killed".to_string();
+                proc.end_time = Some(get_timestamp());
+                println!("Process {} marked as killed", process_id);
+                proc.task_id.clone()
+            }
+            None => return Err("Process not found".to_string()),
+        }
+    };
+
+    // Stop the child and its process group in the background; the exit monitor
+    // reaps it and reports the exit reason with the final status event
+    let child_pid = get_child_processes().lock().unwrap().get(process_id).map(|child| child.id());
+    match child_pid {
+        Some(pid) => {
+            let stopped_id = process_id.to_string();
+            thread::spawn(move || {
+                termination::stop_group(pid);
+                println!("Stop sequence finished for process {}", stopped_id);
+            });
+            persist_process(app, process_id);
+        }
+        None => emit_process_status(app, process_id, &task_id, "killed"),
+    }
+    Ok(())
 }
 
 /// Gets process list summary for UI
@@ -1726,13 +2216,54 @@ pub fn get_process_list() -> Vec<serde_json::Value> {
     let map = processes.lock().unwrap();
 
     map.values()
-        .map(|proc| serde_json::json!({
-            "id": proc.id,
-            "task_id": proc.task_id,
-            "status": proc.status,
-            "start_time": proc.start_time,
-            "message_count": proc.messages.len(),
-            "kind": match proc.kind { AgentKind::Claude => "claude", AgentKind::Codex => "codex" }
-        }))
+        .map(|proc| {
+            let mut summary = persistence::process_summary(proc);
+            // What the run's cost and token limits are measured against
+            if let Some(budget) = &proc.budget {
+                summary["budget_spent"] = serde_json::json!(budget.spent(proc, &map));
+            }
+            summary
+        })
         .collect()
 }
+
+/// Restores the process registry from disk on startup. Processes that were
+/// still starting, running or awaiting input have no live child any more, so they are marked
+/// `interrupted` and a system message is appended to their transcript.
+pub fn rehydrate_processes(app: &tauri::AppHandle) -> usize {
+    let restored = persistence::load_processes(app);
+    let mut count = 0;
+
+    for mut proc in restored {
+        let orphaned = matches!(proc.status.as_str(), "queued" | "starting") || is_live_status(&proc.status);
+        if orphaned {
+            let notice = AgentMessage {
+                id: generate_message_id(),
+                sender: "system".to_string(),
+                content: "Process interrupted: the app exited while the agent was running".to_string(),
+                timestamp: get_timestamp(),
+                message_type: "interrupted".to_string(),
+                metadata: None,
+            };
+            proc.status = "interrupted".to_string();
+            proc.end_time = Some(get_timestamp());
+            proc.queue_position = None;
+            persist_message(app, &proc.task_id, &proc.id, &notice);
+            proc.messages.push(notice);
+            if let Err(e) = persistence::save_process(app, &proc) {
+                println!("Failed to persist interrupted process {}: {}", proc.id, e);
+            }
+            println!("Process {} was left running; marked as interrupted", proc.id);
+        }
+
+        let processes = get_processes();
+        let mut map = processes.lock().unwrap();
+        if !map.contains_key(&proc.id) {
+            map.insert(proc.id.clone(), proc);
+            count += 1;
+        }
+    }
+
+    println!("Rehydrated {} agent processes from disk", count);
+    count
+}
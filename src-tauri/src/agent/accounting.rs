@@ -0,0 +1,268 @@
+//! Token and cost accounting across processes, tasks and projects.
+//!
+//! Every run's normalised usage is written to `agent_usage.json` whenever its
+//! process entry is persisted, so spend is kept even if the process registry is
+//! pruned or rewritten by the UI. Summaries combine that ledger with the live
+//! processes in memory and group it by task, agent kind, day and project.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use tauri_plugin_store::StoreExt;
+
+use super::{budget::Spend, records, AgentProcess};
+
+const USAGE_FILE: &str = "agent_usage.json";
+
+static LEDGER_LOCK: Mutex<()> = Mutex::new(());
+
+/// Usage of one run as stored in the ledger
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageRecord {
+    pub process_id: String,
+    pub task_id: String,
+    pub project_id: Option<String>,
+    pub kind: String,
+    /// Epoch seconds, as `AgentProcess.start_time`
+    pub start_time: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_tokens: u64,
+    /// As reported by the CLI (Claude), otherwise estimated from the tokens
+    pub cost_usd: f64,
+}
+
+impl UsageRecord {
+    fn from_process(proc: &AgentProcess, project_id: Option<String>) -> Self {
+        UsageRecord {
+            process_id: proc.id.clone(),
+            task_id: proc.task_id.clone(),
+            project_id,
+            kind: proc.kind.as_str().to_string(),
+            start_time: proc.start_time.clone(),
+            input_tokens: proc.usage.input_tokens,
+            output_tokens: proc.usage.output_tokens,
+            cached_tokens: proc.usage.cached_tokens,
+            cost_usd: Spend::of(proc).cost_usd,
+        }
+    }
+
+    fn start_secs(&self) -> i64 {
+        self.start_time.parse::<i64>().unwrap_or(0)
+    }
+
+    /// Local calendar day the run started on (`YYYY-MM-DD`)
+    fn day(&self) -> String {
+        Local
+            .timestamp_opt(self.start_secs(), 0)
+            .single()
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+}
+
+/// Summed usage of a group of runs
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageTotals {
+    pub runs: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, record: &UsageRecord) {
+        self.runs += 1;
+        self.input_tokens += record.input_tokens;
+        self.output_tokens += record.output_tokens;
+        self.cached_tokens += record.cached_tokens;
+        self.cost_usd += record.cost_usd;
+    }
+}
+
+/// One row of a breakdown: a task, kind, day or project and its totals
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageBucket {
+    pub key: String,
+    pub label: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageSummary {
+    pub project_id: Option<String>,
+    pub range: String,
+    pub totals: UsageTotals,
+    pub by_task: Vec<UsageBucket>,
+    pub by_kind: Vec<UsageBucket>,
+    pub by_day: Vec<UsageBucket>,
+    pub by_project: Vec<UsageBucket>,
+}
+
+/// Earliest start time (epoch seconds) included by a range: `today`, `Nd`
+/// (last N days) or `all`. Unknown ranges are treated as `all`.
+fn range_start(range: &str) -> Option<i64> {
+    let now = Local::now();
+    match range {
+        "today" => now
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+            .map(|t| t.timestamp()),
+        _ => range
+            .strip_suffix('d')
+            .and_then(|days| days.parse::<i64>().ok())
+            .map(|days| now.timestamp() - days * 24 * 60 * 60),
+    }
+}
+
+fn load_ledger(app: &tauri::AppHandle) -> Vec<UsageRecord> {
+    match app.store(USAGE_FILE).ok().and_then(|store| store.get("records")) {
+        Some(val) => serde_json::from_value(val).unwrap_or_default(),
+        None => Vec::new(),
+    }
+}
+
+/// Every run in the ledger
+pub fn load_records(app: &tauri::AppHandle) -> Vec<UsageRecord> {
+    let _guard = LEDGER_LOCK.lock().unwrap();
+    load_ledger(app)
+}
+
+/// Inserts or replaces the ledger entry of a process. Runs that have not used
+/// anything yet are skipped.
+pub fn record_process(app: &tauri::AppHandle, proc: &AgentProcess) -> Result<(), String> {
+    if proc.usage.total() == 0 && proc.total_cost_usd.is_none() {
+        return Ok(());
+    }
+
+    let _guard = LEDGER_LOCK.lock().unwrap();
+    let mut ledger = load_ledger(app);
+    let project_id = proc.project_id.clone()
+        .or_else(|| ledger.iter().find(|r| r.process_id == proc.id).and_then(|r| r.project_id.clone()))
+        .or_else(|| records::project_of(app, &proc.task_id));
+    let record = UsageRecord::from_process(proc, project_id);
+    match ledger.iter_mut().find(|r| r.process_id == proc.id) {
+        Some(existing) => *existing = record,
+        None => ledger.push(record),
+    }
+
+    let store = app.store(USAGE_FILE).map_err(|e| e.to_string())?;
+    store.set("records", serde_json::to_value(&ledger).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())
+}
+
+fn into_buckets(groups: BTreeMap<String, UsageTotals>, label: impl Fn(&str) -> String) -> Vec<UsageBucket> {
+    groups
+        .into_iter()
+        .map(|(key, totals)| UsageBucket { label: label(&key), key, totals })
+        .collect()
+}
+
+/// Usage of a project (or of every project when `project_id` is `None`) over a
+/// range, broken down by task, agent kind, day and project
+pub fn usage_summary(
+    app: &tauri::AppHandle,
+    live: &HashMap<String, AgentProcess>,
+    project_id: Option<&str>,
+    range: &str,
+) -> UsageSummary {
+    let tasks = records::task_index(app);
+    let project_names: HashMap<String, String> = records::load_projects(app)
+        .iter()
+        .filter_map(|p| {
+            let id = p.get("id")?.as_str()?.to_string();
+            let name = p.get("name").and_then(|v| v.as_str()).unwrap_or(&id).to_string();
+            Some((id, name))
+        })
+        .collect();
+
+    // Ledger entries, replaced by the live figures of processes still in memory
+    let mut runs: HashMap<String, UsageRecord> = {
+        let _guard = LEDGER_LOCK.lock().unwrap();
+        load_ledger(app).into_iter().map(|r| (r.process_id.clone(), r)).collect()
+    };
+    for proc in live.values() {
+        let known_project = proc.project_id.clone().or_else(|| runs.get(&proc.id).and_then(|r| r.project_id.clone()));
+        let record = UsageRecord::from_process(proc, known_project);
+        if record.input_tokens + record.output_tokens + record.cached_tokens > 0 || record.cost_usd > 0.0 {
+            runs.insert(proc.id.clone(), record);
+        }
+    }
+
+    let since = range_start(range);
+    let mut summary = UsageSummary {
+        project_id: project_id.map(|s| s.to_string()),
+        range: range.to_string(),
+        ..Default::default()
+    };
+    let mut by_task = BTreeMap::<String, UsageTotals>::new();
+    let mut by_kind = BTreeMap::<String, UsageTotals>::new();
+    let mut by_day = BTreeMap::<String, UsageTotals>::new();
+    let mut by_project = BTreeMap::<String, UsageTotals>::new();
+
+    for mut record in runs.into_values() {
+        if record.project_id.is_none() {
+            record.project_id = tasks.get(&record.task_id).map(|(project, _)| project.clone());
+        }
+        if project_id.is_some() && record.project_id.as_deref() != project_id {
+            continue;
+        }
+        if since.is_some_and(|since| record.start_secs() < since) {
+            continue;
+        }
+
+        summary.totals.add(&record);
+        by_task.entry(record.task_id.clone()).or_default().add(&record);
+        by_kind.entry(record.kind.clone()).or_default().add(&record);
+        by_day.entry(record.day()).or_default().add(&record);
+        by_project
+            .entry(record.project_id.clone().unwrap_or_default())
+            .or_default()
+            .add(&record);
+    }
+
+    summary.by_task = into_buckets(by_task, |id| {
+        tasks.get(id).map(|(_, title)| title.clone()).unwrap_or_else(|| format!("{} (deleted)", id))
+    });
+    summary.by_kind = into_buckets(by_kind, |kind| kind.to_string());
+    summary.by_day = into_buckets(by_day, |day| day.to_string());
+    summary.by_project = into_buckets(by_project, |id| {
+        project_names.get(id).cloned().unwrap_or_else(|| "Unknown project".to_string())
+    });
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::backends::{AgentBackend, CodexBackend};
+
+    #[test]
+    fn codex_nested_usage_reaches_the_ledger() {
+        let line = r#"{"id":"0","msg":{"type":"token_count","input_tokens":0,"output_tokens":0,"total_tokens":0,"info":{"total_token_usage":{"input_tokens":12800,"cached_input_tokens":3456,"output_tokens":640,"reasoning_output_tokens":320,"total_tokens":13440},"last_token_usage":{"input_tokens":6400,"cached_input_tokens":3456,"output_tokens":320,"reasoning_output_tokens":160,"total_tokens":6720},"model_context_window":272000},"rate_limits":null}}"#;
+        let backend = CodexBackend::default();
+        let mut proc: AgentProcess = serde_json::from_value(serde_json::json!({
+            "id": "p1", "task_id": "t1", "status": "running", "start_time": "0", "kind": "codex",
+        })).unwrap();
+        for message in backend.parse_line(line, &mut String::new()) {
+            backend.apply_message(&mut proc, &message);
+        }
+
+        let record = UsageRecord::from_process(&proc, Some("proj".to_string()));
+        assert_eq!(record.kind, "codex");
+        assert_eq!(record.input_tokens, 12800 - 3456);
+        assert_eq!(record.cached_tokens, 3456);
+        assert_eq!(record.output_tokens, 640);
+        assert!(record.cost_usd > 0.0);
+
+        let mut totals = UsageTotals::default();
+        totals.add(&record);
+        assert_eq!(totals.input_tokens + totals.cached_tokens + totals.output_tokens, 13440);
+    }
+}
@@ -0,0 +1,153 @@
+//! Files and images referenced by a message sent to an agent.
+//!
+//! `@path/to/file` words in a message name files of the worktree; their
+//! contents are appended to the prompt. Images pasted or dropped into the reply
+//! box are saved under `attachments/{task_id}` in the app data directory and
+//! handed to the CLI: Codex takes them as `--image` arguments, other backends get
+//! their paths in the prompt and read them from disk. Both lists are recorded
+//! in the metadata of the user message so the transcript shows what was sent.
+
+use std::path::{Component, Path};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use super::prompt;
+
+/// Largest image accepted from the reply box
+const MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "webp"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Attachments {
+    /// Worktree-relative paths of the `@` references that exist
+    pub files: Vec<String>,
+    /// Absolute paths of saved images
+    pub images: Vec<String>,
+}
+
+impl Attachments {
+    /// Collects the `@` references of `message` that name files in the worktree,
+    /// plus the given images that still exist
+    pub fn from_message(message: &str, worktree_path: &str, images: Vec<String>) -> Self {
+        let root = Path::new(worktree_path);
+        let mut files: Vec<String> = Vec::new();
+        for word in message.split_whitespace() {
+            let Some(reference) = word.strip_prefix('@') else { continue; };
+            let reference = reference.trim_end_matches([',', '.', ';', ':', ')', '!', '?']);
+            if reference.is_empty() || !is_inside(reference) || files.iter().any(|f| f == reference) {
+                continue;
+            }
+            if root.join(reference).is_file() {
+                files.push(reference.to_string());
+            }
+        }
+        let images = images.into_iter().filter(|p| Path::new(p).is_file()).collect();
+        Attachments { files, images }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.images.is_empty()
+    }
+
+    /// The message followed by the referenced files and, unless the backend takes
+    /// images as arguments, the image paths
+    pub fn expand(&self, message: &str, worktree_path: &str, images_in_prompt: bool) -> String {
+        let mut text = message.to_string();
+        if !self.files.is_empty() {
+            text.push_str("\n\nReferenced files:");
+            for file in &self.files {
+                let contents = prompt::include_file(Path::new(worktree_path), file);
+                text.push_str(&format!("\n\n--- {} ---\n{}", file, contents.trim_end()));
+            }
+        }
+        if images_in_prompt && !self.images.is_empty() {
+            text.push_str("\n\nAttached images (read these files):");
+            for image in &self.images {
+                text.push_str(&format!("\n- {}", image));
+            }
+        }
+        text
+    }
+
+    /// Adds the lists to a message's metadata object
+    pub fn annotate(&self, metadata: &mut Option<serde_json::Value>) {
+        if self.is_empty() {
+            return;
+        }
+        let meta = metadata.get_or_insert_with(|| serde_json::json!({}));
+        if let Some(obj) = meta.as_object_mut() {
+            obj.insert("files".to_string(), serde_json::json!(self.files));
+            obj.insert("images".to_string(), serde_json::json!(self.images));
+        }
+    }
+}
+
+/// Relative path that stays inside its root
+fn is_inside(relative: &str) -> bool {
+    let path = Path::new(relative);
+    !path.is_absolute() && !path.components().any(|c| matches!(c, Component::ParentDir | Component::Prefix(_)))
+}
+
+/// Tracked and untracked (but not ignored) files of a worktree whose path
+/// contains `query`, file-name matches first
+pub fn list_worktree_files(worktree_path: &str, query: &str, limit: usize) -> Result<Vec<String>, String> {
+    let output = Command::new("git")
+        .args(["ls-files", "--cached", "--others", "--exclude-standard"])
+        .current_dir(worktree_path)
+        .output()
+        .map_err(|e| format!("Failed to list worktree files: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("Failed to list worktree files: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    let query = query.trim().to_lowercase();
+    let mut matches: Vec<(bool, String)> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|path| path.to_lowercase().contains(&query))
+        .map(|path| {
+            let name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+            (!name.starts_with(&query), path.to_string())
+        })
+        .collect();
+    matches.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.len().cmp(&b.1.len())).then(a.1.cmp(&b.1)));
+    matches.dedup_by(|a, b| a.1 == b.1);
+    Ok(matches.into_iter().take(limit).map(|(_, path)| path).collect())
+}
+
+/// Saves an image for a task and returns its absolute path
+pub fn save_image(app: &tauri::AppHandle, task_id: &str, file_name: &str, data: &[u8]) -> Result<String, String> {
+    if data.is_empty() {
+        return Err("Image is empty".to_string());
+    }
+    if data.len() > MAX_IMAGE_BYTES {
+        return Err(format!("Image is larger than {} MB", MAX_IMAGE_BYTES / (1024 * 1024)));
+    }
+    let name: String = Path::new(file_name)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("image.png")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let extension = Path::new(&name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    if !IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!("Unsupported image type '{}', expected one of {}", extension, IMAGE_EXTENSIONS.join(", ")));
+    }
+
+    let dir = app.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?
+        .join("attachments")
+        .join(task_id);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create attachments directory: {}", e))?;
+    let path = dir.join(format!("{}-{}", chrono::Utc::now().timestamp_millis(), name));
+    std::fs::write(&path, data).map_err(|e| format!("Failed to save image: {}", e))?;
+    println!("Saved attachment {:?} for task {}", path, task_id);
+    Ok(path.to_string_lossy().to_string())
+}
@@ -0,0 +1,953 @@
+//! Agent CLI backends.
+//!
+//! Each supported CLI (Claude Code, Codex, ...) implements [`AgentBackend`], which
+//! describes how to find and launch the binary, how the prompt is delivered and how
+//! its stdout is turned into `AgentMessage`s. The shared runner in `agent.rs` does
+//! the spawning, stream reading and status events for every backend.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::policy::{AgentPolicy, PermissionMode, SandboxLevel};
+use super::settings::{AgentSettings, ClaudeSettings, CodexSettings};
+use super::{
+    generate_message_id, get_timestamp, parse_claude_output, parse_codex_output,
+    split_json_objects, AgentKind, AgentMessage, AgentProcess,
+};
+
+/// How the prompt is handed to the agent CLI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptMode {
+    /// The prompt is part of the argument list built by the backend
+    #[default]
+    Argv,
+    /// The prompt is written to stdin, which is then closed
+    Stdin,
+}
+
+/// How a follow-up turn carries the previous conversation forward
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeStrategy {
+    /// Replay recent messages as text in front of the new prompt
+    TextReplay,
+    /// Resume the CLI's own session by id; text replay is only used when no id was captured
+    NativeSession,
+}
+
+/// A program to try when locating an agent CLI
+#[derive(Debug, Clone)]
+pub struct CommandCandidate {
+    pub program: String,
+    /// Arguments that always precede the backend arguments (e.g. `-y @openai/codex` for npx)
+    pub prefix_args: Vec<String>,
+}
+
+impl CommandCandidate {
+    pub fn new(program: &str) -> Self {
+        CommandCandidate { program: program.to_string(), prefix_args: Vec::new() }
+    }
+
+    pub fn with_prefix(program: &str, prefix_args: &[&str]) -> Self {
+        CommandCandidate {
+            program: program.to_string(),
+            prefix_args: prefix_args.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// Everything a backend needs to build the argument list for one run
+#[derive(Debug, Clone)]
+pub struct LaunchSpec {
+    pub prompt: String,
+    pub worktree_path: String,
+    /// Session to resume when the backend supports native resume
+    pub resume_session_id: Option<String>,
+    /// Permission and tool policy in effect for the task
+    pub policy: AgentPolicy,
+    /// Permission prompts are sent to the board and answered over stdin
+    pub route_permissions: bool,
+    /// Model for this run; `None` keeps the backend default
+    pub model: Option<String>,
+    /// `low`, `medium` or `high`; `None` keeps the backend default
+    pub reasoning_effort: Option<String>,
+    /// Absolute paths of images attached to the prompt
+    pub images: Vec<String>,
+}
+
+impl LaunchSpec {
+    /// Whether the runner must keep a pipe to the process's stdin
+    pub fn uses_stdin(&self, backend: &dyn AgentBackend) -> bool {
+        backend.prompt_mode() == PromptMode::Stdin || self.route_permissions
+    }
+}
+
+pub trait AgentBackend: Send + Sync {
+    /// Registry key, also stored as the process `kind` (e.g. "claude")
+    fn name(&self) -> &str;
+
+    /// Human readable name used in logs and errors
+    fn display_name(&self) -> &str;
+
+    /// Other profile strings that resolve to this backend
+    fn aliases(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Programs to probe, in order of preference
+    fn command_candidates(&self) -> Vec<CommandCandidate>;
+
+    /// Arguments used to check that a candidate runs. `None` skips the probe
+    /// and uses the first candidate as-is.
+    fn probe_args(&self) -> Option<Vec<String>> {
+        Some(vec!["--version".to_string()])
+    }
+
+    /// Arguments passed after the candidate's prefix args
+    fn build_args(&self, spec: &LaunchSpec) -> Vec<String>;
+
+    fn prompt_mode(&self) -> PromptMode;
+
+    /// Extra environment variables for the run
+    fn launch_env(&self, _spec: &LaunchSpec) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    fn resume_strategy(&self) -> ResumeStrategy {
+        ResumeStrategy::TextReplay
+    }
+
+    /// Parses one line of stdout. `buffer` persists across lines for formats that
+    /// can spread a single event over several lines.
+    fn parse_line(&self, line: &str, buffer: &mut String) -> Vec<AgentMessage>;
+
+    /// Assistant text carried by a streaming delta line, if `line` is one. Delta
+    /// lines are streamed to the UI and never become messages themselves.
+    fn parse_delta(&self, _line: &str) -> Option<String> {
+        None
+    }
+
+    /// True if the line closes the streamed message, so later deltas start a new one
+    fn ends_stream(&self, _line: &str) -> bool {
+        false
+    }
+
+    /// True if the message is the finished form of the text streamed so far; it
+    /// takes over the id the deltas were sent under
+    fn completes_stream(&self, _message: &AgentMessage) -> bool {
+        false
+    }
+
+    /// Lets the backend pull session info, cost, etc. out of a parsed message
+    fn apply_message(&self, _process: &mut AgentProcess, _message: &AgentMessage) {}
+
+    /// True if the CLI can hand tool permission prompts to the board
+    /// (`permission_request` messages) and take the answers on stdin
+    fn supports_permission_prompts(&self) -> bool {
+        false
+    }
+
+    /// What is written to stdin to deliver the prompt
+    fn stdin_prompt(&self, spec: &LaunchSpec) -> String {
+        format!("{}\n", spec.prompt)
+    }
+
+    /// Encodes the answer to a `permission_request` message as a line for stdin
+    fn permission_response(&self, _request: &AgentMessage, _approved: bool) -> Option<String> {
+        None
+    }
+
+    /// True if the message ends the turn, after which stdin is closed so the CLI exits
+    fn ends_turn(&self, _message: &AgentMessage) -> bool {
+        false
+    }
+
+    /// True if attached images are listed in the prompt rather than passed as
+    /// arguments by `build_args`
+    fn images_in_prompt(&self) -> bool {
+        true
+    }
+
+    /// First message recorded for a new process
+    fn initial_message(&self, task_id: &str, message: &str, worktree_path: &str) -> AgentMessage {
+        AgentMessage {
+            id: generate_message_id(),
+            sender: "user".to_string(),
+            content: format!("Task: {}", message),
+            timestamp: get_timestamp(),
+            message_type: "text".to_string(),
+            metadata: Some(serde_json::json!({
+                "task_id": task_id,
+                "worktree_path": worktree_path
+            })),
+        }
+    }
+
+    /// True for profiles declared in agent_settings.json rather than built in
+    fn is_custom(&self) -> bool {
+        false
+    }
+
+    /// Error returned when none of the candidates could be started
+    fn not_found_message(&self) -> String {
+        let attempted: Vec<String> = self.command_candidates().iter()
+            .map(|c| c.program.clone())
+            .collect();
+        format!("{} CLI not found. Tried commands: {}. Please ensure it is installed and in PATH.", self.display_name(), attempted.join(", "))
+    }
+}
+
+/// Non-empty configured command, if any
+fn configured_command(command: &Option<String>) -> Option<String> {
+    command.as_ref().map(|c| c.trim().to_string()).filter(|c| !c.is_empty())
+}
+
+/// Claude Code (`claude -p ... --output-format stream-json`)
+#[derive(Default)]
+pub struct ClaudeBackend {
+    settings: ClaudeSettings,
+}
+
+impl ClaudeBackend {
+    pub fn new(settings: ClaudeSettings) -> Self {
+        ClaudeBackend { settings }
+    }
+}
+
+impl AgentBackend for ClaudeBackend {
+    fn name(&self) -> &str { "claude" }
+
+    fn display_name(&self) -> &str { "Claude Code" }
+
+    fn aliases(&self) -> Vec<String> {
+        vec!["claude-code".to_string(), "claudecode".to_string()]
+    }
+
+    fn command_candidates(&self) -> Vec<CommandCandidate> {
+        // Configured binary first, then multiple Claude command variations (similar to VS Code code.cmd issue)
+        let mut candidates = Vec::new();
+        if let Some(command) = configured_command(&self.settings.command) {
+            candidates.push(CommandCandidate::new(&command));
+        }
+        candidates.push(CommandCandidate::new("claude"));
+        candidates.push(CommandCandidate::new("claude.exe"));
+        candidates.push(CommandCandidate::new("claude.cmd"));
+        candidates
+    }
+
+    fn build_args(&self, spec: &LaunchSpec) -> Vec<String> {
+        let mut args = vec!["-p".to_string()];
+        if spec.route_permissions {
+            // The prompt goes over stdin as stream-json so the pipe stays open for permission answers
+            args.push("--input-format".to_string());
+            args.push("stream-json".to_string());
+        } else {
+            args.push(spec.prompt.clone());
+        }
+        args.extend([
+            "--output-format".to_string(), "stream-json".to_string(),
+            "--include-partial-messages".to_string(),
+            "--verbose".to_string(),
+        ]);
+        args.extend(claude_policy_args(&spec.policy));
+        if spec.route_permissions {
+            args.push("--permission-prompt-tool".to_string());
+            args.push("stdio".to_string());
+        }
+        args.push("--add-dir".to_string());
+        args.push(spec.worktree_path.clone());
+        if let Some(session_id) = &spec.resume_session_id {
+            args.push("--resume".to_string());
+            args.push(session_id.clone());
+        }
+        let model = spec.model.as_ref().or(self.settings.model.as_ref()).filter(|m| !m.trim().is_empty());
+        if let Some(model) = model {
+            args.push("--model".to_string());
+            args.push(model.trim().to_string());
+        }
+        if let Some(extra) = &self.settings.args {
+            args.extend(extra.iter().cloned());
+        }
+        args
+    }
+
+    fn prompt_mode(&self) -> PromptMode { PromptMode::Argv }
+
+    fn launch_env(&self, spec: &LaunchSpec) -> Vec<(String, String)> {
+        // Claude has no effort flag; the thinking budget is set through the environment
+        let thinking_tokens = match spec.reasoning_effort.as_deref() {
+            Some("low") => 4_000,
+            Some("medium") => 10_000,
+            Some("high") => 31_999,
+            _ => return Vec::new(),
+        };
+        vec![("MAX_THINKING_TOKENS".to_string(), thinking_tokens.to_string())]
+    }
+
+    fn resume_strategy(&self) -> ResumeStrategy { ResumeStrategy::NativeSession }
+
+    fn parse_line(&self, line: &str, _buffer: &mut String) -> Vec<AgentMessage> {
+        parse_claude_output(line).into_iter().collect()
+    }
+
+    fn apply_message(&self, proc: &mut AgentProcess, message: &AgentMessage) {
+        apply_claude_message(proc, message);
+    }
+
+    fn parse_delta(&self, line: &str) -> Option<String> {
+        let json = serde_json::from_str::<serde_json::Value>(line.trim()).ok()?;
+        if json.get("type").and_then(|v| v.as_str()) != Some("stream_event") {
+            return None;
+        }
+        // Every partial event is swallowed; only text deltas carry anything to show
+        let delta = json.pointer("/event/delta")
+            .filter(|d| d.get("type").and_then(|v| v.as_str()) == Some("text_delta"))
+            .and_then(|d| d.get("text"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        Some(delta.to_string())
+    }
+
+    fn ends_stream(&self, line: &str) -> bool {
+        serde_json::from_str::<serde_json::Value>(line.trim()).ok()
+            .and_then(|json| json.pointer("/event/type").and_then(|v| v.as_str()).map(|t| t == "message_stop"))
+            .unwrap_or(false)
+    }
+
+    fn completes_stream(&self, message: &AgentMessage) -> bool {
+        message.sender == "agent" && message.message_type == "text"
+    }
+
+    fn supports_permission_prompts(&self) -> bool { true }
+
+    fn stdin_prompt(&self, spec: &LaunchSpec) -> String {
+        let message = serde_json::json!({
+            "type": "user",
+            "message": { "role": "user", "content": spec.prompt }
+        });
+        format!("{}\n", message)
+    }
+
+    fn permission_response(&self, request: &AgentMessage, approved: bool) -> Option<String> {
+        let metadata = request.metadata.as_ref()?;
+        let request_id = metadata.get("request_id").and_then(|v| v.as_str())?;
+        let decision = if approved {
+            serde_json::json!({
+                "behavior": "allow",
+                "updatedInput": metadata.pointer("/request/input").cloned().unwrap_or(serde_json::json!({}))
+            })
+        } else {
+            serde_json::json!({ "behavior": "deny", "message": "Denied from the board" })
+        };
+        let response = serde_json::json!({
+            "type": "control_response",
+            "response": { "subtype": "success", "request_id": request_id, "response": decision }
+        });
+        Some(format!("{}\n", response))
+    }
+
+    fn ends_turn(&self, message: &AgentMessage) -> bool {
+        message.message_type == "result"
+    }
+}
+
+/// Codex CLI (`codex exec --json`, prompt on stdin)
+#[derive(Default)]
+pub struct CodexBackend {
+    settings: CodexSettings,
+}
+
+impl CodexBackend {
+    pub fn new(settings: CodexSettings) -> Self {
+        CodexBackend { settings }
+    }
+}
+
+impl AgentBackend for CodexBackend {
+    fn name(&self) -> &str { "codex" }
+
+    fn display_name(&self) -> &str { "Codex" }
+
+    fn aliases(&self) -> Vec<String> {
+        vec!["chat-codex".to_string(), "chatgpt-codex".to_string()]
+    }
+
+    fn command_candidates(&self) -> Vec<CommandCandidate> {
+        // Configured command first, then codex.cmd, npx, and any other codex binary on PATH
+        let mut candidates = Vec::new();
+        if let Some(command) = configured_command(&self.settings.command) {
+            candidates.push(CommandCandidate::new(&command));
+        }
+        candidates.push(CommandCandidate::new("codex.cmd"));
+        candidates.push(CommandCandidate::with_prefix("npx", &["-y", "@openai/codex"]));
+        candidates.push(CommandCandidate::new("codex"));
+        candidates.push(CommandCandidate::new("codex.exe"));
+        candidates
+    }
+
+    fn build_args(&self, spec: &LaunchSpec) -> Vec<String> {
+        let mut args = vec![
+            "exec".to_string(),
+            "--json".to_string(),
+            "--skip-git-repo-check".to_string(),
+        ];
+        args.extend(codex_policy_args(&spec.policy));
+        if let Some(model) = &spec.model {
+            args.push("--model".to_string());
+            args.push(model.clone());
+        }
+        if let Some(effort) = &spec.reasoning_effort {
+            args.push("-c".to_string());
+            args.push(format!("model_reasoning_effort={}", effort));
+        }
+        // `--image` takes several values, so each is attached with `=` to keep it
+        // from swallowing the `resume` subcommand and session id
+        for image in &spec.images {
+            args.push(format!("--image={}", image));
+        }
+        // Extra args go before the prompt (which is sent on stdin)
+        if let Some(extra) = &self.settings.args {
+            args.extend(extra.iter().cloned());
+        }
+        // `codex exec resume <id>` continues a recorded session; the prompt still comes from stdin
+        if let Some(session_id) = &spec.resume_session_id {
+            args.push("resume".to_string());
+            args.push(session_id.clone());
+        }
+        args
+    }
+
+    fn prompt_mode(&self) -> PromptMode { PromptMode::Stdin }
+
+    fn resume_strategy(&self) -> ResumeStrategy { ResumeStrategy::NativeSession }
+
+    fn parse_line(&self, line: &str, buffer: &mut String) -> Vec<AgentMessage> {
+        parse_codex_line(line, buffer)
+    }
+
+    fn apply_message(&self, proc: &mut AgentProcess, message: &AgentMessage) {
+        apply_codex_message(proc, message);
+    }
+
+    fn parse_delta(&self, line: &str) -> Option<String> {
+        let mut text = String::new();
+        let mut found = false;
+        for json_str in split_json_objects(line.trim()) {
+            let json = serde_json::from_str::<serde_json::Value>(&json_str).ok()?;
+            let msg = json.get("msg")?;
+            match msg.get("type").and_then(|v| v.as_str()) {
+                Some("agent_message_delta") => {
+                    text.push_str(msg.get("delta").and_then(|v| v.as_str()).unwrap_or(""));
+                    found = true;
+                }
+                Some("agent_reasoning_delta") => found = true,
+                _ => return None,
+            }
+        }
+        found.then_some(text)
+    }
+
+    fn completes_stream(&self, message: &AgentMessage) -> bool {
+        message.message_type == "agent_message"
+    }
+
+    fn images_in_prompt(&self) -> bool { false }
+
+    fn initial_message(&self, task_id: &str, _message: &str, worktree_path: &str) -> AgentMessage {
+        AgentMessage {
+            id: generate_message_id(),
+            sender: "system".to_string(),
+            content: format!("Starting Codex agent for task: {}", task_id),
+            timestamp: get_timestamp(),
+            message_type: "text".to_string(),
+            metadata: Some(serde_json::json!({
+                "task_id": task_id,
+                "worktree_path": worktree_path
+            })),
+        }
+    }
+
+    fn not_found_message(&self) -> String {
+        "Codex CLI not found. Tried codex.cmd exec, npx -y @openai/codex exec, and direct codex commands. Ensure Codex is installed (npm install -g @openai/codex) or available in PATH.".to_string()
+    }
+}
+
+/// Translates a policy into Claude Code flags. Claude has no sandbox switch, so
+/// read-only and network-off are enforced by disallowing the matching tools.
+fn claude_policy_args(policy: &AgentPolicy) -> Vec<String> {
+    let mut args = vec!["--permission-mode".to_string()];
+    match policy.permission_mode {
+        PermissionMode::Bypass => {
+            args.push("acceptEdits".to_string());
+            args.push("--dangerously-skip-permissions".to_string());
+        }
+        PermissionMode::AcceptEdits => args.push("acceptEdits".to_string()),
+        PermissionMode::Plan => args.push("plan".to_string()),
+        PermissionMode::Ask => args.push("default".to_string()),
+    }
+
+    if !policy.allowed_tools.is_empty() {
+        args.push("--allowedTools".to_string());
+        args.push(policy.allowed_tools.join(","));
+    }
+
+    let mut disallowed = policy.disallowed_tools.clone();
+    if policy.sandbox == SandboxLevel::ReadOnly {
+        disallowed.extend(["Write", "Edit", "MultiEdit", "NotebookEdit", "Bash"].iter().map(|s| s.to_string()));
+    }
+    if !policy.network {
+        disallowed.extend(["WebFetch", "WebSearch"].iter().map(|s| s.to_string()));
+    }
+    let mut seen = std::collections::HashSet::new();
+    disallowed.retain(|tool| seen.insert(tool.clone()));
+    if !disallowed.is_empty() {
+        args.push("--disallowedTools".to_string());
+        args.push(disallowed.join(","));
+    }
+    args
+}
+
+/// Translates a policy into `codex exec` flags. Codex has no per-tool switches,
+/// so the tool lists are not used.
+fn codex_policy_args(policy: &AgentPolicy) -> Vec<String> {
+    // Plan mode never writes, whatever the sandbox says
+    let sandbox = if policy.permission_mode == PermissionMode::Plan {
+        SandboxLevel::ReadOnly
+    } else {
+        policy.sandbox
+    };
+
+    let mut args = Vec::new();
+    if policy.permission_mode == PermissionMode::Bypass && sandbox == SandboxLevel::FullAccess {
+        args.push("--dangerously-bypass-approvals-and-sandbox".to_string());
+    }
+    args.push("--sandbox".to_string());
+    args.push(match sandbox {
+        SandboxLevel::ReadOnly => "read-only",
+        SandboxLevel::WorkspaceWrite => "workspace-write",
+        SandboxLevel::FullAccess => "danger-full-access",
+    }.to_string());
+    if sandbox == SandboxLevel::WorkspaceWrite {
+        args.push("-c".to_string());
+        args.push(format!("sandbox_workspace_write.network_access={}", policy.network));
+    }
+    args
+}
+
+/// Pulls the session id, model, token usage, cost and turn count out of Claude stream-json events
+fn apply_claude_message(proc: &mut AgentProcess, message: &AgentMessage) {
+    // Token usage rides on every assistant event
+    if let Some(metadata) = message.metadata.as_ref().filter(|m| m.get("type").and_then(|v| v.as_str()) == Some("assistant")) {
+        proc.usage.record_claude(metadata);
+    }
+
+    // Update session info and the model in use from system events
+    if message.message_type == "init" || message.message_type == "system" {
+        if let Some(metadata) = &message.metadata {
+            if let Some(session_id) = metadata.get("session_id").and_then(|v| v.as_str()) {
+                proc.session_id = Some(session_id.to_string());
+            }
+            if let Some(model) = metadata.get("model").and_then(|v| v.as_str()) {
+                proc.model = Some(model.to_string());
+            }
+        }
+    }
+
+    // Update cost and turns from result events
+    if message.message_type == "result" {
+        if let Some(metadata) = &message.metadata {
+            if let Some(cost) = metadata.get("total_cost_usd").and_then(|v| v.as_f64()) {
+                proc.total_cost_usd = Some(cost);
+            }
+            if let Some(turns) = metadata.get("num_turns").and_then(|v| v.as_i64()) {
+                proc.num_turns = Some(turns as i32);
+            }
+        }
+        proc.status = "completed".to_string();
+        proc.end_time = Some(get_timestamp());
+    }
+}
+
+/// Pulls the session id and model out of Codex `session_configured` /
+/// `thread.started` events and token usage out of `token_count` and
+/// `turn.completed` events
+fn apply_codex_message(proc: &mut AgentProcess, message: &AgentMessage) {
+    let Some(metadata) = &message.metadata else { return; };
+    if message.message_type == "token_count" {
+        if let Some(usage) = metadata.get("msg").or_else(|| metadata.get("usage")) {
+            proc.usage.record_codex(usage);
+        }
+    }
+    let session_id = metadata.get("msg")
+        .filter(|msg| msg.get("type").and_then(|v| v.as_str()) == Some("session_configured"))
+        .and_then(|msg| msg.get("session_id"))
+        .or_else(|| metadata.get("thread_id"))
+        .and_then(|v| v.as_str());
+    if let Some(session_id) = session_id {
+        proc.session_id = Some(session_id.to_string());
+    }
+    if let Some(model) = metadata.pointer("/msg/model").and_then(|v| v.as_str()) {
+        proc.model = Some(model.to_string());
+    }
+}
+
+/// Parses Codex JSONL output, which may put several objects on one line or
+/// spread one object over several lines
+fn parse_codex_line(line: &str, buffer: &mut String) -> Vec<AgentMessage> {
+    let trimmed = line.trim();
+    // append to buffer to allow multi-line JSON
+    if !trimmed.is_empty() {
+        if !buffer.is_empty() { buffer.push('\n'); }
+        buffer.push_str(trimmed);
+    }
+
+    // Handle multiple JSON objects on the same line
+    let json_objects = split_json_objects(trimmed);
+    if !json_objects.is_empty() {
+        buffer.clear();
+        return json_objects.iter()
+            .filter_map(|json_str| parse_codex_output(json_str))
+            .collect();
+    }
+
+    // If no JSON objects found, try accumulated buffer
+    if !buffer.is_empty() {
+        if let Some(message) = parse_codex_output(buffer) {
+            buffer.clear();
+            return vec![message];
+        }
+    }
+    Vec::new()
+}
+
+/// Treats every non-empty line as agent text
+fn parse_text_line(line: &str) -> Vec<AgentMessage> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return Vec::new();
+    }
+    vec![AgentMessage {
+        id: generate_message_id(),
+        sender: "agent".to_string(),
+        content: trimmed.to_string(),
+        timestamp: get_timestamp(),
+        message_type: "text".to_string(),
+        metadata: None,
+    }]
+}
+
+/// Output formats a custom agent can declare
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    ClaudeStreamJson,
+    CodexJsonl,
+    #[default]
+    Text,
+}
+
+/// A user-defined agent profile from agent_settings.json
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CustomAgentConfig {
+    /// Profile name used in tasks and `start_agent_process` (e.g. "aider")
+    pub name: String,
+    pub display_name: Option<String>,
+    pub command: String,
+    /// Argument template; `{prompt}` and `{worktree}` are substituted per run
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub prompt_mode: PromptMode,
+    #[serde(default)]
+    pub output_format: OutputFormat,
+}
+
+/// Backend driven entirely by a `CustomAgentConfig`
+pub struct CustomBackend {
+    config: CustomAgentConfig,
+}
+
+impl CustomBackend {
+    pub fn new(config: CustomAgentConfig) -> Self {
+        CustomBackend { config }
+    }
+}
+
+impl AgentBackend for CustomBackend {
+    fn name(&self) -> &str { &self.config.name }
+
+    fn display_name(&self) -> &str {
+        self.config.display_name.as_deref().filter(|s| !s.is_empty()).unwrap_or(&self.config.name)
+    }
+
+    fn command_candidates(&self) -> Vec<CommandCandidate> {
+        vec![CommandCandidate::new(&self.config.command)]
+    }
+
+    fn probe_args(&self) -> Option<Vec<String>> {
+        // Arbitrary wrappers may not understand --version
+        None
+    }
+
+    fn build_args(&self, spec: &LaunchSpec) -> Vec<String> {
+        let mut has_prompt = false;
+        let mut args: Vec<String> = self.config.args.iter()
+            .map(|arg| {
+                if arg.contains("{prompt}") { has_prompt = true; }
+                arg.replace("{prompt}", &spec.prompt).replace("{worktree}", &spec.worktree_path)
+            })
+            .collect();
+        // Argv mode without a placeholder: the prompt goes last
+        if self.config.prompt_mode == PromptMode::Argv && !has_prompt {
+            args.push(spec.prompt.clone());
+        }
+        args
+    }
+
+    fn prompt_mode(&self) -> PromptMode { self.config.prompt_mode }
+
+    fn parse_line(&self, line: &str, buffer: &mut String) -> Vec<AgentMessage> {
+        match self.config.output_format {
+            OutputFormat::ClaudeStreamJson => parse_claude_output(line).into_iter().collect(),
+            OutputFormat::CodexJsonl => parse_codex_line(line, buffer),
+            OutputFormat::Text => parse_text_line(line),
+        }
+    }
+
+    fn apply_message(&self, proc: &mut AgentProcess, message: &AgentMessage) {
+        match self.config.output_format {
+            OutputFormat::ClaudeStreamJson => apply_claude_message(proc, message),
+            OutputFormat::CodexJsonl => apply_codex_message(proc, message),
+            OutputFormat::Text => {}
+        }
+    }
+
+    fn is_custom(&self) -> bool { true }
+
+    fn not_found_message(&self) -> String {
+        format!("Custom agent '{}' could not be started with command '{}'. Check the command in Settings.", self.config.name, self.config.command)
+    }
+}
+
+// Registry of known backends, keyed by name
+type BackendMap = Arc<Mutex<HashMap<String, Arc<dyn AgentBackend>>>>;
+static BACKENDS: OnceLock<BackendMap> = OnceLock::new();
+
+fn get_backends() -> &'static BackendMap {
+    BACKENDS.get_or_init(|| {
+        let mut map: HashMap<String, Arc<dyn AgentBackend>> = HashMap::new();
+        map.insert("claude".to_string(), Arc::new(ClaudeBackend::default()));
+        map.insert("codex".to_string(), Arc::new(CodexBackend::default()));
+        Arc::new(Mutex::new(map))
+    })
+}
+
+/// Registers (or replaces) a backend under its name
+pub fn register_backend(backend: Arc<dyn AgentBackend>) {
+    let mut map = get_backends().lock().unwrap();
+    map.insert(backend.name().to_lowercase(), backend);
+}
+
+/// Looks up a backend by name or alias
+pub fn find_backend(profile: &str) -> Option<Arc<dyn AgentBackend>> {
+    let which = profile.trim().to_lowercase();
+    let map = get_backends().lock().unwrap();
+    if let Some(backend) = map.get(&which) {
+        return Some(backend.clone());
+    }
+    map.values()
+        .find(|b| b.aliases().iter().any(|a| a.eq_ignore_ascii_case(&which)))
+        .cloned()
+}
+
+/// Resolves a profile string to a backend, falling back to Claude for unknown profiles
+pub fn resolve_backend(profile: &str) -> Arc<dyn AgentBackend> {
+    match find_backend(profile) {
+        Some(backend) => backend,
+        None => {
+            println!("Unknown agent profile '{}', falling back to {}", profile, AgentKind::default().as_str());
+            find_backend(AgentKind::default().as_str()).expect("default backend is always registered")
+        }
+    }
+}
+
+/// Reconfigures the built-in backends and custom profiles from agent settings
+pub fn apply_settings(settings: &AgentSettings) {
+    register_backend(Arc::new(ClaudeBackend::new(settings.claude.clone().unwrap_or_default())));
+    register_backend(Arc::new(CodexBackend::new(settings.codex.clone().unwrap_or_default())));
+    sync_custom_backends(&settings.custom_agents);
+}
+
+/// Replaces all custom backends with the profiles from agent settings.
+/// Built-in names cannot be overridden.
+pub fn sync_custom_backends(configs: &[CustomAgentConfig]) {
+    let mut map = get_backends().lock().unwrap();
+    map.retain(|_, backend| !backend.is_custom());
+    for config in configs {
+        let name = config.name.trim().to_lowercase();
+        if name.is_empty() || config.command.trim().is_empty() {
+            println!("Skipping custom agent with empty name or command: {:?}", config);
+            continue;
+        }
+        if shadows_builtin(&map, &name) {
+            println!("Custom agent '{}' uses a built-in profile name, skipping", name);
+            continue;
+        }
+        if map.contains_key(&name) {
+            println!("Custom agent '{}' conflicts with an existing profile, skipping", name);
+            continue;
+        }
+        let mut config = config.clone();
+        config.name = name.clone();
+        map.insert(name, Arc::new(CustomBackend::new(config)));
+    }
+    println!("Agent backends registered: {:?}", map.keys().collect::<Vec<_>>());
+}
+
+/// Whether a profile name is taken by a built-in backend, including its aliases
+fn shadows_builtin(map: &HashMap<String, Arc<dyn AgentBackend>>, name: &str) -> bool {
+    map.values()
+        .filter(|backend| !backend.is_custom())
+        .any(|backend| backend.name().eq_ignore_ascii_case(name)
+            || backend.aliases().iter().any(|a| a.eq_ignore_ascii_case(name)))
+}
+
+/// Lists registered backends for profile pickers
+pub fn list_backends() -> Vec<serde_json::Value> {
+    let map = get_backends().lock().unwrap();
+    let mut list: Vec<serde_json::Value> = map.values()
+        .map(|b| serde_json::json!({
+            "name": b.name(),
+            "display_name": b.display_name(),
+            "custom": b.is_custom(),
+        }))
+        .collect();
+    // Built-ins first, then custom profiles by name
+    list.sort_by_key(|v| (
+        v.get("custom").and_then(|c| c.as_bool()).unwrap_or(false),
+        v.get("name").and_then(|n| n.as_str()).unwrap_or("").to_string(),
+    ));
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(mode: PermissionMode, sandbox: SandboxLevel, network: bool) -> AgentPolicy {
+        AgentPolicy { permission_mode: mode, sandbox, network, ..AgentPolicy::default() }
+    }
+
+    #[test]
+    fn claude_bypass_skips_permissions() {
+        let args = claude_policy_args(&AgentPolicy::default());
+        assert_eq!(args, vec![
+            "--permission-mode", "acceptEdits", "--dangerously-skip-permissions",
+            "--allowedTools", "Read,Write,Edit,MultiEdit",
+        ]);
+    }
+
+    #[test]
+    fn claude_routed_modes_do_not_pre_allow_bash() {
+        for (mode, flag) in [(PermissionMode::AcceptEdits, "acceptEdits"), (PermissionMode::Ask, "default")] {
+            let args = claude_policy_args(&policy(mode, SandboxLevel::FullAccess, true));
+            assert_eq!(&args[..2], ["--permission-mode", flag]);
+            assert!(!args.contains(&"--dangerously-skip-permissions".to_string()));
+            assert!(!args.iter().any(|a| a.split(',').any(|tool| tool == "Bash")));
+        }
+    }
+
+    #[test]
+    fn claude_read_only_and_offline_disallow_tools_once() {
+        let mut p = policy(PermissionMode::Plan, SandboxLevel::ReadOnly, false);
+        p.allowed_tools.clear();
+        p.disallowed_tools = vec!["Bash".to_string()];
+        assert_eq!(claude_policy_args(&p), vec![
+            "--permission-mode", "plan",
+            "--disallowedTools", "Bash,Write,Edit,MultiEdit,NotebookEdit,WebFetch,WebSearch",
+        ]);
+    }
+
+    #[test]
+    fn codex_bypass_with_full_access_skips_approvals() {
+        assert_eq!(codex_policy_args(&AgentPolicy::default()), vec![
+            "--dangerously-bypass-approvals-and-sandbox", "--sandbox", "danger-full-access",
+        ]);
+    }
+
+    #[test]
+    fn codex_workspace_write_sets_network_access() {
+        let args = codex_policy_args(&policy(PermissionMode::Ask, SandboxLevel::WorkspaceWrite, false));
+        assert_eq!(args, vec!["--sandbox", "workspace-write", "-c", "sandbox_workspace_write.network_access=false"]);
+    }
+
+    #[test]
+    fn codex_plan_mode_is_read_only() {
+        let args = codex_policy_args(&policy(PermissionMode::Plan, SandboxLevel::FullAccess, true));
+        assert_eq!(args, vec!["--sandbox", "read-only"]);
+    }
+
+    #[test]
+    fn codex_does_not_route_permission_prompts() {
+        assert!(!CodexBackend::default().supports_permission_prompts());
+        assert!(ClaudeBackend::default().supports_permission_prompts());
+    }
+
+    fn spec(resume_session_id: Option<&str>, images: &[&str]) -> LaunchSpec {
+        LaunchSpec {
+            prompt: "hi".to_string(),
+            worktree_path: "/tmp/wt".to_string(),
+            resume_session_id: resume_session_id.map(str::to_string),
+            policy: AgentPolicy::default(),
+            route_permissions: false,
+            model: None,
+            reasoning_effort: None,
+            images: images.iter().map(|i| i.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn codex_resume_with_images_keeps_the_subcommand_intact() {
+        let args = CodexBackend::default().build_args(&spec(Some("sess-1"), &["/tmp/a.png", "/tmp/b.png"]));
+        assert!(args.contains(&"--image=/tmp/a.png".to_string()));
+        assert!(args.contains(&"--image=/tmp/b.png".to_string()));
+        assert!(!args.contains(&"--image".to_string()));
+        assert_eq!(args[args.len() - 2..], ["resume".to_string(), "sess-1".to_string()]);
+    }
+
+    #[test]
+    fn claude_message_stop_ends_stream() {
+        let backend = ClaudeBackend::default();
+        let delta = r#"{"type":"stream_event","event":{"type":"content_block_delta","delta":{"type":"text_delta","text":"Hi"}}}"#;
+        let stop = r#"{"type":"stream_event","event":{"type":"message_stop"}}"#;
+        assert_eq!(backend.parse_delta(delta).as_deref(), Some("Hi"));
+        assert!(!backend.ends_stream(delta));
+        assert_eq!(backend.parse_delta(stop).as_deref(), Some(""));
+        assert!(backend.ends_stream(stop));
+    }
+
+    #[test]
+    fn custom_agents_cannot_take_built_in_names() {
+        let custom = CustomAgentConfig {
+            name: "aider".to_string(),
+            display_name: None,
+            command: "aider".to_string(),
+            args: Vec::new(),
+            prompt_mode: PromptMode::default(),
+            output_format: OutputFormat::default(),
+        };
+        let mut map: HashMap<String, Arc<dyn AgentBackend>> = HashMap::new();
+        map.insert("claude".to_string(), Arc::new(ClaudeBackend::default()));
+        map.insert("codex".to_string(), Arc::new(CodexBackend::default()));
+        map.insert("aider".to_string(), Arc::new(CustomBackend::new(custom)));
+
+        assert!(shadows_builtin(&map, "claude"));
+        assert!(shadows_builtin(&map, "codex"));
+        assert!(shadows_builtin(&map, "claude-code"));
+        assert!(shadows_builtin(&map, "chatgpt-codex"));
+        assert!(!shadows_builtin(&map, "aider"));
+        assert!(!shadows_builtin(&map, "gemini"));
+    }
+}
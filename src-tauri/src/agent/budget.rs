@@ -0,0 +1,273 @@
+//! Cost and time budgets.
+//!
+//! A budget is stored on each project (`agent_budget`) and per task; any limit
+//! set on the task replaces the project's value for that limit. Turn and minute
+//! limits apply to each run. Cost and token limits cap everything spent by the
+//! task's runs when set on the task, or by the project's runs when taken from
+//! the project: the ledger holds earlier runs, runs still in memory count live.
+//!
+//! Limits are checked while a run streams: cost, tokens and turns on every
+//! parsed message, wall-clock minutes on every monitor tick. Cost is the one the
+//! CLI reports (Claude, at the end of a turn) or else estimated from tokens.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::{accounting, records, AgentProcess};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentBudget {
+    pub max_cost_usd: Option<f64>,
+    pub max_tokens: Option<u64>,
+    pub max_turns: Option<u32>,
+    pub max_minutes: Option<u64>,
+}
+
+impl AgentBudget {
+    pub fn is_empty(&self) -> bool {
+        self.max_cost_usd.is_none() && self.max_tokens.is_none() && self.max_turns.is_none() && self.max_minutes.is_none()
+    }
+
+    /// Limits of `self`, falling back to `fallback` for any limit left unset
+    pub fn or(&self, fallback: &AgentBudget) -> AgentBudget {
+        AgentBudget {
+            max_cost_usd: self.max_cost_usd.or(fallback.max_cost_usd),
+            max_tokens: self.max_tokens.or(fallback.max_tokens),
+            max_turns: self.max_turns.or(fallback.max_turns),
+            max_minutes: self.max_minutes.or(fallback.max_minutes),
+        }
+    }
+}
+
+/// Which runs a cost or token limit counts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    #[default]
+    Task,
+    Project,
+}
+
+impl BudgetScope {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BudgetScope::Task => "task",
+            BudgetScope::Project => "project",
+        }
+    }
+
+    fn includes(&self, run: &AgentProcess, other_task: &str, other_project: Option<&str>) -> bool {
+        match self {
+            BudgetScope::Task => run.task_id == other_task,
+            BudgetScope::Project => run.project_id.is_some() && run.project_id.as_deref() == other_project,
+        }
+    }
+}
+
+/// Cost and tokens counted against a budget
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Spend {
+    pub cost_usd: f64,
+    pub tokens: u64,
+}
+
+impl Spend {
+    /// Spend of one run: the reported cost, or an estimate from its tokens
+    pub fn of(proc: &AgentProcess) -> Spend {
+        Spend {
+            cost_usd: proc.total_cost_usd
+                .or_else(|| proc.usage.estimated_cost_usd(proc.kind.as_str(), proc.model.as_deref()))
+                .unwrap_or(0.0),
+            tokens: proc.usage.total(),
+        }
+    }
+}
+
+/// Budget a run is held to, as stored on the process
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunBudget {
+    #[serde(flatten)]
+    pub limits: AgentBudget,
+    pub cost_scope: BudgetScope,
+    pub tokens_scope: BudgetScope,
+    /// Cost (in `cost_scope`) and tokens (in `tokens_scope`) of runs that were
+    /// only in the ledger when this run started
+    pub spent_before: Spend,
+}
+
+impl RunBudget {
+    /// Cost in `cost_scope` and tokens in `tokens_scope`, counting `proc` and
+    /// every other run in memory
+    pub fn spent(&self, proc: &AgentProcess, processes: &HashMap<String, AgentProcess>) -> Spend {
+        let mut spent = self.spent_before;
+        let mut counted_self = false;
+        for run in processes.values().filter(|run| run.project_id.is_some()) {
+            counted_self |= run.id == proc.id;
+            let run_spend = Spend::of(run);
+            if self.cost_scope.includes(run, &proc.task_id, proc.project_id.as_deref()) {
+                spent.cost_usd += run_spend.cost_usd;
+            }
+            if self.tokens_scope.includes(run, &proc.task_id, proc.project_id.as_deref()) {
+                spent.tokens += run_spend.tokens;
+            }
+        }
+        if !counted_self {
+            let own = Spend::of(proc);
+            spent.cost_usd += own.cost_usd;
+            spent.tokens += own.tokens;
+        }
+        spent
+    }
+
+    /// Describes the first limit the process has crossed, if any
+    pub fn exceeded_by(&self, proc: &AgentProcess, processes: &HashMap<String, AgentProcess>, elapsed_secs: u64) -> Option<String> {
+        let spent = self.spent(proc, processes);
+        if let Some(limit) = self.limits.max_cost_usd {
+            if spent.cost_usd > limit {
+                return Some(format!("{} cost ${:.4} exceeds the ${:.2} budget", self.cost_scope.as_str(), spent.cost_usd, limit));
+            }
+        }
+        if let Some(limit) = self.limits.max_tokens {
+            if spent.tokens > limit {
+                return Some(format!("{} tokens of the {} exceed the {} token budget", spent.tokens, self.tokens_scope.as_str(), limit));
+            }
+        }
+        self.limits.run_limit_exceeded(proc, elapsed_secs)
+    }
+}
+
+impl AgentBudget {
+    /// Describes the first per-run (turn or minute) limit the process has crossed, if any
+    fn run_limit_exceeded(&self, proc: &AgentProcess, elapsed_secs: u64) -> Option<String> {
+        if let Some(limit) = self.max_turns {
+            let turns = proc.num_turns.map(|t| t.max(0) as u32).unwrap_or(0).max(proc.usage.turns);
+            if turns > limit {
+                return Some(format!("{} turns exceed the {} turn budget", turns, limit));
+            }
+        }
+        if let Some(limit) = self.max_minutes {
+            if elapsed_secs > limit * 60 {
+                return Some(format!("{} minutes of run time exceed the {} minute budget", elapsed_secs / 60, limit));
+            }
+        }
+        None
+    }
+}
+
+/// Budget for a new run of a task in `project_id`: task limits, then project
+/// limits, with what earlier runs in each limit's scope spent. `processes` are
+/// the runs in memory, which `RunBudget::spent` counts live instead.
+pub fn resolve_budget(
+    app: &tauri::AppHandle,
+    task_id: &str,
+    project_id: Option<&str>,
+    processes: &HashMap<String, AgentProcess>,
+) -> Option<RunBudget> {
+    let record = records::find_task(app, task_id)?;
+    let task = record.task_field::<AgentBudget>("agent_budget").unwrap_or_default();
+    let project = record.project_field::<AgentBudget>("agent_budget").unwrap_or_default();
+    let limits = task.or(&project);
+    if limits.is_empty() {
+        return None;
+    }
+    let scope = |task_limit: bool| if task_limit { BudgetScope::Task } else { BudgetScope::Project };
+    let cost_scope = scope(task.max_cost_usd.is_some());
+    let tokens_scope = scope(task.max_tokens.is_some());
+
+    let live: HashSet<&str> = processes.values()
+        .filter(|run| run.project_id.is_some())
+        .map(|run| run.id.as_str())
+        .collect();
+    let in_scope = |scope: BudgetScope, record: &accounting::UsageRecord| match scope {
+        BudgetScope::Task => record.task_id == task_id,
+        BudgetScope::Project => project_id.is_some() && record.project_id.as_deref() == project_id,
+    };
+    let mut spent_before = Spend::default();
+    for record in accounting::load_records(app).iter().filter(|r| !live.contains(r.process_id.as_str())) {
+        if in_scope(cost_scope, record) {
+            spent_before.cost_usd += record.cost_usd;
+        }
+        if in_scope(tokens_scope, record) {
+            spent_before.tokens += record.input_tokens + record.output_tokens + record.cached_tokens;
+        }
+    }
+
+    Some(RunBudget { limits, cost_scope, tokens_scope, spent_before })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(id: &str, task_id: &str, project_id: &str, output_tokens: u64) -> AgentProcess {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "task_id": task_id,
+            "project_id": project_id,
+            "status": "running",
+            "start_time": "0",
+            "kind": "claude",
+            "model": "claude-sonnet-4-5",
+            "usage": { "output_tokens": output_tokens },
+        })).unwrap()
+    }
+
+    fn budget(max_cost_usd: Option<f64>, max_tokens: Option<u64>, scope: BudgetScope) -> RunBudget {
+        RunBudget {
+            limits: AgentBudget { max_cost_usd, max_tokens, ..AgentBudget::default() },
+            cost_scope: scope,
+            tokens_scope: scope,
+            spent_before: Spend::default(),
+        }
+    }
+
+    fn registry(runs: &[&AgentProcess]) -> HashMap<String, AgentProcess> {
+        runs.iter().map(|run| (run.id.clone(), (*run).clone())).collect()
+    }
+
+    #[test]
+    fn streamed_tokens_trip_the_cost_limit_before_a_cost_is_reported() {
+        // 100k output tokens of Sonnet at $15 per million
+        let proc = run("p1", "t1", "proj", 100_000);
+        let processes = registry(&[&proc]);
+        assert!(proc.total_cost_usd.is_none());
+
+        let reason = budget(Some(1.0), None, BudgetScope::Task).exceeded_by(&proc, &processes, 0).unwrap();
+        assert!(reason.contains("$1.5000"), "{}", reason);
+        assert!(budget(Some(2.0), None, BudgetScope::Task).exceeded_by(&proc, &processes, 0).is_none());
+    }
+
+    #[test]
+    fn reported_cost_replaces_the_estimate() {
+        let mut proc = run("p1", "t1", "proj", 100_000);
+        proc.total_cost_usd = Some(0.5);
+        let processes = registry(&[&proc]);
+        assert!(budget(Some(1.0), None, BudgetScope::Task).exceeded_by(&proc, &processes, 0).is_none());
+    }
+
+    #[test]
+    fn limits_count_every_run_in_their_scope() {
+        let proc = run("p1", "t1", "proj", 400);
+        let same_task = run("p2", "t1", "proj", 400);
+        let same_project = run("p3", "t2", "proj", 400);
+        let other_project = run("p4", "t3", "other", 5000);
+        let processes = registry(&[&proc, &same_task, &same_project, &other_project]);
+
+        let task = budget(None, Some(1000), BudgetScope::Task);
+        assert_eq!(task.spent(&proc, &processes).tokens, 800);
+        assert!(task.exceeded_by(&proc, &processes, 0).is_none());
+
+        let project = budget(None, Some(1000), BudgetScope::Project);
+        assert_eq!(project.spent(&proc, &processes).tokens, 1200);
+        assert!(project.exceeded_by(&proc, &processes, 0).unwrap().contains("project"));
+
+        // Runs only left in the ledger count through the snapshot taken at launch
+        let mut earlier = budget(None, Some(1000), BudgetScope::Task);
+        earlier.spent_before.tokens = 300;
+        assert!(earlier.exceeded_by(&proc, &processes, 0).is_some());
+    }
+}
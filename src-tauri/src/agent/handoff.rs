@@ -0,0 +1,183 @@
+//! Handoff between agent profiles.
+//!
+//! Continuing a conversation on another backend normally replays the last 20
+//! raw messages, tool output included. In handoff mode the outgoing agent is
+//! first asked for a structured summary (goal, what is done, files touched,
+//! open issues) in a run of its own. When that run ends, the new agent starts
+//! with the summary as its context. The summary is recorded as a `handoff`
+//! message at the top of the new run. If the summary run fails, a rougher
+//! summary is built from the transcript instead.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::{model, AgentMessage, AgentProcess};
+
+/// Prompt for the outgoing agent's summary run
+pub const SUMMARY_PROMPT: &str = "Another agent is taking over this task. Do not change any files. Reply with only a JSON object summarising the work so far, with the keys \"goal\" (string), \"done\" (list of strings), \"files\" (list of paths you created or changed) and \"open_issues\" (list of strings).";
+
+/// Longest agent reply quoted in a summary built from the transcript
+const MAX_FALLBACK_CHARS: usize = 2000;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Handoff {
+    pub goal: String,
+    pub done: Vec<String>,
+    pub files: Vec<String>,
+    pub open_issues: Vec<String>,
+}
+
+impl Handoff {
+    /// Reads the JSON object in an agent reply, which may be wrapped in prose or a code fence
+    pub fn parse(reply: &str) -> Option<Self> {
+        let start = reply.find('{')?;
+        let end = reply.rfind('}')?;
+        if end <= start {
+            return None;
+        }
+        let handoff: Handoff = serde_json::from_str(&reply[start..=end]).ok()?;
+        (!handoff.goal.trim().is_empty() || !handoff.done.is_empty()).then_some(handoff)
+    }
+
+    /// Rough summary of a run taken from its transcript
+    pub fn from_transcript(process: &AgentProcess) -> Self {
+        let mut files: Vec<String> = Vec::new();
+        for message in process.messages.iter().filter(|m| m.message_type == "file_edit") {
+            if let Some(path) = message.metadata.as_ref().and_then(find_path) {
+                if !files.contains(&path) {
+                    files.push(path);
+                }
+            }
+        }
+        let last_reply = process.messages.iter()
+            .rev()
+            .find(|m| m.sender == "agent" && matches!(m.message_type.as_str(), "text" | "agent_message") && !m.content.trim().is_empty())
+            .map(|m| m.content.chars().take(MAX_FALLBACK_CHARS).collect::<String>());
+        Handoff {
+            goal: process.prompt.clone(),
+            done: last_reply.into_iter().collect(),
+            files,
+            open_issues: vec!["The outgoing agent did not produce a summary; this one was built from its transcript.".to_string()],
+        }
+    }
+
+    /// Text given to the incoming agent and shown in the transcript
+    pub fn to_context(&self, from_agent: &str) -> String {
+        let section = |title: &str, items: &[String]| {
+            if items.is_empty() {
+                format!("{}: none", title)
+            } else {
+                format!("{}:\n{}", title, items.iter().map(|i| format!("- {}", i)).collect::<Vec<_>>().join("\n"))
+            }
+        };
+        format!(
+            "Handoff from {}\nGoal: {}\n{}\n{}\n{}",
+            from_agent,
+            self.goal.trim(),
+            section("Done", &self.done),
+            section("Files touched", &self.files),
+            section("Open issues", &self.open_issues)
+        )
+    }
+}
+
+/// First `file_path`/`path` string in a tool-use payload
+fn find_path(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Object(map) => map.get("file_path")
+            .or_else(|| map.get("path"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .or_else(|| map.values().find_map(find_path)),
+        serde_json::Value::Array(items) => items.iter().find_map(find_path),
+        _ => None,
+    }
+}
+
+/// The run to start once a summary run ends
+#[derive(Debug, Clone)]
+pub struct PendingHandoff {
+    /// Run the summary was asked from
+    pub from_process_id: String,
+    pub profile: String,
+    pub message: String,
+    pub worktree_path: String,
+    pub choice: model::ModelChoice,
+    pub images: Vec<String>,
+}
+
+// Summary runs in flight, by process id; like the run queue, not kept across restarts
+static PENDING: OnceLock<Mutex<HashMap<String, PendingHandoff>>> = OnceLock::new();
+
+fn pending() -> &'static Mutex<HashMap<String, PendingHandoff>> {
+    PENDING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub fn register(summary_process_id: &str, handoff: PendingHandoff) {
+    pending().lock().unwrap().insert(summary_process_id.to_string(), handoff);
+}
+
+pub fn is_pending(summary_process_id: &str) -> bool {
+    pending().lock().unwrap().contains_key(summary_process_id)
+}
+
+pub fn take(summary_process_id: &str) -> Option<PendingHandoff> {
+    pending().lock().unwrap().remove(summary_process_id)
+}
+
+/// Drops the handoff of a summary run that was cancelled before it started;
+/// returns whether one was waiting
+pub fn cancel(summary_process_id: &str) -> bool {
+    take(summary_process_id).is_some()
+}
+
+/// The summary in a finished summary run, if it produced one
+pub fn summary_of(process: &AgentProcess) -> Option<Handoff> {
+    process.messages.iter()
+        .rev()
+        .filter(|m| m.sender == "agent" && !m.content.trim().is_empty())
+        .find_map(|m| Handoff::parse(&m.content))
+}
+
+/// Transcript entry carrying the summary
+pub fn handoff_message(handoff: &Handoff, from: &AgentProcess, to_kind: &str, from_transcript: bool) -> AgentMessage {
+    AgentMessage {
+        id: super::generate_message_id(),
+        sender: "system".to_string(),
+        content: handoff.to_context(from.kind.as_str()),
+        timestamp: super::get_timestamp(),
+        message_type: "handoff".to_string(),
+        metadata: Some(serde_json::json!({
+            "from_process_id": from.id,
+            "from_kind": from.kind.as_str(),
+            "to_kind": to_kind,
+            "summary": handoff,
+            "from_transcript": from_transcript
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancelled_summary_run_drops_its_handoff() {
+        register("proc_summary_cancel", PendingHandoff {
+            from_process_id: "proc_from".to_string(),
+            profile: "codex".to_string(),
+            message: "Carry on".to_string(),
+            worktree_path: "/tmp/wt".to_string(),
+            choice: model::ModelChoice::default(),
+            images: Vec::new(),
+        });
+        assert!(is_pending("proc_summary_cancel"));
+
+        assert!(cancel("proc_summary_cancel"));
+        assert!(!is_pending("proc_summary_cancel"));
+        assert!(!cancel("proc_summary_cancel"));
+    }
+}
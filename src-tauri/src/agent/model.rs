@@ -0,0 +1,63 @@
+//! Model and reasoning effort an agent run is launched with.
+//!
+//! A task may name a `model` and a `reasoning_effort`; a message can override
+//! either for the run it starts. Whatever is left unset falls back to the task,
+//! then to the backend's own defaults (for Claude, `settings.claude.model`).
+//! Once the CLI reports the model it actually uses, that replaces the requested
+//! one on the process.
+
+use serde::{Deserialize, Serialize};
+
+use super::records;
+
+/// Reasoning effort levels offered in the UI, lowest first
+pub const REASONING_EFFORTS: [&str; 3] = ["low", "medium", "high"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelChoice {
+    pub model: Option<String>,
+    /// One of `REASONING_EFFORTS`
+    pub reasoning_effort: Option<String>,
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+impl ModelChoice {
+    /// Builds a choice from raw command arguments, dropping blank values
+    pub fn new(model: Option<String>, reasoning_effort: Option<String>) -> Self {
+        ModelChoice {
+            model: non_empty(model),
+            reasoning_effort: non_empty(reasoning_effort).map(|e| e.to_lowercase()),
+        }
+    }
+
+    /// Fills unset fields from `fallback`
+    pub fn or(self, fallback: ModelChoice) -> Self {
+        ModelChoice {
+            model: self.model.or(fallback.model),
+            reasoning_effort: self.reasoning_effort.or(fallback.reasoning_effort),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.reasoning_effort {
+            Some(effort) if !REASONING_EFFORTS.contains(&effort.as_str()) => Err(format!(
+                "Unknown reasoning effort '{}', expected one of {}",
+                effort,
+                REASONING_EFFORTS.join(", ")
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Model choice for a run of a task: `overrides` first, then the task's fields
+pub fn resolve_model_choice(app: &tauri::AppHandle, task_id: &str, overrides: ModelChoice) -> ModelChoice {
+    let task = records::find_task(app, task_id)
+        .map(|record| ModelChoice::new(record.task_field("model"), record.task_field("reasoning_effort")))
+        .unwrap_or_default();
+    overrides.or(task)
+}
@@ -0,0 +1,226 @@
+//! Per-project knowledge notes shared with agents.
+//!
+//! Each project has a notes document in `project_notes.json` (next to
+//! `projects.json`), keyed by project id. With `inject_into_prompts` set it is
+//! prepended to the first prompt of every run, together with an instruction to
+//! propose additions inside `<project-note>` tags. Agent text carrying such a
+//! tag becomes a pending proposal; it only joins the notes once someone
+//! approves it on the board.
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri_plugin_store::StoreExt;
+
+use super::{generate_message_id, get_timestamp, records, AgentMessage};
+
+const NOTES_FILE: &str = "project_notes.json";
+const OPEN_TAG: &str = "<project-note>";
+const CLOSE_TAG: &str = "</project-note>";
+
+// Serializes read-modify-write cycles on the notes store across reader threads
+static NOTES_LOCK: Mutex<()> = Mutex::new(());
+
+/// An addition to the notes suggested by an agent, waiting for review
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoteProposal {
+    pub id: String,
+    pub content: String,
+    pub task_id: String,
+    pub process_id: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectNotes {
+    pub content: String,
+    pub inject_into_prompts: bool,
+    pub proposals: Vec<NoteProposal>,
+}
+
+pub fn load_notes(app: &tauri::AppHandle, project_id: &str) -> ProjectNotes {
+    app.store(NOTES_FILE)
+        .ok()
+        .and_then(|store| store.get(project_id))
+        .and_then(|val| serde_json::from_value(val).ok())
+        .unwrap_or_default()
+}
+
+fn store_notes(app: &tauri::AppHandle, project_id: &str, notes: &ProjectNotes) -> Result<(), String> {
+    let store = app.store(NOTES_FILE).map_err(|e| e.to_string())?;
+    store.set(project_id, serde_json::to_value(notes).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Saves the document and the injection switch, keeping pending proposals
+pub fn save_notes(app: &tauri::AppHandle, project_id: &str, content: String, inject_into_prompts: bool) -> Result<ProjectNotes, String> {
+    let _guard = NOTES_LOCK.lock().unwrap();
+    let mut notes = load_notes(app, project_id);
+    notes.content = content;
+    notes.inject_into_prompts = inject_into_prompts;
+    store_notes(app, project_id, &notes)?;
+    Ok(notes)
+}
+
+/// Approves (appending `content`, or the proposal as written) or rejects a proposal
+pub fn resolve_proposal(
+    app: &tauri::AppHandle,
+    project_id: &str,
+    proposal_id: &str,
+    approve: bool,
+    content: Option<String>,
+) -> Result<ProjectNotes, String> {
+    let _guard = NOTES_LOCK.lock().unwrap();
+    let mut notes = load_notes(app, project_id);
+    let index = notes
+        .proposals
+        .iter()
+        .position(|p| p.id == proposal_id)
+        .ok_or_else(|| "Proposal not found".to_string())?;
+    let proposal = notes.proposals.remove(index);
+    if approve {
+        let addition = content.unwrap_or(proposal.content);
+        let addition = addition.trim();
+        if !addition.is_empty() {
+            if !notes.content.trim().is_empty() {
+                notes.content = format!("{}\n\n", notes.content.trim_end());
+            }
+            notes.content.push_str(addition);
+        }
+    }
+    store_notes(app, project_id, &notes)?;
+    Ok(notes)
+}
+
+/// Text put in front of a task's first prompt, if the project shares its notes
+pub fn prompt_preamble(notes: &ProjectNotes) -> Option<String> {
+    if !notes.inject_into_prompts {
+        return None;
+    }
+    let mut preamble = String::new();
+    if !notes.content.trim().is_empty() {
+        preamble.push_str(&format!("Project notes:\n{}\n\n", notes.content.trim()));
+    }
+    preamble.push_str(&format!(
+        "If you learn something about this project that later runs should know, add it to your reply as {}...{}. It is added to the project notes after review.\n\n",
+        OPEN_TAG, CLOSE_TAG
+    ));
+    Some(preamble)
+}
+
+/// Contents of every `<project-note>` block in `text`
+fn extract_proposals(text: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(OPEN_TAG) {
+        let after = &rest[start + OPEN_TAG.len()..];
+        let Some(end) = after.find(CLOSE_TAG) else { break; };
+        let content = after[..end].trim();
+        if !content.is_empty() {
+            found.push(content.to_string());
+        }
+        rest = &after[end + CLOSE_TAG.len()..];
+    }
+    found
+}
+
+/// True for the agent's own reply text (Claude `text`, Codex `agent_message`).
+/// Tool calls and their output are left out: they echo the prompt or the notes
+/// file, tags included, without the agent proposing anything.
+fn is_reply_text(message: &AgentMessage) -> bool {
+    message.sender == "agent" && matches!(message.message_type.as_str(), "text" | "agent_message")
+}
+
+/// Adds the additions not already pending or in the notes; returns how many were added
+fn add_proposals(notes: &mut ProjectNotes, additions: Vec<String>, task_id: &str, process_id: &str) -> usize {
+    let mut added = 0;
+    for content in additions {
+        // The same learning repeated in a later message is not proposed twice
+        if notes.proposals.iter().any(|p| p.content == content) || notes.content.contains(&content) {
+            continue;
+        }
+        notes.proposals.push(NoteProposal {
+            id: generate_message_id(),
+            content,
+            task_id: task_id.to_string(),
+            process_id: process_id.to_string(),
+            created_at: get_timestamp(),
+        });
+        added += 1;
+    }
+    added
+}
+
+/// Records the proposals in an agent reply. Returns a system message to add
+/// to the transcript when any new ones were stored.
+pub fn collect_proposals(app: &tauri::AppHandle, task_id: &str, process_id: &str, message: &AgentMessage) -> Option<AgentMessage> {
+    if !is_reply_text(message) || !message.content.contains(OPEN_TAG) {
+        return None;
+    }
+    let additions = extract_proposals(&message.content);
+    if additions.is_empty() {
+        return None;
+    }
+    let project_id = records::find_task(app, task_id)?
+        .project_field::<String>("id")?;
+
+    let _guard = NOTES_LOCK.lock().unwrap();
+    let mut notes = load_notes(app, &project_id);
+    let count = add_proposals(&mut notes, additions, task_id, process_id);
+    if count == 0 {
+        return None;
+    }
+    if let Err(e) = store_notes(app, &project_id, &notes) {
+        println!("Failed to store note proposals for project {}: {}", project_id, e);
+        return None;
+    }
+
+    Some(AgentMessage {
+        id: generate_message_id(),
+        sender: "system".to_string(),
+        content: format!("Agent proposed {} addition(s) to the project notes; review them in the project settings", count),
+        timestamp: get_timestamp(),
+        message_type: "note_proposal".to_string(),
+        metadata: Some(serde_json::json!({ "project_id": project_id, "count": count })),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent_message(message_type: &str, content: &str) -> AgentMessage {
+        AgentMessage {
+            id: "m1".to_string(),
+            sender: "agent".to_string(),
+            content: content.to_string(),
+            timestamp: "0".to_string(),
+            message_type: message_type.to_string(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn only_reply_text_can_propose() {
+        let text = "<project-note>Run cargo fmt</project-note>";
+        assert!(is_reply_text(&agent_message("text", text)));
+        assert!(is_reply_text(&agent_message("agent_message", text)));
+        assert!(!is_reply_text(&agent_message("file_read", text)));
+        assert!(!is_reply_text(&agent_message("tool_call", text)));
+        assert!(!is_reply_text(&AgentMessage { sender: "system".to_string(), ..agent_message("text", text) }));
+    }
+
+    #[test]
+    fn duplicates_are_not_counted() {
+        let mut notes = ProjectNotes { content: "Use pnpm".to_string(), ..ProjectNotes::default() };
+        let additions = extract_proposals("<project-note>Use pnpm</project-note> <project-note>Run cargo fmt</project-note> <project-note>Run cargo fmt</project-note>");
+        assert_eq!(add_proposals(&mut notes, additions, "t1", "p1"), 1);
+        assert_eq!(notes.proposals.len(), 1);
+        assert_eq!(notes.proposals[0].content, "Run cargo fmt");
+
+        let again = extract_proposals("<project-note>Run cargo fmt</project-note>");
+        assert_eq!(add_proposals(&mut notes, again, "t1", "p2"), 0);
+    }
+}
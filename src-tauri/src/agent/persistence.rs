@@ -0,0 +1,228 @@
+//! Backend-owned persistence of agent transcripts and the process registry.
+//!
+//! Reader threads append each parsed message as one line to a per-process
+//! JSONL log as it arrives, and every status change rewrites the process entry
+//! in `agent_processes.json`, so a transcript survives even when no UI is open
+//! or the app dies mid-run. Whole transcripts (saved at launch or by the UI)
+//! live in the per-process store; loading merges the store with the log.
+
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Manager;
+use tauri_plugin_store::StoreExt;
+
+use super::{AgentMessage, AgentProcess};
+
+const PROCESSES_FILE: &str = "agent_processes.json";
+
+// Serializes read-modify-write cycles on the store files across reader threads
+static PERSIST_LOCK: Mutex<()> = Mutex::new(());
+
+fn process_messages_file(task_id: &str, process_id: &str) -> String {
+    format!("agent_messages_{}_{}.json", task_id, process_id)
+}
+
+/// Append-only log of the messages streamed by a process, next to its store file
+fn process_log_path(app: &tauri::AppHandle, task_id: &str, process_id: &str) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    Ok(dir.join(format!("agent_messages_{}_{}.jsonl", task_id, process_id)))
+}
+
+fn append_line(path: &Path, message: &AgentMessage) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let line = serde_json::to_string(message).map_err(|e| e.to_string())?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path).map_err(|e| e.to_string())?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())
+}
+
+/// Messages in a log; a line cut short by a crash is skipped
+fn read_log(path: &Path) -> Vec<AgentMessage> {
+    let Ok(file) = std::fs::File::open(path) else { return Vec::new(); };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<AgentMessage>(&line).ok())
+        .collect()
+}
+
+/// Stored messages followed by the logged ones they do not already contain
+fn merge_log(mut stored: Vec<AgentMessage>, logged: Vec<AgentMessage>) -> Vec<AgentMessage> {
+    let mut seen: HashSet<String> = stored.iter().map(|m| m.id.clone()).collect();
+    for message in logged {
+        if seen.insert(message.id.clone()) {
+            stored.push(message);
+        }
+    }
+    stored
+}
+
+/// Summary of a process as listed to the UI and stored in `agent_processes.json`
+pub fn process_summary(proc: &AgentProcess) -> serde_json::Value {
+    serde_json::json!({
+        "id": proc.id,
+        "task_id": proc.task_id,
+        "project_id": proc.project_id,
+        "status": proc.status,
+        "start_time": proc.start_time,
+        "end_time": proc.end_time,
+        "message_count": proc.messages.len(),
+        "kind": proc.kind.as_str(),
+        "session_id": proc.session_id,
+        "parent_process_id": proc.parent_process_id,
+        "policy": proc.policy,
+        "policy_summary": proc.policy.as_ref().map(|p| p.summary()),
+        "worktree_path": proc.worktree_path,
+        "total_cost_usd": proc.total_cost_usd,
+        "num_turns": proc.num_turns,
+        "usage": proc.usage,
+        "budget": proc.budget,
+        "exit_reason": proc.exit_reason,
+        "queue_position": proc.queue_position,
+        "prompt": proc.prompt,
+        "last_output_at": proc.last_output_at,
+        "retry_of": proc.retry_of,
+        "attempt": proc.attempt,
+        "max_attempts": proc.max_attempts,
+        "model": proc.model,
+        "reasoning_effort": proc.reasoning_effort,
+        "attachments": proc.attachments,
+        "paused_secs": proc.paused_secs,
+        "paused_at": proc.paused_at
+    })
+}
+
+/// Inserts or replaces the registry entry for a process
+pub fn save_process(app: &tauri::AppHandle, proc: &AgentProcess) -> Result<(), String> {
+    let _guard = PERSIST_LOCK.lock().unwrap();
+    let store = app.store(PROCESSES_FILE).map_err(|e| e.to_string())?;
+    let mut entries = match store.get("processes") {
+        Some(serde_json::Value::Array(entries)) => entries,
+        _ => Vec::new(),
+    };
+
+    let summary = process_summary(proc);
+    match entries.iter_mut().find(|e| e.get("id").and_then(|v| v.as_str()) == Some(proc.id.as_str())) {
+        Some(existing) => *existing = summary,
+        None => entries.push(summary),
+    }
+
+    store.set("processes", serde_json::Value::Array(entries));
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Replaces the stored transcript of a process. The log is cleared, since
+/// `messages` already holds everything in it.
+pub fn save_messages(app: &tauri::AppHandle, task_id: &str, process_id: &str, messages: &[AgentMessage]) -> Result<(), String> {
+    let _guard = PERSIST_LOCK.lock().unwrap();
+    let store = app.store(process_messages_file(task_id, process_id)).map_err(|e| e.to_string())?;
+    let val = serde_json::to_value(messages).map_err(|e| e.to_string())?;
+    store.set("messages", val);
+    store.save().map_err(|e| e.to_string())?;
+    let log = process_log_path(app, task_id, process_id)?;
+    if log.exists() {
+        std::fs::remove_file(&log).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Appends one message to the log of a process without touching the rest of
+/// its transcript
+pub fn append_message(app: &tauri::AppHandle, task_id: &str, process_id: &str, message: &AgentMessage) -> Result<(), String> {
+    let path = process_log_path(app, task_id, process_id)?;
+    let _guard = PERSIST_LOCK.lock().unwrap();
+    append_line(&path, message)
+}
+
+/// Loads the transcript of a process: the stored messages, then any logged
+/// messages they do not already contain
+pub fn load_messages(app: &tauri::AppHandle, task_id: &str, process_id: &str) -> Vec<AgentMessage> {
+    let _guard = PERSIST_LOCK.lock().unwrap();
+    let stored = app.store(process_messages_file(task_id, process_id))
+        .ok()
+        .and_then(|store| store.get("messages"))
+        .and_then(|val| serde_json::from_value::<Vec<AgentMessage>>(val).ok())
+        .unwrap_or_default();
+    let logged = process_log_path(app, task_id, process_id)
+        .map(|path| read_log(&path))
+        .unwrap_or_default();
+    merge_log(stored, logged)
+}
+
+/// Loads every process in the registry together with its stored transcript.
+/// Entries that fail to parse are skipped.
+pub fn load_processes(app: &tauri::AppHandle) -> Vec<AgentProcess> {
+    let entries = {
+        let _guard = PERSIST_LOCK.lock().unwrap();
+        match app.store(PROCESSES_FILE).ok().and_then(|store| store.get("processes")) {
+            Some(serde_json::Value::Array(entries)) => entries,
+            _ => Vec::new(),
+        }
+    };
+
+    entries
+        .into_iter()
+        .filter_map(|entry| match serde_json::from_value::<AgentProcess>(entry) {
+            Ok(mut proc) => {
+                proc.messages = load_messages(app, &proc.task_id, &proc.id);
+                Some(proc)
+            }
+            Err(e) => {
+                println!("Skipping unreadable process entry: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: &str, content: &str) -> AgentMessage {
+        AgentMessage {
+            id: id.to_string(),
+            sender: "agent".to_string(),
+            content: content.to_string(),
+            timestamp: "0".to_string(),
+            message_type: "text".to_string(),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn summary_round_trips_attachments() {
+        let mut proc: AgentProcess = serde_json::from_value(serde_json::json!({
+            "id": "p1", "task_id": "t1", "status": "completed", "start_time": "0",
+        })).unwrap();
+        proc.attachments.files = vec!["src/main.rs".to_string()];
+        proc.attachments.images = vec!["/data/attachments/t1/1-shot.png".to_string()];
+
+        let restored: AgentProcess = serde_json::from_value(process_summary(&proc)).unwrap();
+        assert_eq!(restored.attachments, proc.attachments);
+    }
+
+    #[test]
+    fn log_appends_and_merges_behind_stored_messages() {
+        let dir = std::env::temp_dir().join(format!("agent-board-log-{}", std::process::id()));
+        let path = dir.join("agent_messages_t1_p1.jsonl");
+        let _ = std::fs::remove_file(&path);
+
+        append_line(&path, &message("m1", "first")).unwrap();
+        append_line(&path, &message("m2", "second")).unwrap();
+        // A line cut short by a crash
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"id\":\"m3\"").unwrap();
+
+        let logged = read_log(&path);
+        assert_eq!(logged.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), vec!["m1", "m2"]);
+
+        let merged = merge_log(vec![message("m0", "prompt"), message("m1", "first")], logged);
+        assert_eq!(merged.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(), vec!["m0", "m1", "m2"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,111 @@
+//! Agent permission and tool policy.
+//!
+//! A policy is stored on each project (`agent_policy`) and may be replaced for a
+//! single task (`agent_policy` on the task). The runner resolves it at spawn time
+//! and each backend translates it into its own CLI flags. The defaults match the
+//! flags the app used before policies existed.
+
+use serde::{Deserialize, Serialize};
+
+use super::records;
+
+/// How much the agent may do without asking
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionMode {
+    /// Skip all permission prompts
+    #[default]
+    Bypass,
+    /// Apply file edits without asking, ask for everything else
+    AcceptEdits,
+    /// Plan only, no changes
+    Plan,
+    /// Ask before any tool that needs permission
+    Ask,
+}
+
+impl PermissionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PermissionMode::Bypass => "bypass",
+            PermissionMode::AcceptEdits => "accept_edits",
+            PermissionMode::Plan => "plan",
+            PermissionMode::Ask => "ask",
+        }
+    }
+}
+
+/// File system access granted to the agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxLevel {
+    ReadOnly,
+    /// Writes limited to the worktree
+    WorkspaceWrite,
+    #[default]
+    FullAccess,
+}
+
+impl SandboxLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SandboxLevel::ReadOnly => "read_only",
+            SandboxLevel::WorkspaceWrite => "workspace_write",
+            SandboxLevel::FullAccess => "full_access",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentPolicy {
+    pub allowed_tools: Vec<String>,
+    pub disallowed_tools: Vec<String>,
+    pub permission_mode: PermissionMode,
+    pub sandbox: SandboxLevel,
+    pub network: bool,
+}
+
+impl Default for AgentPolicy {
+    fn default() -> Self {
+        AgentPolicy {
+            // Bash is left out so modes that route prompts ask before running commands;
+            // bypass skips prompts either way
+            allowed_tools: ["Read", "Write", "Edit", "MultiEdit"].iter().map(|s| s.to_string()).collect(),
+            disallowed_tools: Vec::new(),
+            permission_mode: PermissionMode::default(),
+            sandbox: SandboxLevel::default(),
+            network: true,
+        }
+    }
+}
+
+impl AgentPolicy {
+    /// True when tool permission prompts should be answered from the board
+    /// rather than skipped (bypass) or never raised (plan)
+    pub fn routes_permissions(&self) -> bool {
+        matches!(self.permission_mode, PermissionMode::AcceptEdits | PermissionMode::Ask)
+    }
+
+    /// Short description shown in the process header
+    pub fn summary(&self) -> String {
+        format!(
+            "{} · {} · net {}",
+            self.permission_mode.as_str(),
+            self.sandbox.as_str(),
+            if self.network { "on" } else { "off" }
+        )
+    }
+}
+
+/// Policy in effect for a task: the task's own policy if it has one, otherwise
+/// its project's, otherwise the default.
+pub fn resolve_policy(app: &tauri::AppHandle, task_id: &str) -> AgentPolicy {
+    match records::find_task(app, task_id) {
+        Some(record) => record
+            .task_field::<AgentPolicy>("agent_policy")
+            .or_else(|| record.project_field::<AgentPolicy>("agent_policy"))
+            .unwrap_or_default(),
+        None => AgentPolicy::default(),
+    }
+}
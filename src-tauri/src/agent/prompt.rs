@@ -0,0 +1,258 @@
+//! Prompt templates for the first message of a task's agent run.
+//!
+//! A project may set `prompt_template`; without one the prompt stays
+//! `{title}: {description}`. Placeholders are `{{title}}`, `{{description}}`,
+//! `{{base_branch}}`, `{{branch}}`, `{{project_name}}`, `{{labels}}` and
+//! `{{file:<path>}}`, which inlines a file relative to the worktree (or the
+//! project directory when there is no worktree yet). Unknown placeholders are
+//! left as written. Shared project notes (see `notes`) go in front of it.
+
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use super::{notes, records};
+
+/// Largest file inlined by `{{file:...}}`; longer files are cut off
+const MAX_INCLUDE_BYTES: usize = 64 * 1024;
+
+/// Values the placeholders are replaced with
+#[derive(Debug, Clone, Default)]
+pub struct PromptContext {
+    pub title: String,
+    pub description: String,
+    pub base_branch: String,
+    pub branch: String,
+    pub project_name: String,
+    pub labels: Vec<String>,
+    /// Directory `{{file:...}}` paths are resolved against
+    pub root: PathBuf,
+}
+
+impl PromptContext {
+    /// Context of a stored task. `worktree_path` is where the run happens, if
+    /// the worktree exists already.
+    pub fn for_task(app: &tauri::AppHandle, task_id: &str, worktree_path: Option<&str>) -> Result<(Self, Option<String>), String> {
+        let record = records::find_task(app, task_id).ok_or_else(|| format!("Task {} not found", task_id))?;
+        let project_path = record.project_field::<String>("project_path").unwrap_or_default();
+        let worktree_path = worktree_path
+            .map(|s| s.to_string())
+            .or_else(|| record.task_field::<String>("worktree_path"))
+            .filter(|p| !p.trim().is_empty());
+        let branch = worktree_path
+            .as_deref()
+            .and_then(current_branch)
+            .unwrap_or_else(|| format!("task/{}", task_id));
+        let context = PromptContext {
+            title: record.task_field("title").unwrap_or_default(),
+            description: record.task_field("description").unwrap_or_default(),
+            base_branch: record.task_field("base_branch").unwrap_or_else(|| "main".to_string()),
+            branch,
+            project_name: record.project_field("name").unwrap_or_default(),
+            labels: record.task_field("labels").unwrap_or_default(),
+            root: PathBuf::from(worktree_path.as_deref().unwrap_or(&project_path)),
+        };
+        Ok((context, record.project_field::<String>("prompt_template")))
+    }
+
+    /// Stand-in values for previewing a template before any task exists
+    pub fn sample(project_name: &str, project_path: &str) -> Self {
+        PromptContext {
+            title: "Example task".to_string(),
+            description: "Description of the example task.".to_string(),
+            base_branch: "main".to_string(),
+            branch: "task/example".to_string(),
+            project_name: project_name.to_string(),
+            labels: vec!["example".to_string()],
+            root: PathBuf::from(project_path),
+        }
+    }
+}
+
+/// Branch checked out in a worktree
+fn current_branch(worktree_path: &str) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .current_dir(worktree_path)
+        .output()
+        .ok()?;
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !branch.is_empty() && branch != "HEAD").then_some(branch)
+}
+
+/// Contents of an included file. Paths must stay inside `root`, also once
+/// symlinks are followed.
+pub(super) fn include_file(root: &Path, relative: &str) -> String {
+    let relative = Path::new(relative.trim());
+    let escapes = relative.is_absolute()
+        || relative.components().any(|c| matches!(c, Component::ParentDir | Component::Prefix(_)));
+    if escapes {
+        return format!("[file outside the worktree: {}]", relative.display());
+    }
+    let (Ok(root), Ok(path)) = (root.canonicalize(), root.join(relative).canonicalize()) else {
+        return format!("[missing file: {}]", relative.display());
+    };
+    if !path.starts_with(&root) {
+        return format!("[file outside the worktree: {}]", relative.display());
+    }
+    match std::fs::read(&path) {
+        Ok(bytes) => {
+            let mut text = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_INCLUDE_BYTES)]).into_owned();
+            if bytes.len() > MAX_INCLUDE_BYTES {
+                text.push_str("\n[truncated]");
+            }
+            text
+        }
+        Err(_) => format!("[missing file: {}]", relative.display()),
+    }
+}
+
+/// Replaces the placeholders of `template`
+pub fn render(template: &str, context: &PromptContext) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            output.push_str(&rest[start..]);
+            return output;
+        };
+        let name = after[..end].trim();
+        let value = match name {
+            "title" => Some(context.title.clone()),
+            "description" => Some(context.description.clone()),
+            "base_branch" => Some(context.base_branch.clone()),
+            "branch" => Some(context.branch.clone()),
+            "project_name" => Some(context.project_name.clone()),
+            "labels" => Some(context.labels.join(", ")),
+            _ => name.strip_prefix("file:").map(|path| include_file(&context.root, path)),
+        };
+        match value {
+            Some(value) => output.push_str(&value),
+            None => output.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// First prompt of a task's run: the project's notes, if shared, followed by
+/// the project's template rendered for the task (or `{title}: {description}`)
+pub fn task_prompt(app: &tauri::AppHandle, task_id: &str, title: &str, description: &str, worktree_path: &str) -> String {
+    let preamble = records::find_task(app, task_id)
+        .and_then(|record| record.project_field::<String>("id"))
+        .and_then(|project_id| notes::prompt_preamble(&notes::load_notes(app, &project_id)))
+        .unwrap_or_default();
+    format!("{}{}", preamble, task_body(app, task_id, title, description, worktree_path))
+}
+
+fn task_body(app: &tauri::AppHandle, task_id: &str, title: &str, description: &str, worktree_path: &str) -> String {
+    let fallback = format!("{}: {}", title, description);
+    match PromptContext::for_task(app, task_id, Some(worktree_path)) {
+        Ok((mut context, Some(template))) if !template.trim().is_empty() => {
+            // The caller's copy is the one just edited in the UI
+            context.title = title.to_string();
+            context.description = description.to_string();
+            render(&template, &context)
+        }
+        Ok(_) => fallback,
+        Err(e) => {
+            println!("Prompt template not applied: {}", e);
+            fallback
+        }
+    }
+}
+
+/// Renders an unsaved template for the project settings preview, against a
+/// task when one is given and stand-in values otherwise
+pub fn preview(app: &tauri::AppHandle, project_id: &str, template: &str, task_id: Option<&str>) -> Result<String, String> {
+    let context = match task_id {
+        Some(task_id) => PromptContext::for_task(app, task_id, None)?.0,
+        None => {
+            let project = records::load_projects(app)
+                .into_iter()
+                .find(|p| p.get("id").and_then(|v| v.as_str()) == Some(project_id))
+                .ok_or_else(|| format!("Project {} not found", project_id))?;
+            let text = |key: &str| project.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            PromptContext::sample(&text("name"), &text("project_path"))
+        }
+    };
+    Ok(render(template, &context))
+}
+
+/// Reply asking a task's agent to resolve the conflicts a merge into the base
+/// branch ran into. The agent merges the base branch into its own branch, so
+/// the board's merge goes through cleanly afterwards.
+pub fn conflict_prompt(base_branch: &str, paths: &[String]) -> String {
+    let files = paths.iter().map(|p| format!("- {}", p)).collect::<Vec<_>>().join("\n");
+    format!(
+        "Merging this task's branch into '{base}' runs into conflicts in these files:\n{files}\n\n\
+         Resolve these conflicts: run `git merge {base}` in this worktree, resolve every conflict so both \
+         '{base}' and this task's changes keep working, then commit the merge. Do not rebase or push.",
+        base = base_branch,
+        files = files
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh empty directory under the system temp dir
+    fn temp_root(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("agent-board-prompt-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn context(root: PathBuf) -> PromptContext {
+        PromptContext { title: "Fix login".to_string(), labels: vec!["bug".to_string(), "ui".to_string()], root, ..Default::default() }
+    }
+
+    #[test]
+    fn renders_known_placeholders_and_keeps_unknown_ones() {
+        let rendered = render("{{title}} [{{ labels }}] {{owner}}", &context(PathBuf::new()));
+        assert_eq!(rendered, "Fix login [bug, ui] {{owner}}");
+    }
+
+    #[test]
+    fn keeps_unterminated_placeholder() {
+        assert_eq!(render("{{title}} and {{title", &context(PathBuf::new())), "Fix login and {{title");
+    }
+
+    #[test]
+    fn includes_files_inside_the_root() {
+        let root = temp_root("include");
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/notes.md"), "notes").unwrap();
+        assert_eq!(render("{{file:docs/notes.md}}", &context(root.clone())), "notes");
+        assert_eq!(include_file(&root, "missing.md"), "[missing file: missing.md]");
+    }
+
+    #[test]
+    fn rejects_paths_outside_the_root() {
+        let root = temp_root("escape");
+        assert_eq!(include_file(&root, "../secret"), "[file outside the worktree: ../secret]");
+        assert_eq!(include_file(&root, "/etc/passwd"), "[file outside the worktree: /etc/passwd]");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_leading_outside_the_root() {
+        let root = temp_root("symlink");
+        let outside = temp_root("symlink-target");
+        std::fs::write(outside.join("secret"), "secret").unwrap();
+        std::os::unix::fs::symlink(outside.join("secret"), root.join("link")).unwrap();
+        assert_eq!(include_file(&root, "link"), "[file outside the worktree: link]");
+    }
+
+    #[test]
+    fn truncates_large_files() {
+        let root = temp_root("large");
+        std::fs::write(root.join("big.txt"), "x".repeat(MAX_INCLUDE_BYTES + 10)).unwrap();
+        let included = include_file(&root, "big.txt");
+        assert_eq!(included, format!("{}\n[truncated]", "x".repeat(MAX_INCLUDE_BYTES)));
+    }
+}
@@ -0,0 +1,81 @@
+//! Read-only access to the project and task records the UI saves in the store
+//! (`projects.json` / `tasks_{project_id}.json`), for settings that live on them.
+
+use std::collections::HashMap;
+use tauri_plugin_store::StoreExt;
+
+/// A task together with the project it belongs to, as stored JSON
+#[derive(Debug, Clone)]
+pub struct TaskRecord {
+    pub project: serde_json::Value,
+    pub task: serde_json::Value,
+}
+
+impl TaskRecord {
+    /// Deserializes a field of the task, if present and well-formed
+    pub fn task_field<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<T> {
+        field(&self.task, key)
+    }
+
+    /// Deserializes a field of the project, if present and well-formed
+    pub fn project_field<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<T> {
+        field(&self.project, key)
+    }
+}
+
+fn field<T: serde::de::DeserializeOwned>(record: &serde_json::Value, key: &str) -> Option<T> {
+    record
+        .get(key)
+        .filter(|v| !v.is_null())
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+}
+
+fn load_array(app: &tauri::AppHandle, file: &str, key: &str) -> Vec<serde_json::Value> {
+    match app.store(file).ok().and_then(|store| store.get(key)) {
+        Some(serde_json::Value::Array(items)) => items,
+        _ => Vec::new(),
+    }
+}
+
+/// All stored projects
+pub fn load_projects(app: &tauri::AppHandle) -> Vec<serde_json::Value> {
+    load_array(app, "projects.json", "projects")
+}
+
+/// Finds a stored task by id, searching every project's task list
+pub fn find_task(app: &tauri::AppHandle, task_id: &str) -> Option<TaskRecord> {
+    for project in load_projects(app) {
+        let Some(project_id) = project.get("id").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let tasks = load_array(app, &format!("tasks_{}.json", project_id), "tasks");
+        if let Some(task) = tasks.into_iter().find(|t| t.get("id").and_then(|v| v.as_str()) == Some(task_id)) {
+            return Some(TaskRecord { project, task });
+        }
+    }
+    None
+}
+
+/// Id of the project a stored task belongs to
+pub fn project_of(app: &tauri::AppHandle, task_id: &str) -> Option<String> {
+    find_task(app, task_id)
+        .and_then(|record| record.project.get("id").and_then(|v| v.as_str()).map(|s| s.to_string()))
+}
+
+/// Project id and title of every stored task, keyed by task id
+pub fn task_index(app: &tauri::AppHandle) -> HashMap<String, (String, String)> {
+    let mut index = HashMap::new();
+    for project in load_projects(app) {
+        let Some(project_id) = project.get("id").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        for task in load_array(app, &format!("tasks_{}.json", project_id), "tasks") {
+            let Some(task_id) = task.get("id").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let title = task.get("title").and_then(|v| v.as_str()).unwrap_or(task_id);
+            index.insert(task_id.to_string(), (project_id.to_string(), title.to_string()));
+        }
+    }
+    index
+}
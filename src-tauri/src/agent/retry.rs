@@ -0,0 +1,59 @@
+//! Automatic retries of failed agent runs.
+//!
+//! A task may carry a `retry_policy`. When a run exits non-zero and its error
+//! output matches one of the retryable patterns (or no patterns are set), the
+//! runner waits for the backoff and starts the next attempt through the same
+//! backend, resuming the session where the backend allows it. Every attempt
+//! records the original run in `retry_of` and its own `attempt` number.
+
+use serde::{Deserialize, Serialize};
+
+use super::{records, AgentProcess};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Total attempts including the first run; 1 disables retries
+    pub max_attempts: u32,
+    /// Wait before the second attempt; doubled for each later one
+    pub backoff_secs: u64,
+    /// Case-insensitive substrings of error output that make a failure
+    /// retryable. Empty means every failure is retried.
+    pub retryable_patterns: Vec<String>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy { max_attempts: 1, backoff_secs: 10, retryable_patterns: Vec::new() }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before starting attempt `next_attempt` (2 for the first retry)
+    pub fn backoff_for(&self, next_attempt: u32) -> u64 {
+        let doublings = next_attempt.saturating_sub(2).min(10);
+        self.backoff_secs.saturating_mul(1 << doublings)
+    }
+
+    /// Whether the failed run's error messages match the retryable patterns
+    pub fn matches(&self, proc: &AgentProcess) -> bool {
+        if self.retryable_patterns.is_empty() {
+            return true;
+        }
+        let patterns: Vec<String> = self.retryable_patterns.iter().map(|p| p.to_lowercase()).collect();
+        proc.messages
+            .iter()
+            .filter(|m| m.message_type == "error")
+            .any(|m| {
+                let content = m.content.to_lowercase();
+                patterns.iter().any(|p| !p.is_empty() && content.contains(p.as_str()))
+            })
+    }
+}
+
+/// Retry policy of a task, or the default (no retries)
+pub fn resolve_retry_policy(app: &tauri::AppHandle, task_id: &str) -> RetryPolicy {
+    records::find_task(app, task_id)
+        .and_then(|record| record.task_field::<RetryPolicy>("retry_policy"))
+        .unwrap_or_default()
+}
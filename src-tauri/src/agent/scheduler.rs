@@ -0,0 +1,229 @@
+//! Concurrency limits for agent runs and the FIFO queue behind them.
+//!
+//! `agent_settings.json` → `settings.max_concurrent_agents` caps the number of
+//! runs alive at once and `max_concurrent_agents` on a project caps that
+//! project's share. A run that would exceed either limit is queued; the runner
+//! pulls the first run that fits whenever a slot frees up. Paused runs and runs
+//! waiting for a permission answer keep their slot.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::backends::{AgentBackend, LaunchSpec};
+use super::{records, settings};
+
+/// A run waiting for (or holding) a slot
+pub struct QueuedRun {
+    pub process_id: String,
+    pub task_id: String,
+    pub project_id: Option<String>,
+    pub backend: Arc<dyn AgentBackend>,
+    pub spec: LaunchSpec,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    queue: Vec<QueuedRun>,
+    /// Runs holding a slot: process id -> project id
+    active: HashMap<String, Option<String>>,
+}
+
+static STATE: OnceLock<Mutex<SchedulerState>> = OnceLock::new();
+
+fn state() -> &'static Mutex<SchedulerState> {
+    STATE.get_or_init(|| Mutex::new(SchedulerState::default()))
+}
+
+/// Concurrency limits read from settings and projects; `None` is unlimited
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    pub global: Option<usize>,
+    pub per_project: HashMap<String, usize>,
+}
+
+impl Limits {
+    fn allows(&self, active: &HashMap<String, Option<String>>, project_id: &Option<String>) -> bool {
+        if self.global.is_some_and(|limit| active.len() >= limit) {
+            return false;
+        }
+        match project_id.as_ref().and_then(|id| self.per_project.get(id).map(|limit| (id, *limit))) {
+            Some((id, limit)) => active.values().filter(|p| p.as_ref() == Some(id)).count() < limit,
+            None => true,
+        }
+    }
+}
+
+/// Reads the current limits. A limit of 0 is treated as unset.
+pub fn load_limits(app: &tauri::AppHandle) -> Limits {
+    let global = settings::load_agent_settings(app).max_concurrent_agents.filter(|n| *n > 0);
+    let per_project = records::load_projects(app)
+        .iter()
+        .filter_map(|project| {
+            let id = project.get("id")?.as_str()?.to_string();
+            let limit = project.get("max_concurrent_agents")?.as_u64().filter(|n| *n > 0)?;
+            Some((id, limit as usize))
+        })
+        .collect();
+    Limits { global: global.map(|n| n as usize), per_project }
+}
+
+impl SchedulerState {
+    fn submit(&mut self, limits: &Limits, run: QueuedRun) -> Option<QueuedRun> {
+        if limits.allows(&self.active, &run.project_id) {
+            self.active.insert(run.process_id.clone(), run.project_id.clone());
+            Some(run)
+        } else {
+            self.queue.push(run);
+            None
+        }
+    }
+
+    fn next_ready(&mut self, limits: &Limits) -> Option<QueuedRun> {
+        let index = self.queue.iter().position(|run| limits.allows(&self.active, &run.project_id))?;
+        let run = self.queue.remove(index);
+        self.active.insert(run.process_id.clone(), run.project_id.clone());
+        Some(run)
+    }
+
+    fn release(&mut self, process_id: &str) -> bool {
+        self.active.remove(process_id).is_some()
+    }
+
+    fn cancel(&mut self, process_id: &str) -> bool {
+        let before = self.queue.len();
+        self.queue.retain(|run| run.process_id != process_id);
+        self.queue.len() != before
+    }
+
+    fn move_to(&mut self, process_id: &str, position: usize) -> Result<(), String> {
+        let index = self
+            .queue
+            .iter()
+            .position(|run| run.process_id == process_id)
+            .ok_or_else(|| "Process is not queued".to_string())?;
+        let run = self.queue.remove(index);
+        let position = position.min(self.queue.len());
+        self.queue.insert(position, run);
+        Ok(())
+    }
+
+    fn queued(&self) -> Vec<(String, String)> {
+        self.queue.iter().map(|run| (run.process_id.clone(), run.task_id.clone())).collect()
+    }
+}
+
+/// Gives the run a slot if one is free and returns it to be started now;
+/// otherwise appends it to the queue and returns `None`
+pub fn submit(limits: &Limits, run: QueuedRun) -> Option<QueuedRun> {
+    state().lock().unwrap().submit(limits, run)
+}
+
+/// Takes the first queued run that fits the limits and gives it a slot
+pub fn next_ready(limits: &Limits) -> Option<QueuedRun> {
+    state().lock().unwrap().next_ready(limits)
+}
+
+/// Frees the slot held by a run. Returns false if it held none.
+pub fn release(process_id: &str) -> bool {
+    state().lock().unwrap().release(process_id)
+}
+
+/// Removes a run from the queue. Returns false if it was not queued.
+pub fn cancel(process_id: &str) -> bool {
+    state().lock().unwrap().cancel(process_id)
+}
+
+/// Moves a queued run to `position` (0 = next to start), clamped to the queue
+pub fn move_to(process_id: &str, position: usize) -> Result<(), String> {
+    state().lock().unwrap().move_to(process_id, position)
+}
+
+/// Queued process ids and tasks in start order
+pub fn queued() -> Vec<(String, String)> {
+    state().lock().unwrap().queued()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::backends::ClaudeBackend;
+    use crate::agent::policy::AgentPolicy;
+
+    fn run(process_id: &str, project_id: Option<&str>) -> QueuedRun {
+        QueuedRun {
+            process_id: process_id.to_string(),
+            task_id: format!("task-{}", process_id),
+            project_id: project_id.map(|id| id.to_string()),
+            backend: Arc::new(ClaudeBackend::default()),
+            spec: LaunchSpec {
+                prompt: String::new(),
+                worktree_path: String::new(),
+                resume_session_id: None,
+                policy: AgentPolicy::default(),
+                route_permissions: false,
+                model: None,
+                reasoning_effort: None,
+                images: Vec::new(),
+            },
+        }
+    }
+
+    fn limits(global: Option<usize>, per_project: &[(&str, usize)]) -> Limits {
+        Limits { global, per_project: per_project.iter().map(|(id, n)| (id.to_string(), *n)).collect() }
+    }
+
+    fn queued_ids(state: &SchedulerState) -> Vec<String> {
+        state.queued().into_iter().map(|(process_id, _)| process_id).collect()
+    }
+
+    #[test]
+    fn queues_runs_over_the_global_limit() {
+        let mut state = SchedulerState::default();
+        let limits = limits(Some(2), &[]);
+        assert!(state.submit(&limits, run("a", None)).is_some());
+        assert!(state.submit(&limits, run("b", None)).is_some());
+        assert!(state.submit(&limits, run("c", None)).is_none());
+        assert_eq!(queued_ids(&state), vec!["c"]);
+        assert!(state.next_ready(&limits).is_none());
+
+        assert!(state.release("a"));
+        assert!(!state.release("a"));
+        assert_eq!(state.next_ready(&limits).map(|r| r.process_id), Some("c".to_string()));
+        assert!(state.queued().is_empty());
+    }
+
+    #[test]
+    fn project_limit_lets_other_projects_pass() {
+        let mut state = SchedulerState::default();
+        let limits = limits(None, &[("p1", 1)]);
+        assert!(state.submit(&limits, run("a", Some("p1"))).is_some());
+        assert!(state.submit(&limits, run("b", Some("p1"))).is_none());
+        assert!(state.submit(&limits, run("c", Some("p2"))).is_some());
+        assert!(state.submit(&limits, run("d", None)).is_some());
+
+        // The first queued run that fits starts; "b" still waits for "a"
+        assert!(state.submit(&limits, run("e", Some("p1"))).is_none());
+        assert!(state.next_ready(&limits).is_none());
+        state.release("a");
+        assert_eq!(state.next_ready(&limits).map(|r| r.process_id), Some("b".to_string()));
+        assert_eq!(queued_ids(&state), vec!["e"]);
+    }
+
+    #[test]
+    fn moves_and_cancels_queued_runs() {
+        let mut state = SchedulerState::default();
+        let limits = limits(Some(0), &[]);
+        for id in ["a", "b", "c"] {
+            state.submit(&limits, run(id, None));
+        }
+        state.move_to("c", 0).unwrap();
+        assert_eq!(queued_ids(&state), vec!["c", "a", "b"]);
+        state.move_to("c", 10).unwrap();
+        assert_eq!(queued_ids(&state), vec!["a", "b", "c"]);
+        assert!(state.move_to("missing", 0).is_err());
+
+        assert!(state.cancel("b"));
+        assert!(!state.cancel("b"));
+        assert_eq!(queued_ids(&state), vec!["a", "c"]);
+    }
+}
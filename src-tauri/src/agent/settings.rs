@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use tauri_plugin_store::StoreExt;
+
+use super::backends::CustomAgentConfig;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CodexSettings {
+    pub command: Option<String>,
+    pub args: Option<Vec<String>>, // extra args before prompt
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ClaudeSettings {
+    pub command: Option<String>, // binary path, e.g. ~/.local/bin/claude
+    pub args: Option<Vec<String>>, // extra flags appended to the defaults
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AgentSettings {
+    pub codex: Option<CodexSettings>,
+    #[serde(default)]
+    pub claude: Option<ClaudeSettings>,
+    #[serde(default)]
+    pub custom_agents: Vec<CustomAgentConfig>, // user-defined agent CLIs
+    #[serde(default)]
+    pub max_concurrent_agents: Option<u32>, // runs alive at once across all projects
+    #[serde(default)]
+    pub stall_timeout_secs: Option<u64>, // seconds without output before a run counts as stalled
+    #[serde(default)]
+    pub retry_on_stall: bool, // kill a stalled run and start it again (once)
+}
+
+/// Reads agent settings from the store, falling back to defaults
+pub fn load_agent_settings(app: &tauri::AppHandle) -> AgentSettings {
+    let store = match app.store("agent_settings.json") {
+        Ok(store) => store,
+        Err(e) => {
+            println!("Failed to open agent settings store: {}", e);
+            return AgentSettings::default();
+        }
+    };
+    match store.get("settings") {
+        Some(val) => serde_json::from_value::<AgentSettings>(val.clone()).unwrap_or_else(|e| {
+            println!("Failed to parse agent settings: {}", e);
+            AgentSettings::default()
+        }),
+        None => AgentSettings::default(),
+    }
+}
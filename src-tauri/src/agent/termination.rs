@@ -0,0 +1,118 @@
+//! Stopping an agent run together with everything it started.
+//!
+//! On Unix each agent is spawned as the leader of its own process group, so the
+//! `bash` shells, test runners and dev servers it launches can be signalled as
+//! one unit. A stop is staged: SIGINT, then SIGTERM after a grace period, then
+//! SIGKILL for whatever is left of the group. On Windows the process tree is
+//! ended with `taskkill /T`. The same group is frozen and thawed with
+//! SIGSTOP/SIGCONT to pause a run (Unix only).
+
+use std::process::{Command, ExitStatus};
+#[cfg(unix)]
+use std::time::{Duration, Instant};
+
+/// Time each stage gets before the next, stronger signal is sent
+#[cfg(unix)]
+const GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+/// Starts the command in a new process group (Unix only)
+pub fn isolate(cmd: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    #[cfg(not(unix))]
+    let _ = cmd;
+}
+
+/// Waits up to `timeout` for `done` to become true
+#[cfg(unix)]
+fn wait_until(timeout: Duration, done: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if done() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    done()
+}
+
+/// True while any process of the group is alive (including an unreaped leader)
+#[cfg(unix)]
+fn group_alive(pgid: u32) -> bool {
+    unsafe { libc::killpg(pgid as libc::pid_t, 0) == 0 }
+}
+
+/// Stops the process group led by `pid`, escalating from SIGINT to SIGTERM to
+/// SIGKILL. Blocks until the group is gone or SIGKILL has been sent.
+#[cfg(unix)]
+pub fn stop_group(pid: u32) {
+    for (signal, name) in [(libc::SIGINT, "SIGINT"), (libc::SIGTERM, "SIGTERM")] {
+        if !group_alive(pid) {
+            return;
+        }
+        println!("Sending {} to process group {}", name, pid);
+        unsafe {
+            libc::killpg(pid as libc::pid_t, signal);
+            // A paused group only sees the signal once it is continued
+            libc::killpg(pid as libc::pid_t, libc::SIGCONT);
+        }
+        if wait_until(GRACE_PERIOD, || !group_alive(pid)) {
+            return;
+        }
+    }
+    if group_alive(pid) {
+        println!("Sending SIGKILL to process group {}", pid);
+        unsafe { libc::killpg(pid as libc::pid_t, libc::SIGKILL) };
+    }
+}
+
+/// Ends the process tree rooted at `pid`
+#[cfg(not(unix))]
+pub fn stop_group(pid: u32) {
+    let mut cmd = Command::new("taskkill");
+    cmd.args(["/PID", &pid.to_string(), "/T", "/F"]);
+    if let Err(e) = cmd.status() {
+        println!("taskkill failed for process {}: {}", pid, e);
+    }
+}
+
+/// Freezes (`pause = true`) or continues the process group led by `pid`
+#[cfg(unix)]
+pub fn pause_group(pid: u32, pause: bool) -> Result<(), String> {
+    let signal = if pause { libc::SIGSTOP } else { libc::SIGCONT };
+    if unsafe { libc::killpg(pid as libc::pid_t, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(format!("Failed to signal process group {}: {}", pid, std::io::Error::last_os_error()))
+    }
+}
+
+#[cfg(not(unix))]
+pub fn pause_group(_pid: u32, _pause: bool) -> Result<(), String> {
+    Err("Pausing agent processes is only supported on Unix".to_string())
+}
+
+/// Human readable reason a child exited, e.g. `exited with code 1` or
+/// `terminated by SIGTERM`
+pub fn exit_reason(status: &ExitStatus) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            let name = match signal {
+                libc::SIGINT => "SIGINT".to_string(),
+                libc::SIGTERM => "SIGTERM".to_string(),
+                libc::SIGKILL => "SIGKILL".to_string(),
+                other => format!("signal {}", other),
+            };
+            return format!("terminated by {}", name);
+        }
+    }
+    match status.code() {
+        Some(code) => format!("exited with code {}", code),
+        None => "exited".to_string(),
+    }
+}
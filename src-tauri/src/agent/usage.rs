@@ -0,0 +1,114 @@
+//! Token usage normalised across backends.
+//!
+//! `input_tokens` are uncached prompt tokens (including cache writes),
+//! `cached_tokens` are prompt tokens served from cache and `output_tokens` are
+//! generated tokens. Backends feed their usage events in from `apply_message`.
+//! Runs whose CLI reports no cost get one estimated from list prices.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_tokens: u64,
+    /// Model turns seen so far
+    pub turns: u32,
+    /// Last counted Claude message and its contribution; Claude repeats a message
+    /// (with growing output) for each content block
+    #[serde(skip)]
+    last_message: Option<(String, [u64; 3])>,
+}
+
+/// USD per million input, output and cached input tokens of a model, falling
+/// back to the backend's usual model when the model is unknown
+fn prices(kind: &str, model: Option<&str>) -> Option<[f64; 3]> {
+    let model = model.unwrap_or("").to_lowercase();
+    let claude = kind == "claude" || model.contains("claude");
+    if claude {
+        return Some(if model.contains("opus-4-5") || model.contains("opus-4.5") {
+            [5.0, 25.0, 0.5]
+        } else if model.contains("opus") {
+            [15.0, 75.0, 1.5]
+        } else if model.contains("haiku") {
+            [1.0, 5.0, 0.1]
+        } else {
+            [3.0, 15.0, 0.3]
+        });
+    }
+    if kind == "codex" || model.starts_with("gpt") {
+        return Some(if model.contains("mini") {
+            [0.25, 2.0, 0.025]
+        } else {
+            [1.25, 10.0, 0.125]
+        });
+    }
+    None
+}
+
+impl TokenUsage {
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cached_tokens
+    }
+
+    /// Cost of the tokens so far at list prices; `None` for backends without a price list
+    pub fn estimated_cost_usd(&self, kind: &str, model: Option<&str>) -> Option<f64> {
+        let [input, output, cached] = prices(kind, model)?;
+        Some((self.input_tokens as f64 * input + self.output_tokens as f64 * output + self.cached_tokens as f64 * cached) / 1_000_000.0)
+    }
+
+    fn add(&mut self, amounts: [u64; 3]) {
+        self.input_tokens += amounts[0];
+        self.output_tokens += amounts[1];
+        self.cached_tokens += amounts[2];
+    }
+
+    fn subtract(&mut self, amounts: [u64; 3]) {
+        self.input_tokens = self.input_tokens.saturating_sub(amounts[0]);
+        self.output_tokens = self.output_tokens.saturating_sub(amounts[1]);
+        self.cached_tokens = self.cached_tokens.saturating_sub(amounts[2]);
+    }
+
+    /// Records the `message.usage` of a Claude stream-json `assistant` event
+    pub fn record_claude(&mut self, event: &serde_json::Value) {
+        let Some(message) = event.get("message") else { return; };
+        let Some(usage) = message.get("usage") else { return; };
+        let read = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        let amounts = [
+            read("input_tokens") + read("cache_creation_input_tokens"),
+            read("output_tokens"),
+            read("cache_read_input_tokens"),
+        ];
+        let message_id = message.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+
+        match self.last_message.take() {
+            // Same message again: replace its earlier contribution
+            Some((last_id, previous)) if last_id == message_id => self.subtract(previous),
+            _ => self.turns += 1,
+        }
+        self.add(amounts);
+        self.last_message = Some((message_id, amounts));
+    }
+
+    /// Records a Codex `token_count` event (`msg` object) or the `usage` of a
+    /// `turn.completed` event. Newer CLIs report a running total in
+    /// `info.total_token_usage`; older ones and `turn.completed` report per-turn usage.
+    pub fn record_codex(&mut self, msg: &serde_json::Value) {
+        let read = |obj: &serde_json::Value, key: &str| obj.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        let split = |obj: &serde_json::Value| {
+            let cached = read(obj, "cached_input_tokens");
+            [read(obj, "input_tokens").saturating_sub(cached), read(obj, "output_tokens"), cached]
+        };
+
+        if let Some(total) = msg.pointer("/info/total_token_usage") {
+            let amounts = split(total);
+            self.input_tokens = amounts[0];
+            self.output_tokens = amounts[1];
+            self.cached_tokens = amounts[2];
+        } else {
+            self.add(split(msg));
+        }
+        self.turns += 1;
+    }
+}
@@ -5,10 +5,12 @@ use tauri::{AppHandle, Manager};
 use serde::{Serialize, Deserialize};
 use std::process::Command;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct GitWorktree {
     pub path: PathBuf,
-    pub _branch_name: String,
+    pub branch_name: String,
+    /// Commit the task branch was created from
+    pub base_commit: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -152,9 +154,10 @@ pub fn get_worktree_uncommitted_diffs(worktree_path: &str) -> Result<Vec<DiffFil
     Ok(files)
 }
 
-/// Get all changes in branch (committed + uncommitted) for diff viewer
-pub fn get_worktree_diffs(worktree_path: &str) -> Result<Vec<DiffFile>, String> {
-    println!("[diffs] worktree_path={} ", worktree_path);
+/// Get all changes in branch (committed + uncommitted) for diff viewer.
+/// `base_commit` is the commit the task branch was created from, when known.
+pub fn get_worktree_diffs(worktree_path: &str, base_commit: Option<&str>) -> Result<Vec<DiffFile>, String> {
+    println!("[diffs] worktree_path={} base_commit={:?}", worktree_path, base_commit);
 
     // Try to get the current branch name
     let branch_output = Command::new("git")
@@ -172,9 +175,9 @@ pub fn get_worktree_diffs(worktree_path: &str) -> Result<Vec<DiffFile>, String>
     // This will show all changes (committed + uncommitted) in this branch
     let diff_base = if let Some(ref branch) = current_branch {
         if branch.starts_with("task/") {
-            // Try to find merge-base with common base branch names
+            // Try to find merge-base with the recorded base, then common base branch names
             let mut found_base = None;
-            for base in &["main", "master"] {
+            for base in base_commit.into_iter().chain(["main", "master"]) {
                 let merge_base_output = Command::new("git")
                     .args(["-C", worktree_path, "merge-base", base, "HEAD"])
                     .output()
@@ -401,11 +404,19 @@ fn get_worktrees_base_dir(app: &AppHandle) -> Result<PathBuf, String> {
 /// * `app` - Tauri app handle for getting app data directory
 /// * `project_path` - Path to the main project repository
 /// * `task_id` - Unique identifier for the task
+/// * `base_ref` - Branch, tag or commit to branch from; `None` uses HEAD
 /// 
 /// # Returns
-/// * `Ok(GitWorktree)` - Contains the path to the created worktree and branch name
+/// * `Ok(GitWorktree)` - Contains the path to the created worktree, branch name and base commit
 /// * `Err(String)` - Error message if worktree creation fails
-pub fn create_worktree(app: &AppHandle, project_path: &str, task_id: &str) -> Result<GitWorktree, String> {
+pub fn create_worktree(app: &AppHandle, project_path: &str, task_id: &str, base_ref: Option<&str>) -> Result<GitWorktree, String> {
+    // Get worktrees base directory (in app data, not temp)
+    let worktrees_base = get_worktrees_base_dir(app)?;
+    add_worktree(project_path, &worktrees_base, task_id, base_ref)
+}
+
+/// Creates the worktree of a task inside `worktrees_base`
+fn add_worktree(project_path: &str, worktrees_base: &Path, task_id: &str, base_ref: Option<&str>) -> Result<GitWorktree, String> {
     println!("Creating worktree for task {} in project {}", task_id, project_path);
     
     // Open the repository
@@ -414,12 +425,13 @@ pub fn create_worktree(app: &AppHandle, project_path: &str, task_id: &str) -> Re
     
     println!("Successfully opened repository: {}", project_path);
 
+    // Resolve the commit to branch from before touching the worktrees directory
+    let base_commit = resolve_base_commit(&repo, base_ref)?;
+
     // Generate branch name following vibe-kanban pattern
     let branch_name = format!("task/{}", task_id);
     
-    // Get worktrees base directory (in app data, not temp)
-    let worktrees_base = get_worktrees_base_dir(app)?;
-    let worktree_dir = worktrees_base.join(&task_id);
+    let worktree_dir = worktrees_base.join(task_id);
     
     println!("Worktree will be created at: {:?}", worktree_dir);
     
@@ -430,16 +442,10 @@ pub fn create_worktree(app: &AppHandle, project_path: &str, task_id: &str) -> Re
             .map_err(|e| format!("Failed to remove existing worktree directory: {}", e))?;
     }
 
-    // Get the current HEAD commit to create branch from
-    let head = repo.head()
-        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
-    let head_commit = head.peel_to_commit()
-        .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
-
-    println!("Creating branch '{}' from commit {}", branch_name, head_commit.id());
+    println!("Creating branch '{}' from commit {}", branch_name, base_commit.id());
 
-    // Create a new branch from HEAD
-    let branch = repo.branch(&branch_name, &head_commit, false)
+    // Create a new branch from the base commit
+    let branch = repo.branch(&branch_name, &base_commit, false)
         .map_err(|e| format!("Failed to create branch '{}': {}", branch_name, e))?;
 
     println!("Successfully created branch: {}", branch_name);
@@ -455,7 +461,7 @@ pub fn create_worktree(app: &AppHandle, project_path: &str, task_id: &str) -> Re
 
     // Create the worktree using git2-rs proper API
     let _worktree = repo.worktree(
-        &task_id,  // worktree name
+        task_id,  // worktree name
         worktree_dir.as_path(),  // worktree path
         Some(&opts)  // options
     ).map_err(|e| format!("Failed to create worktree: {}", e))?;
@@ -464,10 +470,28 @@ pub fn create_worktree(app: &AppHandle, project_path: &str, task_id: &str) -> Re
 
     Ok(GitWorktree {
         path: worktree_dir,
-        _branch_name: branch_name,
+        branch_name,
+        base_commit: base_commit.id().to_string(),
     })
 }
 
+/// Commit a task branch starts from: a local branch, a remote-tracking branch,
+/// a tag or any commit-ish; HEAD when no base is given
+fn resolve_base_commit<'r>(repo: &'r Repository, base_ref: Option<&str>) -> Result<git2::Commit<'r>, String> {
+    let base_ref = base_ref.map(str::trim).filter(|r| !r.is_empty());
+    let Some(base_ref) = base_ref else {
+        let head = repo.head()
+            .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+        return head.peel_to_commit()
+            .map_err(|e| format!("Failed to get HEAD commit: {}", e));
+    };
+    let object = repo.revparse_single(base_ref)
+        .or_else(|_| repo.revparse_single(&format!("origin/{}", base_ref)))
+        .map_err(|_| format!("Base '{}' does not exist in this repository (expected a branch, tag or commit)", base_ref))?;
+    object.peel_to_commit()
+        .map_err(|e| format!("Base '{}' does not point to a commit: {}", base_ref, e))
+}
+
 /// Removes a git worktree and cleans up the branch
 /// 
 /// # Arguments
@@ -600,152 +624,748 @@ pub fn open_worktree_location(worktree_path: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// A file both sides changed in ways that could not be merged automatically.
+/// Sides are `None` when that side deleted the file or it is binary.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConflictFile {
+    pub path: String,
+    /// Contents at the merge base
+    pub base: Option<String>,
+    /// Contents on the base branch
+    pub ours: Option<String>,
+    /// Contents on the task branch
+    pub theirs: Option<String>,
+    /// The file with `<<<<<<<`/`=======`/`>>>>>>>` markers around each conflicting hunk
+    pub merged: Option<String>,
+    pub binary: bool,
+}
+
+/// How to resolve one conflicted path when completing a merge
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConflictResolution {
+    pub path: String,
+    /// Final contents; `None` deletes the file
+    pub content: Option<String>,
+}
+
+/// How a task branch lands on its base branch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Fast-forward when possible, otherwise record a merge commit
+    #[default]
+    FastForwardOrMerge,
+    /// Only move the base branch forward; fails once the base branch has moved on
+    FastForwardOnly,
+    /// Always record a merge commit with both branches as parents
+    MergeCommit,
+    /// One commit on the base branch with all of the task's changes
+    Squash,
+    /// Replay the task's commits on top of the base branch
+    Rebase,
+}
+
+/// Result of merging a task branch
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum MergeOutcome {
+    Merged { message: String },
+    UpToDate { message: String },
+    /// Nothing was changed; resolve `files` and merge again with resolutions
+    Conflicted { base_branch: String, task_branch: String, files: Vec<ConflictFile> },
+}
+
+/// Task branch of a worktree and the commits at the tip of it and of `base_branch`
+fn merge_heads<'r>(repo: &'r Repository, worktree_path: &str, base_branch: &str) -> Result<(String, git2::Commit<'r>, git2::Commit<'r>), String> {
+    // Extract task_id from worktree path to get branch name
+    let task_id = Path::new(worktree_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| "Failed to extract task ID from worktree path".to_string())?;
+    let task_branch = format!("task/{}", task_id);
+
+    let base_commit = repo.find_branch(base_branch, git2::BranchType::Local)
+        .map_err(|e| format!("Failed to find base branch '{}': {}", base_branch, e))?
+        .get()
+        .peel_to_commit()
+        .map_err(|e| format!("Failed to get base branch commit: {}", e))?;
+    let task_commit = repo.find_branch(&task_branch, git2::BranchType::Local)
+        .map_err(|e| format!("Failed to find task branch '{}': {}", task_branch, e))?
+        .get()
+        .peel_to_commit()
+        .map_err(|e| format!("Failed to get task branch commit: {}", e))?;
+    Ok((task_branch, base_commit, task_commit))
+}
+
+/// Conflicts a merge of the task branch into `base_branch` would run into,
+/// without merging anything. Empty when the merge would go through.
+pub fn get_merge_conflicts(worktree_path: &str, base_branch: &str, project_path: &str) -> Result<Vec<ConflictFile>, String> {
+    let repo = Repository::open(project_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let (task_branch, base_commit, task_commit) = merge_heads(&repo, worktree_path, base_branch)?;
+    let index = repo.merge_commits(&base_commit, &task_commit, None)
+        .map_err(|e| format!("Merge failed: {}", e))?;
+    conflict_files(&repo, &index, base_branch, &task_branch)
+}
+
 /// Merges a task branch into the base branch
 ///
+/// The merge happens in memory and only moves the base branch ref; the main
+/// checkout's HEAD is never switched. If the base branch is checked out
+/// somewhere (the main checkout or a linked worktree) that checkout must be
+/// clean. Once the ref has moved, its files follow so it does not show the
+/// merge as reverted changes; if the ref moved underneath the merge, nothing
+/// is touched.
+///
+/// Conflicts, including a rebase merge stopping on one of the task's commits,
+/// leave everything untouched and come back as `MergeOutcome::Conflicted`. Merging again with a resolution for every
+/// conflicted path writes those contents into the merge (or squash) commit;
+/// fast-forward only and rebase merges cannot take resolutions.
+///
 /// # Arguments
 /// * `worktree_path` - Path to the worktree
 /// * `base_branch` - Name of the base branch to merge into
 /// * `project_path` - Path to the main project repository
+/// * `strategy` - How the task branch lands on the base branch
+/// * `message` - Commit message of a squash merge; defaults to one naming the branch
+/// * `resolutions` - Resolved contents of conflicted paths, if any
 ///
 /// # Returns
-/// * `Ok(String)` - Success message with merge details
-/// * `Err(String)` - Error message if merge fails (including conflict details)
-pub fn merge_to_base_branch(worktree_path: &str, base_branch: &str, project_path: &str) -> Result<String, String> {
-    println!("Merging worktree at {} to base branch {}", worktree_path, base_branch);
-
-    // Extract task_id from worktree path to get branch name
-    let worktree_path_obj = Path::new(worktree_path);
-    let task_id = worktree_path_obj
-        .file_name()
-        .and_then(|name| name.to_str())
-        .ok_or_else(|| "Failed to extract task ID from worktree path".to_string())?;
-
-    let task_branch = format!("task/{}", task_id);
-    println!("Task branch: {}", task_branch);
+/// * `Ok(MergeOutcome)` - Merged, already up to date, or the conflicts to resolve
+/// * `Err(String)` - Error message if merge fails
+pub fn merge_to_base_branch(
+    worktree_path: &str,
+    base_branch: &str,
+    project_path: &str,
+    strategy: MergeStrategy,
+    message: Option<&str>,
+    resolutions: &[ConflictResolution],
+) -> Result<MergeOutcome, String> {
+    println!("Merging worktree at {} to base branch {} ({:?})", worktree_path, base_branch, strategy);
 
     // Open the main repository
     let repo = Repository::open(project_path)
         .map_err(|e| format!("Failed to open repository: {}", e))?;
 
-    // Get current branch before switching
-    let current_head = repo.head()
-        .map_err(|e| format!("Failed to get current HEAD: {}", e))?;
-    let current_branch_name = current_head.shorthand().unwrap_or("unknown");
-    println!("Current branch: {}", current_branch_name);
+    let base_ref_name = format!("refs/heads/{}", base_branch);
+    let (task_branch, base_commit, task_commit) = merge_heads(&repo, worktree_path, base_branch)?;
+    println!("Task branch: {}", task_branch);
 
-    // Checkout the base branch
-    println!("Checking out base branch: {}", base_branch);
-    let base_branch_ref = repo.find_branch(base_branch, git2::BranchType::Local)
-        .map_err(|e| format!("Failed to find base branch '{}': {}", base_branch, e))?;
+    // Refuse before doing anything if a checkout of the base branch has local changes
+    let checkouts = checkouts_of_branch(&repo, &base_ref_name)?;
+    for checkout in &checkouts {
+        if is_dirty(checkout)? {
+            let location = checkout.workdir().map(|p| p.display().to_string()).unwrap_or_default();
+            return Err(format!(
+                "'{}' is checked out at {} and has uncommitted changes; commit or stash them before merging",
+                base_branch, location
+            ));
+        }
+        let task_tree = task_commit.tree()
+            .map_err(|e| format!("Failed to read task tree: {}", e))?;
+        let collisions = untracked_collisions(checkout, &task_tree)?;
+        if !collisions.is_empty() {
+            let location = checkout.workdir().map(|p| p.display().to_string()).unwrap_or_default();
+            return Err(format!(
+                "'{}' is checked out at {} and has untracked files the merge would overwrite ({}); move or commit them before merging",
+                base_branch, location, collisions.join(", ")
+            ));
+        }
+    }
 
-    let base_commit = base_branch_ref.get().peel_to_commit()
-        .map_err(|e| format!("Failed to get base branch commit: {}", e))?;
+    // Resolutions describe a single merge result, which a replay of the task's commits has no room for
+    if !resolutions.is_empty() && matches!(strategy, MergeStrategy::FastForwardOnly | MergeStrategy::Rebase) {
+        return Err("Resolved conflicts go into a merge or squash commit; pick one of those strategies to complete the merge".to_string());
+    }
 
-    repo.checkout_tree(base_commit.as_object(), None)
-        .map_err(|e| format!("Failed to checkout base branch: {}", e))?;
+    let merge_base = repo.merge_base(base_commit.id(), task_commit.id())
+        .map_err(|e| format!("Failed to find merge base: {}", e))?;
+    if merge_base == task_commit.id() {
+        return Ok(MergeOutcome::UpToDate { message: "Already up to date, no merge needed".to_string() });
+    }
+    let fast_forward = merge_base == base_commit.id();
 
-    repo.set_head(&format!("refs/heads/{}", base_branch))
-        .map_err(|e| format!("Failed to set HEAD to base branch: {}", e))?;
+    let (new_oid, summary) = match strategy {
+        MergeStrategy::FastForwardOrMerge | MergeStrategy::FastForwardOnly | MergeStrategy::Rebase if fast_forward => {
+            println!("Fast-forward merge possible");
+            (task_commit.id(), format!("Successfully fast-forward merged {} into {}", task_branch, base_branch))
+        }
+        MergeStrategy::FastForwardOnly => {
+            return Err(format!(
+                "'{}' has commits that {} does not; update the task branch first or pick another merge strategy",
+                base_branch, task_branch
+            ));
+        }
+        MergeStrategy::Rebase => {
+            println!("Rebasing in memory");
+            match rebase_commits(&repo, &base_commit, &task_commit, base_branch, &task_branch)? {
+                Rebased::Done { head, count } => {
+                    (head, format!("Successfully rebased {} commit(s) of {} onto {} (head: {})", count, task_branch, base_branch, head))
+                }
+                Rebased::Conflicted(files) => {
+                    println!("Rebase conflict in files: {}", files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>().join(", "));
+                    return Ok(MergeOutcome::Conflicted {
+                        base_branch: base_branch.to_string(),
+                        task_branch,
+                        files,
+                    });
+                }
+            }
+        }
+        MergeStrategy::FastForwardOrMerge | MergeStrategy::MergeCommit | MergeStrategy::Squash => {
+            println!("Performing in-memory merge");
+            let mut index = repo.merge_commits(&base_commit, &task_commit, None)
+                .map_err(|e| format!("Merge failed: {}", e))?;
+            if index.has_conflicts() {
+                let files = conflict_files(&repo, &index, base_branch, &task_branch)?;
+                let resolved = files.iter().all(|f| resolutions.iter().any(|r| r.path == f.path));
+                if !resolved {
+                    println!("Merge conflict in files: {}", files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>().join(", "));
+                    return Ok(MergeOutcome::Conflicted {
+                        base_branch: base_branch.to_string(),
+                        task_branch,
+                        files,
+                    });
+                }
+                apply_resolutions(&repo, &mut index, resolutions)?;
+            }
+            let tree_id = index.write_tree_to(&repo)
+                .map_err(|e| format!("Failed to write tree: {}", e))?;
+            let tree = repo.find_tree(tree_id)
+                .map_err(|e| format!("Failed to find tree: {}", e))?;
+            let signature = repo.signature()
+                .map_err(|e| format!("Failed to get signature: {}", e))?;
+            if strategy == MergeStrategy::Squash {
+                let default_message = format!("Squash merge branch '{}' into {}", task_branch, base_branch);
+                let message = message.map(str::trim).filter(|m| !m.is_empty()).unwrap_or(&default_message);
+                let squash_oid = repo.commit(None, &signature, &signature, message, &tree, &[&base_commit])
+                    .map_err(|e| format!("Failed to create squash commit: {}", e))?;
+                (squash_oid, format!("Successfully squash merged {} into {} (commit: {})", task_branch, base_branch, squash_oid))
+            } else {
+                let merge_commit_oid = repo.commit(
+                    None,
+                    &signature,
+                    &signature,
+                    &format!("Merge branch '{}' into {}", task_branch, base_branch),
+                    &tree,
+                    &[&base_commit, &task_commit],
+                ).map_err(|e| format!("Failed to create merge commit: {}", e))?;
+                (merge_commit_oid, format!("Successfully merged {} into {} (commit: {})", task_branch, base_branch, merge_commit_oid))
+            }
+        }
+    };
 
-    println!("Successfully checked out {}", base_branch);
+    let notes = move_base_branch(
+        &repo,
+        &checkouts,
+        &base_ref_name,
+        base_commit.id(),
+        new_oid,
+        &format!("Merge {} into {}", task_branch, base_branch),
+    )?;
+    let summary = std::iter::once(summary).chain(notes).collect::<Vec<_>>().join("\n\n");
+
+    println!("{}", summary);
+    Ok(MergeOutcome::Merged { message: summary })
+}
 
-    // Find the task branch
-    let task_branch_ref = repo.find_branch(&task_branch, git2::BranchType::Local)
-        .map_err(|e| format!("Failed to find task branch '{}': {}", task_branch, e))?;
+/// Moves `branch_ref` from `from` to `to`, failing if someone else moved it in
+/// the meantime. Only once the ref has moved do clean checkouts of the branch
+/// follow it; returns a note for each checkout that could not be updated.
+fn move_base_branch(
+    repo: &Repository,
+    checkouts: &[Repository],
+    branch_ref: &str,
+    from: git2::Oid,
+    to: git2::Oid,
+    log_message: &str,
+) -> Result<Vec<String>, String> {
+    repo.reference_matching(branch_ref, to, true, from, log_message)
+        .map_err(|e| format!("Failed to update '{}': {}", branch_ref.trim_start_matches("refs/heads/"), e))?;
+
+    let mut notes = Vec::new();
+    for checkout in checkouts {
+        if let Err(e) = sync_checkout(checkout, from, to) {
+            println!("Checkout not updated after merge: {}", e);
+            notes.push(e);
+        }
+    }
+    Ok(notes)
+}
 
-    let task_commit = task_branch_ref.get().peel_to_commit()
-        .map_err(|e| format!("Failed to get task branch commit: {}", e))?;
+/// The main checkout and linked worktrees that have `branch_ref` checked out
+fn checkouts_of_branch(repo: &Repository, branch_ref: &str) -> Result<Vec<Repository>, String> {
+    let mut checkouts = Vec::new();
+    let on_branch = |r: &Repository| r.head().ok().and_then(|h| h.name().map(|n| n == branch_ref)).unwrap_or(false);
+    if !repo.is_bare() && on_branch(repo) {
+        checkouts.push(Repository::open(repo.workdir().unwrap_or(repo.path()))
+            .map_err(|e| format!("Failed to open main checkout: {}", e))?);
+    }
+    let names = repo.worktrees().map_err(|e| format!("Failed to list worktrees: {}", e))?;
+    for name in names.iter().flatten() {
+        let Ok(worktree) = repo.find_worktree(name) else { continue; };
+        if let Ok(linked) = Repository::open_from_worktree(&worktree) {
+            if on_branch(&linked) {
+                checkouts.push(linked);
+            }
+        }
+    }
+    Ok(checkouts)
+}
 
-    // Perform the merge
-    println!("Merging {} into {}", task_branch, base_branch);
-    let mut merge_options = git2::MergeOptions::new();
-    let annotated_commit = repo.find_annotated_commit(task_commit.id())
-        .map_err(|e| format!("Failed to create annotated commit: {}", e))?;
+/// True if tracked files in a checkout have staged or unstaged changes
+fn is_dirty(checkout: &Repository) -> Result<bool, String> {
+    let mut opts = git2::StatusOptions::new();
+    opts.include_untracked(false).include_ignored(false);
+    let statuses = checkout.statuses(Some(&mut opts))
+        .map_err(|e| format!("Failed to read status: {}", e))?;
+    Ok(statuses.iter().any(|entry| entry.status() != git2::Status::CURRENT))
+}
 
-    let (merge_analysis, _merge_pref) = repo.merge_analysis(&[&annotated_commit])
-        .map_err(|e| format!("Failed to analyze merge: {}", e))?;
+/// Untracked files in a checkout that `tree` has a file or folder at
+fn untracked_collisions(checkout: &Repository, tree: &git2::Tree) -> Result<Vec<String>, String> {
+    let mut opts = git2::StatusOptions::new();
+    opts.include_untracked(true).recurse_untracked_dirs(true).include_ignored(false);
+    let statuses = checkout.statuses(Some(&mut opts))
+        .map_err(|e| format!("Failed to read status: {}", e))?;
+    Ok(statuses.iter()
+        .filter(|entry| entry.status().contains(git2::Status::WT_NEW))
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .filter(|path| tree.get_path(Path::new(path)).is_ok())
+        .collect())
+}
 
-    if merge_analysis.is_up_to_date() {
-        return Ok("Already up to date, no merge needed".to_string());
+/// Updates the files and index of a checkout whose branch moved from `from` to
+/// `to`. A checkout that no longer matches `from` (it changed while the merge
+/// ran), or has untracked files where `to` puts one, is left alone so no local
+/// work is overwritten.
+fn sync_checkout(checkout: &Repository, from: git2::Oid, to: git2::Oid) -> Result<(), String> {
+    let location = checkout.workdir().map(|p| p.display().to_string()).unwrap_or_default();
+    let old_tree = checkout.find_commit(from)
+        .and_then(|commit| commit.tree())
+        .map_err(|e| format!("Failed to read the previous commit at {}: {}", location, e))?;
+    let changes = checkout.diff_tree_to_workdir_with_index(Some(&old_tree), None)
+        .map_err(|e| format!("Failed to read status at {}: {}", location, e))?;
+    if changes.deltas().len() > 0 {
+        return Err(format!("{} changed during the merge; its files were left as they were", location));
     }
+    let commit = checkout.find_commit(to)
+        .map_err(|e| format!("Failed to find merged commit: {}", e))?;
+    let new_tree = commit.tree()
+        .map_err(|e| format!("Failed to read merged tree: {}", e))?;
+    let collisions = untracked_collisions(checkout, &new_tree)?;
+    if !collisions.is_empty() {
+        return Err(format!(
+            "{} has untracked files the merge would overwrite ({}); its files were left as they were",
+            location, collisions.join(", ")
+        ));
+    }
+    let mut builder = git2::build::CheckoutBuilder::new();
+    builder.force();
+    checkout.checkout_tree(commit.as_object(), Some(&mut builder))
+        .map_err(|e| format!("Failed to update checkout at {} after merge: {}", location, e))
+}
 
-    if merge_analysis.is_fast_forward() {
-        println!("Fast-forward merge possible");
-        // Fast-forward merge
-        let target_oid = annotated_commit.id();
-        let mut reference = repo.find_reference(&format!("refs/heads/{}", base_branch))
-            .map_err(|e| format!("Failed to find base branch reference: {}", e))?;
-
-        reference.set_target(target_oid, &format!("Fast-forward merge {} into {}", task_branch, base_branch))
-            .map_err(|e| format!("Failed to fast-forward: {}", e))?;
+/// Conflicted files of a merge index with the contents of each side
+fn conflict_files(repo: &Repository, index: &git2::Index, base_branch: &str, task_branch: &str) -> Result<Vec<ConflictFile>, String> {
+    let mut files = Vec::new();
+    for conflict in index.conflicts()
+        .map_err(|e| format!("Failed to get conflicts: {}", e))?
+        .flatten() {
+        let side = conflict.our.as_ref().or(conflict.their.as_ref()).or(conflict.ancestor.as_ref());
+        let Some(path) = side.and_then(|e| String::from_utf8(e.path.clone()).ok()) else { continue; };
+
+        let mut binary = false;
+        let mut text = |entry: &Option<git2::IndexEntry>| -> Option<String> {
+            let blob = repo.find_blob(entry.as_ref()?.id).ok()?;
+            if blob.is_binary() {
+                binary = true;
+                return None;
+            }
+            Some(String::from_utf8_lossy(blob.content()).into_owned())
+        };
+        let base = text(&conflict.ancestor);
+        let ours = text(&conflict.our);
+        let theirs = text(&conflict.their);
+
+        let merged = match (&conflict.ancestor, &conflict.our, &conflict.their) {
+            _ if binary => None,
+            (Some(ancestor), Some(our), Some(their)) => {
+                let mut opts = git2::MergeFileOptions::new();
+                opts.our_label(base_branch).their_label(task_branch);
+                repo.merge_file_from_index(ancestor, our, their, Some(&mut opts))
+                    .ok()
+                    .map(|result| String::from_utf8_lossy(result.content()).into_owned())
+            }
+            // Added on both sides: the whole file is one conflicting hunk
+            (None, Some(_), Some(_)) => Some(format!(
+                "<<<<<<< {}\n{}=======\n{}>>>>>>> {}\n",
+                base_branch,
+                with_newline(ours.as_deref().unwrap_or_default()),
+                with_newline(theirs.as_deref().unwrap_or_default()),
+                task_branch
+            )),
+            // One side deleted the file: keep or delete it as a whole
+            _ => None,
+        };
+        files.push(ConflictFile { path, base, ours, theirs, merged, binary });
+    }
+    Ok(files)
+}
 
-        repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))
-            .map_err(|e| format!("Failed to checkout after fast-forward: {}", e))?;
+fn with_newline(text: &str) -> String {
+    if text.is_empty() || text.ends_with('\n') {
+        text.to_string()
+    } else {
+        format!("{}\n", text)
+    }
+}
 
-        return Ok(format!("Successfully fast-forward merged {} into {}", task_branch, base_branch));
+/// Replaces the conflict entries of `index` with the resolved contents. Every
+/// conflicted path must have a resolution.
+fn apply_resolutions(repo: &Repository, index: &mut git2::Index, resolutions: &[ConflictResolution]) -> Result<(), String> {
+    let mut conflicts = Vec::new();
+    for conflict in index.conflicts()
+        .map_err(|e| format!("Failed to get conflicts: {}", e))?
+        .flatten() {
+        if let Some(side) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+            conflicts.push(side);
+        }
     }
+    for mut entry in conflicts {
+        let path = String::from_utf8_lossy(&entry.path).into_owned();
+        let resolution = resolutions.iter()
+            .find(|r| r.path == path)
+            .ok_or_else(|| format!("No resolution for '{}'", path))?;
+        index.conflict_remove(Path::new(&path))
+            .map_err(|e| format!("Failed to resolve '{}': {}", path, e))?;
+        let Some(content) = &resolution.content else { continue; };
+        entry.id = repo.blob(content.as_bytes())
+            .map_err(|e| format!("Failed to store '{}': {}", path, e))?;
+        entry.file_size = content.len() as u32;
+        // Clear the stage bits so the entry lands in the merged stage
+        entry.flags &= !0x3000;
+        index.add(&entry)
+            .map_err(|e| format!("Failed to resolve '{}': {}", path, e))?;
+    }
+    Ok(())
+}
 
-    // Normal merge
-    println!("Performing normal merge");
-    repo.merge(&[&annotated_commit], Some(&mut merge_options), None)
-        .map_err(|e| format!("Merge failed: {}", e))?;
+/// Result of replaying a task's commits onto its base branch
+enum Rebased {
+    /// New head and the number of commits written
+    Done { head: git2::Oid, count: usize },
+    /// The first commit that did not apply cleanly and nothing was written
+    Conflicted(Vec<ConflictFile>),
+}
 
-    // Check for conflicts
-    let mut index = repo.index()
-        .map_err(|e| format!("Failed to get repository index: {}", e))?;
+/// Replays the task's commits onto the base branch in memory, stopping on the
+/// first conflict
+fn rebase_commits(repo: &Repository, base: &git2::Commit, task: &git2::Commit, base_branch: &str, task_branch: &str) -> Result<Rebased, String> {
+    let upstream = repo.find_annotated_commit(base.id())
+        .map_err(|e| format!("Failed to find base commit: {}", e))?;
+    let branch = repo.find_annotated_commit(task.id())
+        .map_err(|e| format!("Failed to find task commit: {}", e))?;
+    let mut opts = git2::RebaseOptions::new();
+    opts.inmemory(true);
+    let mut rebase = repo.rebase(Some(&branch), Some(&upstream), None, Some(&mut opts))
+        .map_err(|e| format!("Failed to start rebase: {}", e))?;
+    let signature = repo.signature()
+        .map_err(|e| format!("Failed to get signature: {}", e))?;
 
-    if index.has_conflicts() {
-        println!("Merge has conflicts");
-
-        // Collect conflicted files
-        let mut conflicted_files = Vec::new();
-        for entry in index.conflicts()
-            .map_err(|e| format!("Failed to get conflicts: {}", e))?
-            .flatten() {
-            if let Some(our) = entry.our {
-                if let Ok(path) = std::str::from_utf8(&our.path) {
-                    conflicted_files.push(path.to_string());
-                }
+    let mut head = base.id();
+    let mut count = 0;
+    while let Some(operation) = rebase.next() {
+        if let Err(e) = operation {
+            let _ = rebase.abort();
+            return Err(format!("Rebase failed: {}", e));
+        }
+        let index = rebase.inmemory_index()
+            .map_err(|e| format!("Rebase failed: {}", e))?;
+        if index.has_conflicts() {
+            let files = conflict_files(repo, &index, base_branch, task_branch)?;
+            let _ = rebase.abort();
+            return Ok(Rebased::Conflicted(files));
+        }
+        match rebase.commit(None, &signature, None) {
+            Ok(oid) => {
+                head = oid;
+                count += 1;
+            }
+            // The change is already on the base branch
+            Err(e) if e.code() == git2::ErrorCode::Applied => {}
+            Err(e) => {
+                let _ = rebase.abort();
+                return Err(format!("Failed to write rebased commit: {}", e));
             }
         }
+    }
+    rebase.finish(None)
+        .map_err(|e| format!("Failed to finish rebase: {}", e))?;
+    Ok(Rebased::Done { head, count })
+}
 
-        // Abort the merge
-        repo.cleanup_state()
-            .map_err(|e| format!("Failed to cleanup merge state: {}", e))?;
+/// Squash commit message offered before a squash merge: the task title and
+/// description followed by the subjects of the task branch's own commits
+pub fn default_squash_message(worktree_path: &str, base_branch: &str, title: &str, description: &str) -> Result<String, String> {
+    let repo = Repository::open(worktree_path)
+        .map_err(|e| format!("Failed to open worktree: {}", e))?;
+    let (_, base_commit, task_commit) = merge_heads(&repo, worktree_path, base_branch)?;
+    let mut walk = repo.revwalk()
+        .map_err(|e| format!("Failed to list commits: {}", e))?;
+    walk.push(task_commit.id()).map_err(|e| format!("Failed to list commits: {}", e))?;
+    walk.hide(base_commit.id()).map_err(|e| format!("Failed to list commits: {}", e))?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)
+        .map_err(|e| format!("Failed to list commits: {}", e))?;
+    let subjects: Vec<String> = walk
+        .flatten()
+        .filter_map(|oid| repo.find_commit(oid).ok())
+        .filter(|commit| commit.parent_count() < 2)
+        .filter_map(|commit| commit.summary().map(|s| s.to_string()))
+        .collect();
+    Ok(squash_message(title, description, &subjects))
+}
+
+/// Title, description and a list of commit subjects, separated by blank lines;
+/// empty parts are left out
+fn squash_message(title: &str, description: &str, subjects: &[String]) -> String {
+    let list = subjects.iter().map(|s| format!("- {}", s)).collect::<Vec<_>>().join("\n");
+    [title.trim(), description.trim(), &list]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
 
-        return Err(format!("Merge conflict in files: {}", conflicted_files.join(", ")));
+/// Abandons a `git merge` or `git rebase` left in progress in a worktree, e.g.
+/// by an agent asked to resolve conflicts or a paused branch update. Does
+/// nothing when neither is in progress.
+pub fn abort_worktree_merge(worktree_path: &str) -> Result<(), String> {
+    let repo = Repository::open(worktree_path)
+        .map_err(|e| format!("Failed to open worktree: {}", e))?;
+    let Some(command) = update_in_progress(&repo).map(UpdateStrategy::command) else {
+        return Ok(());
+    };
+    let output = run_git(worktree_path, &[command, "--abort"])?;
+    if !output.status.success() {
+        return Err(format!("Failed to abort {}: {}", command, String::from_utf8_lossy(&output.stderr).trim()));
     }
+    Ok(())
+}
 
-    // Commit the merge
-    println!("Committing merge");
-    let signature = repo.signature()
-        .map_err(|e| format!("Failed to get signature: {}", e))?;
+/// How `update_task_branch` brings a task branch up to date with its base
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateStrategy {
+    /// Replay the task's commits on top of the base branch
+    Rebase,
+    /// Merge the base branch into the task branch
+    Merge,
+}
 
-    let tree_id = index.write_tree()
-        .map_err(|e| format!("Failed to write tree: {}", e))?;
-    let tree = repo.find_tree(tree_id)
-        .map_err(|e| format!("Failed to find tree: {}", e))?;
+impl UpdateStrategy {
+    /// The git command doing the update
+    fn command(self) -> &'static str {
+        match self {
+            UpdateStrategy::Rebase => "rebase",
+            UpdateStrategy::Merge => "merge",
+        }
+    }
+}
 
-    let base_commit_obj = repo.find_commit(base_commit.id())
-        .map_err(|e| format!("Failed to find base commit: {}", e))?;
-    let task_commit_obj = repo.find_commit(task_commit.id())
-        .map_err(|e| format!("Failed to find task commit: {}", e))?;
+/// Commits the task branch has that its base branch does not, and the other way round
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AheadBehind {
+    pub ahead: usize,
+    pub behind: usize,
+}
 
-    let merge_commit_oid = repo.commit(
-        Some(&format!("refs/heads/{}", base_branch)),
-        &signature,
-        &signature,
-        &format!("Merge branch '{}' into {}", task_branch, base_branch),
-        &tree,
-        &[&base_commit_obj, &task_commit_obj],
-    ).map_err(|e| format!("Failed to create merge commit: {}", e))?;
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateState {
+    Updated,
+    UpToDate,
+    /// Stopped on conflicts; resolve them and continue, or abort
+    Paused,
+    /// Hit conflicts and was rolled back
+    Aborted,
+}
+
+/// Result of updating a task branch from its base branch
+#[derive(Debug, Serialize)]
+pub struct BranchUpdate {
+    pub state: UpdateState,
+    pub strategy: UpdateStrategy,
+    pub before: AheadBehind,
+    pub after: AheadBehind,
+    /// Conflicted files when paused or aborted
+    pub conflicts: Vec<String>,
+    /// Base branch commit the task branch now builds on, once it is up to date
+    pub base_commit: Option<String>,
+}
+
+/// Where a task branch stands against its base branch
+#[derive(Debug, Serialize)]
+pub struct BranchStatus {
+    pub counts: AheadBehind,
+    /// Rebase or merge paused in the worktree, if any
+    pub in_progress: Option<UpdateStrategy>,
+    pub conflicts: Vec<String>,
+}
+
+fn run_git(worktree_path: &str, args: &[&str]) -> Result<std::process::Output, String> {
+    Command::new("git")
+        .args(args)
+        .current_dir(worktree_path)
+        // Rebase and merge commits keep their default messages instead of opening an editor
+        .env("GIT_EDITOR", "true")
+        .output()
+        .map_err(|e| format!("Failed to run git {}: {}", args.join(" "), e))
+}
+
+fn update_in_progress(repo: &Repository) -> Option<UpdateStrategy> {
+    match repo.state() {
+        git2::RepositoryState::Merge => Some(UpdateStrategy::Merge),
+        git2::RepositoryState::Rebase
+        | git2::RepositoryState::RebaseInteractive
+        | git2::RepositoryState::RebaseMerge => Some(UpdateStrategy::Rebase),
+        _ => None,
+    }
+}
+
+fn ahead_behind(repo: &Repository, worktree_path: &str, base_branch: &str) -> Result<(AheadBehind, git2::Oid), String> {
+    let (_, base_commit, task_commit) = merge_heads(repo, worktree_path, base_branch)?;
+    let (ahead, behind) = repo.graph_ahead_behind(task_commit.id(), base_commit.id())
+        .map_err(|e| format!("Failed to compare with '{}': {}", base_branch, e))?;
+    Ok((AheadBehind { ahead, behind }, base_commit.id()))
+}
+
+/// Paths git reports as unmerged in a worktree
+fn unmerged_paths(worktree_path: &str) -> Result<Vec<String>, String> {
+    let output = run_git(worktree_path, &["diff", "--name-only", "--diff-filter=U"])?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.to_string())
+        .collect())
+}
+
+/// Ahead/behind counts of a task branch and any update paused in its worktree
+pub fn get_branch_status(worktree_path: &str, base_branch: &str) -> Result<BranchStatus, String> {
+    let repo = Repository::open(worktree_path)
+        .map_err(|e| format!("Failed to open worktree: {}", e))?;
+    let (counts, _) = ahead_behind(&repo, worktree_path, base_branch)?;
+    let in_progress = update_in_progress(&repo);
+    let conflicts = if in_progress.is_some() { unmerged_paths(worktree_path)? } else { Vec::new() };
+    Ok(BranchStatus { counts, in_progress, conflicts })
+}
+
+/// Brings a task branch up to date with its base branch inside the task's
+/// worktree, by rebasing onto the base branch or merging it in
+///
+/// On conflicts the rebase or merge is left paused with the conflicted files
+/// reported, or rolled back when `abort_on_conflict` is set.
+pub fn update_task_branch(worktree_path: &str, base_branch: &str, strategy: UpdateStrategy, abort_on_conflict: bool) -> Result<BranchUpdate, String> {
+    println!("Updating task branch at {} from {} ({:?})", worktree_path, base_branch, strategy);
+    let repo = Repository::open(worktree_path)
+        .map_err(|e| format!("Failed to open worktree: {}", e))?;
+    if let Some(current) = update_in_progress(&repo) {
+        return Err(format!("A {} is already in progress in the worktree; continue or abort it first", current.command()));
+    }
+    if is_dirty(&repo)? {
+        return Err("The task has uncommitted changes; commit or stash them before updating the branch".to_string());
+    }
+
+    let (before, base_oid) = ahead_behind(&repo, worktree_path, base_branch)?;
+    if before.behind == 0 {
+        return Ok(BranchUpdate {
+            state: UpdateState::UpToDate,
+            strategy,
+            before,
+            after: before,
+            conflicts: Vec::new(),
+            base_commit: Some(base_oid.to_string()),
+        });
+    }
+
+    let output = match strategy {
+        UpdateStrategy::Rebase => run_git(worktree_path, &["rebase", base_branch])?,
+        UpdateStrategy::Merge => run_git(worktree_path, &["merge", "--no-edit", base_branch])?,
+    };
+    finish_update(&repo, worktree_path, base_branch, strategy, before, output, abort_on_conflict)
+}
 
-    // Cleanup merge state
-    repo.cleanup_state()
-        .map_err(|e| format!("Failed to cleanup merge state: {}", e))?;
+/// Continues a paused rebase or merge once its conflicts are resolved in the
+/// worktree. The resolved files are staged; files still holding conflict
+/// markers are refused.
+pub fn continue_branch_update(worktree_path: &str, base_branch: &str) -> Result<BranchUpdate, String> {
+    let repo = Repository::open(worktree_path)
+        .map_err(|e| format!("Failed to open worktree: {}", e))?;
+    let strategy = update_in_progress(&repo)
+        .ok_or_else(|| "No rebase or merge is in progress in the worktree".to_string())?;
+
+    let conflicts = unmerged_paths(worktree_path)?;
+    let marked: Vec<&String> = conflicts.iter()
+        .filter(|path| {
+            fs::read_to_string(Path::new(worktree_path).join(path))
+                .map(|text| text.lines().any(|l| l.starts_with("<<<<<<<") || l.starts_with(">>>>>>>")))
+                .unwrap_or(false)
+        })
+        .collect();
+    if !marked.is_empty() {
+        return Err(format!("Conflict markers are still in: {}", marked.iter().map(|p| p.as_str()).collect::<Vec<_>>().join(", ")));
+    }
+    if !conflicts.is_empty() {
+        let mut args = vec!["add", "-A", "--"];
+        args.extend(conflicts.iter().map(|p| p.as_str()));
+        let output = run_git(worktree_path, &args)?;
+        if !output.status.success() {
+            return Err(format!("Failed to stage resolved files: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+    }
 
-    println!("Merge successful, commit: {}", merge_commit_oid);
-    Ok(format!("Successfully merged {} into {} (commit: {})", task_branch, base_branch, merge_commit_oid))
+    let (before, _) = ahead_behind(&repo, worktree_path, base_branch)?;
+    let output = match strategy {
+        UpdateStrategy::Rebase => run_git(worktree_path, &["rebase", "--continue"])?,
+        UpdateStrategy::Merge => run_git(worktree_path, &["commit", "--no-edit"])?,
+    };
+    finish_update(&repo, worktree_path, base_branch, strategy, before, output, false)
+}
+
+/// Reads the outcome of a rebase or merge step: done, stopped on conflicts, or failed
+fn finish_update(
+    repo: &Repository,
+    worktree_path: &str,
+    base_branch: &str,
+    strategy: UpdateStrategy,
+    before: AheadBehind,
+    output: std::process::Output,
+    abort_on_conflict: bool,
+) -> Result<BranchUpdate, String> {
+    // A rebase can stop again on a later commit even after a step succeeded
+    let paused = update_in_progress(repo).is_some();
+    if output.status.success() && !paused {
+        let (after, base_oid) = ahead_behind(repo, worktree_path, base_branch)?;
+        println!("Task branch updated: {:?} -> {:?}", before, after);
+        return Ok(BranchUpdate { state: UpdateState::Updated, strategy, before, after, conflicts: Vec::new(), base_commit: Some(base_oid.to_string()) });
+    }
+
+    let conflicts = unmerged_paths(worktree_path)?;
+    if conflicts.is_empty() {
+        // Failed for another reason; do not leave the worktree half updated
+        let error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        abort_worktree_merge(worktree_path)?;
+        return Err(format!("Failed to update the task branch: {}", error));
+    }
+    let state = if abort_on_conflict {
+        abort_worktree_merge(worktree_path)?;
+        UpdateState::Aborted
+    } else {
+        UpdateState::Paused
+    };
+    println!("Task branch update {:?} on conflicts in: {}", state, conflicts.join(", "));
+    let (after, _) = ahead_behind(repo, worktree_path, base_branch)?;
+    Ok(BranchUpdate { state, strategy, before, after, conflicts, base_commit: None })
 }
 
 /// File status information
@@ -1050,3 +1670,472 @@ pub fn open_worktree_in_ide(worktree_path: &str) -> Result<(), String> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+    /// Fresh empty directory under the system temp dir
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "agent-board-git-{}-{}-{}",
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::SeqCst),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Repository with `main` checked out and one commit holding `file.txt`
+    fn init_repo() -> (PathBuf, Repository) {
+        let dir = temp_dir("repo");
+        let mut opts = git2::RepositoryInitOptions::new();
+        opts.initial_head("main");
+        let repo = Repository::init_opts(&dir, &opts).unwrap();
+        {
+            let mut config = repo.config().unwrap();
+            config.set_str("user.name", "Test").unwrap();
+            config.set_str("user.email", "test@example.com").unwrap();
+        }
+        commit(&repo, "refs/heads/main", &[("file.txt", Some("one\ntwo\nthree\n"))], "Initial commit");
+        checkout_head(&repo);
+        (dir, repo)
+    }
+
+    /// Commits file changes (`None` deletes) on top of `refname` without
+    /// touching any checkout
+    fn commit(repo: &Repository, refname: &str, files: &[(&str, Option<&str>)], message: &str) -> git2::Oid {
+        let parent = repo.find_reference(refname).ok().and_then(|r| r.peel_to_commit().ok());
+        let base_tree = match &parent {
+            Some(parent) => parent.tree().unwrap(),
+            None => repo.find_tree(repo.treebuilder(None).unwrap().write().unwrap()).unwrap(),
+        };
+        let mut update = git2::build::TreeUpdateBuilder::new();
+        for (path, content) in files {
+            match content {
+                Some(content) => {
+                    let blob = repo.blob(content.as_bytes()).unwrap();
+                    update.upsert(*path, blob, git2::FileMode::Blob);
+                }
+                None => {
+                    update.remove(*path);
+                }
+            }
+        }
+        let tree = repo.find_tree(update.create_updated(repo, &base_tree).unwrap()).unwrap();
+        let signature = repo.signature().unwrap();
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some(refname), &signature, &signature, message, &tree, &parents).unwrap()
+    }
+
+    fn checkout_head(repo: &Repository) {
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+    }
+
+    fn branch_tip(repo: &Repository, branch: &str) -> git2::Oid {
+        repo.find_reference(&format!("refs/heads/{}", branch)).unwrap().peel_to_commit().unwrap().id()
+    }
+
+    fn read(dir: &Path, path: &str) -> String {
+        fs::read_to_string(dir.join(path)).unwrap()
+    }
+
+    /// Worktree of task `id` branched from `main`; returns its path
+    fn task_worktree(dir: &Path, id: &str) -> String {
+        let worktree = add_worktree(dir.to_str().unwrap(), &temp_dir("worktrees"), id, Some("main")).unwrap();
+        worktree.path.to_str().unwrap().to_string()
+    }
+
+    /// Commits on the task branch and brings its worktree along
+    fn commit_task(repo: &Repository, worktree_path: &str, files: &[(&str, Option<&str>)], message: &str) -> git2::Oid {
+        let id = Path::new(worktree_path).file_name().unwrap().to_str().unwrap();
+        let oid = commit(repo, &format!("refs/heads/task/{}", id), files, message);
+        checkout_head(&Repository::open(worktree_path).unwrap());
+        oid
+    }
+
+    /// Commits on `main` and brings the main checkout along
+    fn commit_main(repo: &Repository, files: &[(&str, Option<&str>)], message: &str) -> git2::Oid {
+        let oid = commit(repo, "refs/heads/main", files, message);
+        checkout_head(repo);
+        oid
+    }
+
+    fn merge(dir: &Path, worktree_path: &str, strategy: MergeStrategy, message: Option<&str>, resolutions: &[ConflictResolution]) -> Result<MergeOutcome, String> {
+        merge_to_base_branch(worktree_path, "main", dir.to_str().unwrap(), strategy, message, resolutions)
+    }
+
+    fn tip_commit<'r>(repo: &'r Repository, branch: &str) -> git2::Commit<'r> {
+        repo.find_commit(branch_tip(repo, branch)).unwrap()
+    }
+
+    #[test]
+    fn squash_message_joins_non_empty_parts() {
+        let subjects = vec!["Add parser".to_string(), "Fix typo".to_string()];
+        assert_eq!(
+            squash_message("feat: parser", "Parses input.", &subjects),
+            "feat: parser\n\nParses input.\n\n- Add parser\n- Fix typo"
+        );
+        assert_eq!(squash_message("", "Parses input.", &subjects), "Parses input.\n\n- Add parser\n- Fix typo");
+        assert_eq!(squash_message("  ", "", &subjects), "- Add parser\n- Fix typo");
+        assert_eq!(squash_message("feat: parser", "  ", &[]), "feat: parser");
+        assert_eq!(squash_message("", "", &[]), "");
+    }
+
+    #[test]
+    fn unknown_base_ref_keeps_existing_worktree() {
+        let (dir, _repo) = init_repo();
+        let worktrees = temp_dir("worktrees");
+        let existing = worktrees.join("t1");
+        fs::create_dir_all(&existing).unwrap();
+        fs::write(existing.join("work.txt"), "unsaved work").unwrap();
+
+        let result = add_worktree(dir.to_str().unwrap(), &worktrees, "t1", Some("master"));
+        assert!(result.unwrap_err().contains("'master' does not exist"));
+        assert_eq!(read(&existing, "work.txt"), "unsaved work");
+    }
+
+    #[test]
+    fn worktree_branches_from_base_ref() {
+        let (dir, repo) = init_repo();
+        let start = branch_tip(&repo, "main");
+        commit(&repo, "refs/heads/main", &[("later.txt", Some("later\n"))], "Later change");
+        let worktrees = temp_dir("worktrees");
+
+        let worktree = add_worktree(dir.to_str().unwrap(), &worktrees, "t1", Some(&start.to_string())).unwrap();
+        assert_eq!(worktree.base_commit, start.to_string());
+        assert_eq!(branch_tip(&repo, "task/t1"), start);
+        assert!(worktree.path.join("file.txt").exists());
+        assert!(!worktree.path.join("later.txt").exists());
+    }
+
+    #[test]
+    fn merge_fast_forwards_when_base_has_not_moved() {
+        let (dir, repo) = init_repo();
+        let worktree = task_worktree(&dir, "t1");
+        let task_tip = commit_task(&repo, &worktree, &[("file.txt", Some("changed\n"))], "Task change");
+
+        let outcome = merge(&dir, &worktree, MergeStrategy::FastForwardOrMerge, None, &[]).unwrap();
+        assert!(matches!(outcome, MergeOutcome::Merged { .. }));
+        assert_eq!(branch_tip(&repo, "main"), task_tip);
+        assert_eq!(read(&dir, "file.txt"), "changed\n");
+        assert_eq!(repo.head().unwrap().name(), Some("refs/heads/main"));
+        assert!(!is_dirty(&repo).unwrap());
+    }
+
+    #[test]
+    fn merge_commit_joins_both_branches() {
+        let (dir, repo) = init_repo();
+        let worktree = task_worktree(&dir, "t1");
+        let task_tip = commit_task(&repo, &worktree, &[("file.txt", Some("changed\n"))], "Task change");
+        let base_tip = commit_main(&repo, &[("other.txt", Some("other\n"))], "Base change");
+
+        let outcome = merge(&dir, &worktree, MergeStrategy::FastForwardOrMerge, None, &[]).unwrap();
+        assert!(matches!(outcome, MergeOutcome::Merged { .. }));
+        let merged = tip_commit(&repo, "main");
+        assert_eq!(merged.parent_ids().collect::<Vec<_>>(), vec![base_tip, task_tip]);
+        assert_eq!(read(&dir, "file.txt"), "changed\n");
+        assert_eq!(read(&dir, "other.txt"), "other\n");
+        assert!(!is_dirty(&repo).unwrap());
+    }
+
+    #[test]
+    fn merge_commit_strategy_never_fast_forwards() {
+        let (dir, repo) = init_repo();
+        let start = branch_tip(&repo, "main");
+        let worktree = task_worktree(&dir, "t1");
+        let task_tip = commit_task(&repo, &worktree, &[("file.txt", Some("changed\n"))], "Task change");
+
+        merge(&dir, &worktree, MergeStrategy::MergeCommit, None, &[]).unwrap();
+        let merged = tip_commit(&repo, "main");
+        assert_eq!(merged.parent_ids().collect::<Vec<_>>(), vec![start, task_tip]);
+    }
+
+    #[test]
+    fn merge_refuses_dirty_base_checkout() {
+        let (dir, repo) = init_repo();
+        let start = branch_tip(&repo, "main");
+        let worktree = task_worktree(&dir, "t1");
+        commit_task(&repo, &worktree, &[("file.txt", Some("changed\n"))], "Task change");
+        fs::write(dir.join("file.txt"), "local edit\n").unwrap();
+
+        let error = merge(&dir, &worktree, MergeStrategy::FastForwardOrMerge, None, &[]).unwrap_err();
+        assert!(error.contains("uncommitted changes"));
+        assert_eq!(branch_tip(&repo, "main"), start);
+        assert_eq!(read(&dir, "file.txt"), "local edit\n");
+    }
+
+    #[test]
+    fn merge_refuses_colliding_untracked_file() {
+        let (dir, repo) = init_repo();
+        let start = branch_tip(&repo, "main");
+        let worktree = task_worktree(&dir, "t1");
+        commit_task(&repo, &worktree, &[("new.txt", Some("task\n"))], "Add new");
+        fs::write(dir.join("new.txt"), "USER WORK\n").unwrap();
+        fs::write(dir.join("scratch.txt"), "notes\n").unwrap();
+
+        let error = merge(&dir, &worktree, MergeStrategy::FastForwardOrMerge, None, &[]).unwrap_err();
+        assert!(error.contains("untracked files"));
+        assert!(error.contains("new.txt"));
+        assert!(!error.contains("scratch.txt"));
+        assert_eq!(branch_tip(&repo, "main"), start);
+        assert_eq!(read(&dir, "new.txt"), "USER WORK\n");
+
+        // Untracked files the merge does not touch do not block it
+        fs::remove_file(dir.join("new.txt")).unwrap();
+        let outcome = merge(&dir, &worktree, MergeStrategy::FastForwardOrMerge, None, &[]).unwrap();
+        assert!(matches!(outcome, MergeOutcome::Merged { .. }));
+        assert_eq!(read(&dir, "new.txt"), "task\n");
+        assert_eq!(read(&dir, "scratch.txt"), "notes\n");
+    }
+
+    /// Task and base branch both change the second line of `file.txt`
+    fn conflicting_task(dir: &Path, repo: &Repository) -> String {
+        let worktree = task_worktree(dir, "t1");
+        commit_task(repo, &worktree, &[("file.txt", Some("one\ntask\nthree\n"))], "Task change");
+        commit_main(repo, &[("file.txt", Some("one\nbase\nthree\n"))], "Base change");
+        worktree
+    }
+
+    #[test]
+    fn conflicted_merge_changes_nothing_until_resolved() {
+        let (dir, repo) = init_repo();
+        let worktree = conflicting_task(&dir, &repo);
+        let base_tip = branch_tip(&repo, "main");
+
+        let MergeOutcome::Conflicted { files, .. } = merge(&dir, &worktree, MergeStrategy::FastForwardOrMerge, None, &[]).unwrap() else {
+            panic!("expected conflicts");
+        };
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "file.txt");
+        assert_eq!(files[0].ours.as_deref(), Some("one\nbase\nthree\n"));
+        assert_eq!(files[0].theirs.as_deref(), Some("one\ntask\nthree\n"));
+        assert!(files[0].merged.as_deref().unwrap().contains("<<<<<<< main"));
+        assert_eq!(branch_tip(&repo, "main"), base_tip);
+        assert_eq!(get_merge_conflicts(&worktree, "main", dir.to_str().unwrap()).unwrap().len(), 1);
+
+        // A resolution is needed for every conflicted path
+        let resolutions = vec![ConflictResolution { path: "file.txt".to_string(), content: Some("one\nboth\nthree\n".to_string()) }];
+        let outcome = merge(&dir, &worktree, MergeStrategy::MergeCommit, None, &resolutions).unwrap();
+        assert!(matches!(outcome, MergeOutcome::Merged { .. }));
+        let merged = tip_commit(&repo, "main");
+        assert_eq!(merged.parent_count(), 2);
+        let blob = merged.tree().unwrap().get_path(Path::new("file.txt")).unwrap().to_object(&repo).unwrap().peel_to_blob().unwrap();
+        assert_eq!(blob.content(), b"one\nboth\nthree\n");
+        assert_eq!(read(&dir, "file.txt"), "one\nboth\nthree\n");
+        assert!(!is_dirty(&repo).unwrap());
+    }
+
+    #[test]
+    fn resolution_can_delete_a_file() {
+        let (dir, repo) = init_repo();
+        let worktree = conflicting_task(&dir, &repo);
+        let resolutions = vec![ConflictResolution { path: "file.txt".to_string(), content: None }];
+
+        merge(&dir, &worktree, MergeStrategy::Squash, Some("Drop file"), &resolutions).unwrap();
+        assert!(tip_commit(&repo, "main").tree().unwrap().get_path(Path::new("file.txt")).is_err());
+        assert!(!dir.join("file.txt").exists());
+    }
+
+    #[test]
+    fn rebase_merge_conflict_is_reported_as_conflicted() {
+        let (dir, repo) = init_repo();
+        let worktree = conflicting_task(&dir, &repo);
+        let base_tip = branch_tip(&repo, "main");
+
+        let MergeOutcome::Conflicted { files, .. } = merge(&dir, &worktree, MergeStrategy::Rebase, None, &[]).unwrap() else {
+            panic!("expected conflicts");
+        };
+        assert_eq!(files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(), vec!["file.txt"]);
+        assert_eq!(branch_tip(&repo, "main"), base_tip);
+
+        // Resolutions only fit strategies that write a single commit
+        let resolutions = vec![ConflictResolution { path: "file.txt".to_string(), content: Some("x\n".to_string()) }];
+        assert!(merge(&dir, &worktree, MergeStrategy::Rebase, None, &resolutions).is_err());
+        assert_eq!(branch_tip(&repo, "main"), base_tip);
+    }
+
+    #[test]
+    fn update_rebases_task_branch_onto_base() {
+        let (dir, repo) = init_repo();
+        let worktree = task_worktree(&dir, "t1");
+        commit_task(&repo, &worktree, &[("task.txt", Some("task\n"))], "Task change");
+        let base_tip = commit_main(&repo, &[("other.txt", Some("other\n"))], "Base change");
+
+        let update = update_task_branch(&worktree, "main", UpdateStrategy::Rebase, false).unwrap();
+        assert!(matches!(update.state, UpdateState::Updated));
+        assert_eq!((update.before.ahead, update.before.behind), (1, 1));
+        assert_eq!((update.after.ahead, update.after.behind), (1, 0));
+        assert_eq!(update.base_commit, Some(base_tip.to_string()));
+        assert_eq!(tip_commit(&repo, "task/t1").parent_id(0).unwrap(), base_tip);
+        assert_eq!(read(Path::new(&worktree), "other.txt"), "other\n");
+    }
+
+    #[test]
+    fn paused_update_continues_once_resolved() {
+        let (dir, repo) = init_repo();
+        let worktree = conflicting_task(&dir, &repo);
+
+        let update = update_task_branch(&worktree, "main", UpdateStrategy::Rebase, false).unwrap();
+        assert!(matches!(update.state, UpdateState::Paused));
+        assert_eq!(update.conflicts, vec!["file.txt".to_string()]);
+        let status = get_branch_status(&worktree, "main").unwrap();
+        assert_eq!(status.in_progress, Some(UpdateStrategy::Rebase));
+        assert_eq!(status.conflicts, vec!["file.txt".to_string()]);
+
+        // Conflict markers still in the file are refused
+        assert!(continue_branch_update(&worktree, "main").unwrap_err().contains("file.txt"));
+
+        fs::write(Path::new(&worktree).join("file.txt"), "one\nboth\nthree\n").unwrap();
+        let update = continue_branch_update(&worktree, "main").unwrap();
+        assert!(matches!(update.state, UpdateState::Updated));
+        assert_eq!(update.after.behind, 0);
+        assert!(get_branch_status(&worktree, "main").unwrap().in_progress.is_none());
+        assert_eq!(read(Path::new(&worktree), "file.txt"), "one\nboth\nthree\n");
+    }
+
+    #[test]
+    fn paused_update_can_be_aborted() {
+        let (dir, repo) = init_repo();
+        let worktree = conflicting_task(&dir, &repo);
+        let task_tip = branch_tip(&repo, "task/t1");
+
+        let update = update_task_branch(&worktree, "main", UpdateStrategy::Merge, false).unwrap();
+        assert!(matches!(update.state, UpdateState::Paused));
+        assert_eq!(get_branch_status(&worktree, "main").unwrap().in_progress, Some(UpdateStrategy::Merge));
+
+        abort_worktree_merge(&worktree).unwrap();
+        assert!(get_branch_status(&worktree, "main").unwrap().in_progress.is_none());
+        assert_eq!(branch_tip(&repo, "task/t1"), task_tip);
+        assert_eq!(read(Path::new(&worktree), "file.txt"), "one\ntask\nthree\n");
+    }
+
+    #[test]
+    fn update_rolls_back_when_asked_to_abort_on_conflict() {
+        let (dir, repo) = init_repo();
+        let worktree = conflicting_task(&dir, &repo);
+        let task_tip = branch_tip(&repo, "task/t1");
+
+        let update = update_task_branch(&worktree, "main", UpdateStrategy::Rebase, true).unwrap();
+        assert!(matches!(update.state, UpdateState::Aborted));
+        assert_eq!(update.conflicts, vec!["file.txt".to_string()]);
+        assert!(get_branch_status(&worktree, "main").unwrap().in_progress.is_none());
+        assert_eq!(branch_tip(&repo, "task/t1"), task_tip);
+    }
+
+    #[test]
+    fn squash_merge_writes_one_commit_with_the_message() {
+        let (dir, repo) = init_repo();
+        let worktree = task_worktree(&dir, "t1");
+        commit_task(&repo, &worktree, &[("a.txt", Some("a\n"))], "Add a");
+        commit_task(&repo, &worktree, &[("b.txt", Some("b\n"))], "Add b");
+        let base_tip = commit_main(&repo, &[("other.txt", Some("other\n"))], "Base change");
+
+        merge(&dir, &worktree, MergeStrategy::Squash, Some("feat: add a and b"), &[]).unwrap();
+        let squashed = tip_commit(&repo, "main");
+        assert_eq!(squashed.parent_ids().collect::<Vec<_>>(), vec![base_tip]);
+        assert_eq!(squashed.message(), Some("feat: add a and b"));
+        for path in ["a.txt", "b.txt", "other.txt"] {
+            assert!(dir.join(path).exists(), "{} missing", path);
+        }
+    }
+
+    #[test]
+    fn rebase_merge_replays_task_commits() {
+        let (dir, repo) = init_repo();
+        let worktree = task_worktree(&dir, "t1");
+        commit_task(&repo, &worktree, &[("a.txt", Some("a\n"))], "Add a");
+        commit_task(&repo, &worktree, &[("b.txt", Some("b\n"))], "Add b");
+        let base_tip = commit_main(&repo, &[("other.txt", Some("other\n"))], "Base change");
+
+        merge(&dir, &worktree, MergeStrategy::Rebase, None, &[]).unwrap();
+        let head = tip_commit(&repo, "main");
+        assert_eq!(head.summary(), Some("Add b"));
+        assert_eq!(head.parent_count(), 1);
+        let first = head.parent(0).unwrap();
+        assert_eq!(first.summary(), Some("Add a"));
+        assert_eq!(first.parent_ids().collect::<Vec<_>>(), vec![base_tip]);
+        assert!(dir.join("b.txt").exists());
+    }
+
+    #[test]
+    fn fast_forward_only_refuses_once_base_moved() {
+        let (dir, repo) = init_repo();
+        let worktree = task_worktree(&dir, "t1");
+        let task_tip = commit_task(&repo, &worktree, &[("a.txt", Some("a\n"))], "Add a");
+        let base_tip = commit_main(&repo, &[("other.txt", Some("other\n"))], "Base change");
+
+        assert!(merge(&dir, &worktree, MergeStrategy::FastForwardOnly, None, &[]).is_err());
+        assert_eq!(branch_tip(&repo, "main"), base_tip);
+
+        // Once the task branch is updated the fast-forward goes through
+        update_task_branch(&worktree, "main", UpdateStrategy::Rebase, false).unwrap();
+        let task_tip_after = branch_tip(&repo, "task/t1");
+        assert_ne!(task_tip_after, task_tip);
+        merge(&dir, &worktree, MergeStrategy::FastForwardOnly, None, &[]).unwrap();
+        assert_eq!(branch_tip(&repo, "main"), task_tip_after);
+    }
+
+    #[test]
+    fn default_squash_message_lists_task_commits() {
+        let (dir, repo) = init_repo();
+        let worktree = task_worktree(&dir, "t1");
+        commit_task(&repo, &worktree, &[("a.txt", Some("a\n"))], "Add a");
+        commit_task(&repo, &worktree, &[("b.txt", Some("b\n"))], "Add b");
+        commit_main(&repo, &[("other.txt", Some("other\n"))], "Base change");
+
+        let message = default_squash_message(&worktree, "main", "feat: a and b", "").unwrap();
+        assert_eq!(message, "feat: a and b\n\n- Add a\n- Add b");
+    }
+
+    #[test]
+    fn moved_base_branch_leaves_checkout_untouched() {
+        let (dir, repo) = init_repo();
+        let start = branch_tip(&repo, "main");
+        let merged = commit(&repo, "refs/heads/task/t1", &[("file.txt", Some("merged\n"))], "Task change");
+        repo.reference("refs/heads/task/t1", start, true, "reset").unwrap();
+        let checkouts = checkouts_of_branch(&repo, "refs/heads/main").unwrap();
+        assert_eq!(checkouts.len(), 1);
+
+        // Someone else moves main while the merge is running
+        let other = commit(&repo, "refs/heads/main", &[("other.txt", Some("other\n"))], "Other change");
+
+        let result = move_base_branch(&repo, &checkouts, "refs/heads/main", start, merged, "Merge");
+        assert!(result.is_err());
+        assert_eq!(branch_tip(&repo, "main"), other);
+        assert_eq!(read(&dir, "file.txt"), "one\ntwo\nthree\n");
+    }
+
+    #[test]
+    fn moved_base_branch_syncs_clean_checkout() {
+        let (dir, repo) = init_repo();
+        let start = branch_tip(&repo, "main");
+        let merged = commit(&repo, "refs/heads/task/t1", &[("file.txt", Some("merged\n"))], "Task change");
+        let checkouts = checkouts_of_branch(&repo, "refs/heads/main").unwrap();
+
+        let notes = move_base_branch(&repo, &checkouts, "refs/heads/main", start, merged, "Merge").unwrap();
+        assert!(notes.is_empty());
+        assert_eq!(branch_tip(&repo, "main"), merged);
+        assert_eq!(read(&dir, "file.txt"), "merged\n");
+        assert!(!is_dirty(&repo).unwrap());
+    }
+    #[test]
+    fn moved_base_branch_keeps_colliding_untracked_file() {
+        let (dir, repo) = init_repo();
+        let start = branch_tip(&repo, "main");
+        let merged = commit(&repo, "refs/heads/task/t1", &[("new.txt", Some("task\n"))], "Task change");
+        let checkouts = checkouts_of_branch(&repo, "refs/heads/main").unwrap();
+        fs::write(dir.join("new.txt"), "USER WORK\n").unwrap();
+
+        let notes = move_base_branch(&repo, &checkouts, "refs/heads/main", start, merged, "Merge").unwrap();
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].contains("new.txt"));
+        assert_eq!(branch_tip(&repo, "main"), merged);
+        assert_eq!(read(&dir, "new.txt"), "USER WORK\n");
+    }
+}
@@ -7,16 +7,7 @@ mod git;
 mod agent;
 mod web;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
-struct CodexSettings {
-    command: Option<String>,
-    args: Option<Vec<String>>, // extra args before prompt
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
-struct AgentSettings {
-    codex: Option<CodexSettings>,
-}
+use agent::settings::AgentSettings;
 
 use tauri_plugin_store::StoreExt;
 use tauri::Manager;
@@ -254,13 +245,12 @@ async fn save_tasks_data(app: tauri::AppHandle, project_id: String, tasks: Vec<s
 }
 
 #[tauri::command]
-async fn create_task_worktree(app: tauri::AppHandle, project_path: String, task_id: String) -> Result<String, String> {
-    println!("Tauri command: create_task_worktree called with project_path='{}', task_id='{}'", project_path, task_id);
-    match git::create_worktree(&app, &project_path, &task_id) {
+async fn create_task_worktree(app: tauri::AppHandle, project_path: String, task_id: String, base_ref: Option<String>) -> Result<git::GitWorktree, String> {
+    println!("Tauri command: create_task_worktree called with project_path='{}', task_id='{}', base_ref={:?}", project_path, task_id, base_ref);
+    match git::create_worktree(&app, &project_path, &task_id, base_ref.as_deref()) {
         Ok(worktree) => {
-            let path_str = worktree.path.to_string_lossy().to_string();
-            println!("Tauri command: create_task_worktree succeeded, returning path: {}", path_str);
-            Ok(path_str)
+            println!("Tauri command: create_task_worktree succeeded: {:?} from {}", worktree.path, worktree.base_commit);
+            Ok(worktree)
         },
         Err(e) => {
             println!("Tauri command: create_task_worktree failed with error: {}", e);
@@ -344,17 +334,44 @@ async fn list_git_branches(repo_path: String) -> Result<Vec<String>, String> {
     }
 }
 
+/// Defaults the merge dialog opens with
+#[derive(Debug, Serialize)]
+pub struct MergeDefaults {
+    pub strategy: git::MergeStrategy,
+    pub squash_message: String,
+}
+
+/// Merge strategy set on the project at `project_path`, or the default one
+fn project_merge_strategy(app: &tauri::AppHandle, project_path: &str) -> git::MergeStrategy {
+    agent::records::load_projects(app)
+        .into_iter()
+        .find(|p| p.get("project_path").and_then(|v| v.as_str()) == Some(project_path))
+        .and_then(|p| p.get("merge_strategy").cloned())
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
 #[tauri::command]
-async fn merge_worktree_to_base(worktree_path: String, base_branch: String, project_path: String) -> Result<String, String> {
+async fn merge_worktree_to_base(
+    app: tauri::AppHandle,
+    worktree_path: String,
+    base_branch: String,
+    project_path: String,
+    strategy: Option<git::MergeStrategy>,
+    message: Option<String>,
+    resolutions: Option<Vec<git::ConflictResolution>>,
+) -> Result<git::MergeOutcome, String> {
     println!("Tauri command: merge_worktree_to_base called");
     println!("  worktree_path: {}", worktree_path);
     println!("  base_branch: {}", base_branch);
     println!("  project_path: {}", project_path);
 
-    match git::merge_to_base_branch(&worktree_path, &base_branch, &project_path) {
-        Ok(message) => {
-            println!("Tauri command: merge succeeded: {}", message);
-            Ok(message)
+    // Without an override the project's strategy applies
+    let strategy = strategy.unwrap_or_else(|| project_merge_strategy(&app, &project_path));
+    match git::merge_to_base_branch(&worktree_path, &base_branch, &project_path, strategy, message.as_deref(), &resolutions.unwrap_or_default()) {
+        Ok(outcome) => {
+            println!("Tauri command: merge_worktree_to_base finished");
+            Ok(outcome)
         },
         Err(e) => {
             println!("Tauri command: merge failed: {}", e);
@@ -363,6 +380,80 @@ async fn merge_worktree_to_base(worktree_path: String, base_branch: String, proj
     }
 }
 
+#[tauri::command]
+async fn get_merge_defaults(app: tauri::AppHandle, task_id: String) -> Result<MergeDefaults, String> {
+    let record = agent::records::find_task(&app, &task_id).ok_or_else(|| format!("Task {} not found", task_id))?;
+    let project_path = record.project_field::<String>("project_path").unwrap_or_default();
+    let (worktree_path, base_branch) = task_branch_location(&app, &task_id)?;
+    let squash_message = git::default_squash_message(
+        &worktree_path,
+        &base_branch,
+        &record.task_field::<String>("title").unwrap_or_default(),
+        &record.task_field::<String>("description").unwrap_or_default(),
+    )?;
+    Ok(MergeDefaults { strategy: project_merge_strategy(&app, &project_path), squash_message })
+}
+
+// Conflict resolution view of a task
+#[tauri::command]
+async fn get_merge_conflicts(worktree_path: String, base_branch: String, project_path: String) -> Result<Vec<git::ConflictFile>, String> {
+    git::get_merge_conflicts(&worktree_path, &base_branch, &project_path)
+}
+
+#[tauri::command]
+async fn abort_worktree_merge(worktree_path: String) -> Result<(), String> {
+    println!("Tauri command: abort_worktree_merge called for: {}", worktree_path);
+    git::abort_worktree_merge(&worktree_path)
+}
+
+#[tauri::command]
+async fn resolve_conflicts_with_agent(
+    app: tauri::AppHandle,
+    process_id: String,
+    worktree_path: String,
+    base_branch: String,
+    paths: Vec<String>,
+) -> Result<String, String> {
+    println!("Tauri command: resolve_conflicts_with_agent called for process '{}' ({} files)", process_id, paths.len());
+    let message = agent::prompt::conflict_prompt(&base_branch, &paths);
+    agent::send_message_to_process(app, &process_id, message, worktree_path, agent::model::ModelChoice::default(), Vec::new())
+}
+
+// Keeping a task branch up to date with its base branch
+fn task_branch_location(app: &tauri::AppHandle, task_id: &str) -> Result<(String, String), String> {
+    let record = agent::records::find_task(app, task_id).ok_or_else(|| format!("Task {} not found", task_id))?;
+    let worktree_path = record.task_field::<String>("worktree_path")
+        .filter(|p| !p.trim().is_empty())
+        .ok_or_else(|| format!("Task {} has no worktree", task_id))?;
+    let base_branch = record.task_field::<String>("base_branch").unwrap_or_else(|| "main".to_string());
+    Ok((worktree_path, base_branch))
+}
+
+#[tauri::command]
+async fn update_task_branch(
+    app: tauri::AppHandle,
+    task_id: String,
+    strategy: git::UpdateStrategy,
+    abort_on_conflict: Option<bool>,
+) -> Result<git::BranchUpdate, String> {
+    println!("Tauri command: update_task_branch called for task '{}' ({:?})", task_id, strategy);
+    let (worktree_path, base_branch) = task_branch_location(&app, &task_id)?;
+    git::update_task_branch(&worktree_path, &base_branch, strategy, abort_on_conflict.unwrap_or(false))
+}
+
+#[tauri::command]
+async fn continue_task_branch_update(app: tauri::AppHandle, task_id: String) -> Result<git::BranchUpdate, String> {
+    println!("Tauri command: continue_task_branch_update called for task '{}'", task_id);
+    let (worktree_path, base_branch) = task_branch_location(&app, &task_id)?;
+    git::continue_branch_update(&worktree_path, &base_branch)
+}
+
+#[tauri::command]
+async fn get_task_branch_status(app: tauri::AppHandle, task_id: String) -> Result<git::BranchStatus, String> {
+    let (worktree_path, base_branch) = task_branch_location(&app, &task_id)?;
+    git::get_branch_status(&worktree_path, &base_branch)
+}
+
 #[tauri::command]
 async fn get_worktree_status(worktree_path: String) -> Result<Vec<git::FileStatus>, String> {
     println!("Tauri command: get_worktree_status called for: {}", worktree_path);
@@ -415,6 +506,7 @@ async fn get_file_diff(worktree_path: String, file_path: String) -> Result<Strin
 // Agent Commands
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn start_agent_process(
     app: tauri::AppHandle,
     task_id: String,
@@ -422,21 +514,57 @@ async fn start_agent_process(
     task_description: String,
     worktree_path: String,
     #[allow(non_snake_case)] profile: Option<String>,
+    model: Option<String>,
+    reasoning_effort: Option<String>,
 ) -> Result<String, String> {
     println!("Tauri command: start_agent_process called for task '{}' in worktree '{}'", task_id, worktree_path);
-    let initial_message = format!("{}: {}", task_title, task_description);
+    let initial_message = agent::prompt::task_prompt(&app, &task_id, &task_title, &task_description, &worktree_path);
     println!("start_agent_process: received profile = {:?}", profile);
     let which = profile
         .map(|s| s.trim().to_string())
         .unwrap_or_else(|| "claude".to_string())
         .to_lowercase();
     println!("start_agent_process: launching agent kind = {}", which);
-    match which.as_str() {
-        "codex" | "chat-codex" | "chatgpt-codex" => {
-            agent::spawn_codex_process(app, task_id, initial_message, worktree_path, None)
-        }
-        _ => agent::spawn_claude_process(app, task_id, initial_message, worktree_path, None)
-    }
+    let choice = agent::model::ModelChoice::new(model, reasoning_effort);
+    agent::spawn_agent_for_profile(app, &which, task_id, initial_message, worktree_path, choice)
+}
+
+// Renders a project's prompt template for the settings preview
+#[tauri::command]
+async fn preview_prompt_template(
+    app: tauri::AppHandle,
+    project_id: String,
+    template: String,
+    task_id: Option<String>,
+) -> Result<String, String> {
+    agent::prompt::preview(&app, &project_id, &template, task_id.as_deref())
+}
+
+// Per-project notes shared with agents, and the additions they propose
+#[tauri::command]
+async fn load_project_notes(app: tauri::AppHandle, project_id: String) -> Result<agent::notes::ProjectNotes, String> {
+    Ok(agent::notes::load_notes(&app, &project_id))
+}
+
+#[tauri::command]
+async fn save_project_notes(
+    app: tauri::AppHandle,
+    project_id: String,
+    content: String,
+    inject_into_prompts: bool,
+) -> Result<agent::notes::ProjectNotes, String> {
+    agent::notes::save_notes(&app, &project_id, content, inject_into_prompts)
+}
+
+#[tauri::command]
+async fn resolve_note_proposal(
+    app: tauri::AppHandle,
+    project_id: String,
+    proposal_id: String,
+    approve: bool,
+    content: Option<String>,
+) -> Result<agent::notes::ProjectNotes, String> {
+    agent::notes::resolve_proposal(&app, &project_id, &proposal_id, approve, content)
 }
 
 // Global agent settings: load and save
@@ -456,9 +584,16 @@ async fn save_agent_settings(app: tauri::AppHandle, settings: AgentSettings) ->
     let val = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
     store.set("settings", val);
     store.save().map_err(|e| e.to_string())?;
+    agent::backends::apply_settings(&settings);
     Ok("Agent settings saved".to_string())
 }
 
+// Built-in and custom agent profiles for profile pickers
+#[tauri::command]
+async fn list_agent_profiles() -> Result<Vec<serde_json::Value>, String> {
+    Ok(agent::backends::list_backends())
+}
+
 // Persisted agent messages per task
 #[tauri::command]
 async fn load_task_agent_messages(app: tauri::AppHandle, task_id: String) -> Result<Vec<agent::AgentMessage>, String> {
@@ -511,21 +646,32 @@ async fn send_agent_message(
     process_id: String,
     message: String,
     worktree_path: String,
+    model: Option<String>,
+    reasoning_effort: Option<String>,
+    images: Option<Vec<String>>,
 ) -> Result<String, String> {
     println!("Tauri command: send_agent_message called for process '{}' with message: {}", process_id, message);
-    agent::send_message_to_process(app, &process_id, message, worktree_path)
+    let choice = agent::model::ModelChoice::new(model, reasoning_effort);
+    agent::send_message_to_process(app, &process_id, message, worktree_path, choice, images.unwrap_or_default())
+}
+
+// Reply box: `@` file autocomplete and pasted images
+#[tauri::command]
+async fn list_worktree_files(worktree_path: String, query: String) -> Result<Vec<String>, String> {
+    agent::attachments::list_worktree_files(&worktree_path, &query, 50)
+}
+
+#[tauri::command]
+async fn save_task_attachment(app: tauri::AppHandle, task_id: String, file_name: String, data: Vec<u8>) -> Result<String, String> {
+    println!("Tauri command: save_task_attachment called for task '{}' ({} bytes)", task_id, data.len());
+    agent::attachments::save_image(&app, &task_id, &file_name, &data)
 }
 
 // Per-process agent messages persistence
 #[tauri::command]
 async fn load_process_agent_messages(app: tauri::AppHandle, task_id: String, process_id: String) -> Result<Vec<agent::AgentMessage>, String> {
-    let file = format!("agent_messages_{}_{}.json", task_id, process_id);
-    let store = app.store(&file).map_err(|e| e.to_string())?;
-    if let Some(val) = store.get("messages") {
-        serde_json::from_value::<Vec<agent::AgentMessage>>(val.clone()).map_err(|e| e.to_string())
-    } else {
-        Ok(vec![])
-    }
+    // Saved transcript plus whatever the reader thread has logged since
+    Ok(agent::persistence::load_messages(&app, &task_id, &process_id))
 }
 
 #[tauri::command]
@@ -539,15 +685,25 @@ async fn save_process_agent_messages(app: tauri::AppHandle, task_id: String, pro
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn send_agent_message_with_profile(
     app: tauri::AppHandle,
     process_id: String,
     message: String,
     worktree_path: String,
     profile: String,
+    model: Option<String>,
+    reasoning_effort: Option<String>,
+    images: Option<Vec<String>>,
+    handoff: Option<bool>,
 ) -> Result<String, String> {
     println!("Tauri command: send_agent_message_with_profile called for process '{}' with profile '{}'", process_id, profile);
-    agent::send_message_with_profile(app, &process_id, message, worktree_path, &profile)
+    let choice = agent::model::ModelChoice::new(model, reasoning_effort);
+    if handoff.unwrap_or(false) {
+        agent::handoff_to_profile(app, &process_id, message, worktree_path, &profile, choice, images.unwrap_or_default())
+    } else {
+        agent::send_message_with_profile(app, &process_id, message, worktree_path, &profile, choice, images.unwrap_or_default())
+    }
 }
 
 #[tauri::command]
@@ -569,12 +725,47 @@ async fn get_agent_messages(process_id: String) -> Result<Vec<agent::AgentMessag
 }
 
 #[tauri::command]
-async fn kill_agent_process(process_id: String) -> Result<String, String> {
+async fn kill_agent_process(app: tauri::AppHandle, process_id: String) -> Result<String, String> {
     println!("Tauri command: kill_agent_process called for process '{}'", process_id);
-    agent::kill_process(&process_id)?;
+    agent::kill_process(&app, &process_id)?;
     Ok("Process killed successfully".to_string())
 }
 
+#[tauri::command]
+async fn pause_agent_process(app: tauri::AppHandle, process_id: String) -> Result<String, String> {
+    println!("Tauri command: pause_agent_process called for process '{}'", process_id);
+    agent::pause_process(&app, &process_id)?;
+    Ok("Process paused".to_string())
+}
+
+#[tauri::command]
+async fn resume_agent_process(app: tauri::AppHandle, process_id: String) -> Result<String, String> {
+    println!("Tauri command: resume_agent_process called for process '{}'", process_id);
+    agent::resume_process(&app, &process_id)?;
+    Ok("Process resumed".to_string())
+}
+
+#[tauri::command]
+async fn move_queued_process(app: tauri::AppHandle, process_id: String, position: usize) -> Result<String, String> {
+    println!("Tauri command: move_queued_process called for process '{}' to position {}", process_id, position);
+    agent::move_queued_process(&app, &process_id, position)?;
+    Ok("Queue updated".to_string())
+}
+
+#[tauri::command]
+async fn respond_to_permission(app: tauri::AppHandle, process_id: String, message_id: String, approved: bool) -> Result<String, String> {
+    println!("Tauri command: respond_to_permission called for process '{}' message '{}' approved={}", process_id, message_id, approved);
+    agent::respond_to_permission(&app, &process_id, &message_id, approved)?;
+    Ok(if approved { "Permission approved".to_string() } else { "Permission denied".to_string() })
+}
+
+#[tauri::command]
+async fn get_usage_summary(app: tauri::AppHandle, project_id: Option<String>, range: Option<String>) -> Result<agent::accounting::UsageSummary, String> {
+    let range = range.unwrap_or_else(|| "all".to_string());
+    println!("Tauri command: get_usage_summary called for project {:?} over '{}'", project_id, range);
+    Ok(agent::get_usage_summary(&app, project_id.as_deref(), &range))
+}
+
 #[tauri::command]
 fn is_dev_mode() -> bool {
     cfg!(debug_assertions)
@@ -585,7 +776,7 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet, list_directory, get_parent_directory, get_home_directory, create_project_directory, initialize_git_repo, validate_git_repository, load_projects_data, save_projects_data, load_tasks_data, save_tasks_data, create_task_worktree, remove_task_worktree, open_worktree_location, open_worktree_in_ide, list_app_worktrees, list_git_branches, merge_worktree_to_base, get_worktree_status, commit_worktree_changes, get_file_diff, start_agent_process, send_agent_message, send_agent_message_with_profile, get_process_list, get_process_details, get_agent_messages, kill_agent_process, load_agent_settings, save_agent_settings, load_task_agent_messages, save_task_agent_messages, load_process_agent_messages, save_process_agent_messages, load_agent_processes, save_agent_processes, get_worktree_diffs, get_worktree_uncommitted_diffs, is_dev_mode])
+        .invoke_handler(tauri::generate_handler![greet, list_directory, get_parent_directory, get_home_directory, create_project_directory, initialize_git_repo, validate_git_repository, load_projects_data, save_projects_data, load_tasks_data, save_tasks_data, create_task_worktree, remove_task_worktree, open_worktree_location, open_worktree_in_ide, list_app_worktrees, list_git_branches, merge_worktree_to_base, get_merge_defaults, get_merge_conflicts, abort_worktree_merge, resolve_conflicts_with_agent, update_task_branch, continue_task_branch_update, get_task_branch_status, get_worktree_status, commit_worktree_changes, get_file_diff, start_agent_process, send_agent_message, send_agent_message_with_profile, list_worktree_files, save_task_attachment, get_process_list, get_process_details, get_agent_messages, kill_agent_process, pause_agent_process, resume_agent_process, move_queued_process, respond_to_permission, get_usage_summary, preview_prompt_template, load_project_notes, save_project_notes, resolve_note_proposal, load_agent_settings, save_agent_settings, list_agent_profiles, load_task_agent_messages, save_task_agent_messages, load_process_agent_messages, save_process_agent_messages, load_agent_processes, save_agent_processes, get_worktree_diffs, get_worktree_uncommitted_diffs, is_dev_mode])
         .setup(|app| {
             // Bind to preferred fixed port, with fallback to a random high port if occupied
             let listener = match std::net::TcpListener::bind(("0.0.0.0", 17872)) {
@@ -594,6 +785,12 @@ pub fn run() {
             };
             let port = listener.local_addr()?.port();
 
+            // Register configured and custom agent profiles before anything can spawn
+            agent::backends::apply_settings(&agent::settings::load_agent_settings(app.handle()));
+
+            // Restore persisted processes; any left running by a previous session become interrupted
+            agent::rehydrate_processes(app.handle());
+
             // Spawn the web server (serves the embedded dist and HTTP API)
             web::spawn(listener, app.handle().clone());
             println!("Embedded web server listening on 0.0.0.0:{}", port);
@@ -651,8 +848,8 @@ pub fn run() {
         .expect("error while running tauri application");
 }
 #[tauri::command]
-async fn get_worktree_diffs(worktree_path: String) -> Result<Vec<git::DiffFile>, String> {
-    git::get_worktree_diffs(&worktree_path)
+async fn get_worktree_diffs(worktree_path: String, base_commit: Option<String>) -> Result<Vec<git::DiffFile>, String> {
+    git::get_worktree_diffs(&worktree_path, base_commit.as_deref().filter(|b| !b.is_empty()))
 }
 
 #[tauri::command]
@@ -443,7 +443,8 @@ async fn invoke(State(state): State<WebState>, Json(req): Json<InvokeReq>) -> im
                 str_arg_from(&args, &["projectPath", "project_path"]),
                 str_arg_from(&args, &["taskId", "task_id"]),
             ) {
-                match create_task_worktree(app.clone(), project_path, task_id).await {
+                let base_ref = str_arg_from(&args, &["baseRef", "base_ref"]);
+                match create_task_worktree(app.clone(), project_path, task_id, base_ref).await {
                     Ok(v) => json!(v),
                     Err(e) => json!(e),
                 }
@@ -487,8 +488,10 @@ async fn invoke(State(state): State<WebState>, Json(req): Json<InvokeReq>) -> im
                 .get("profile")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string());
+            let model = str_arg_from(&args, &["model"]);
+            let reasoning_effort = str_arg_from(&args, &["reasoningEffort", "reasoning_effort"]);
             if let (Some(task_id), Some(task_title), Some(task_description), Some(worktree_path)) = (task_id, task_title, task_description, worktree_path) {
-                match start_agent_process(app.clone(), task_id, task_title, task_description, worktree_path, profile).await {
+                match start_agent_process(app.clone(), task_id, task_title, task_description, worktree_path, profile, model, reasoning_effort).await {
                     Ok(v) => json!(v),
                     Err(e) => json!(e),
                 }
@@ -500,7 +503,10 @@ async fn invoke(State(state): State<WebState>, Json(req): Json<InvokeReq>) -> im
                 str_arg_from(&args, &["message"]),
                 str_arg_from(&args, &["worktreePath", "worktree_path"]),
             ) {
-                match send_agent_message(app.clone(), process_id, message, worktree_path).await {
+                let model = str_arg_from(&args, &["model"]);
+                let reasoning_effort = str_arg_from(&args, &["reasoningEffort", "reasoning_effort"]);
+                let images = args.get("images").and_then(|v| serde_json::from_value(v.clone()).ok());
+                match send_agent_message(app.clone(), process_id, message, worktree_path, model, reasoning_effort, images).await {
                     Ok(v) => json!(v),
                     Err(e) => json!(e),
                 }
@@ -513,12 +519,37 @@ async fn invoke(State(state): State<WebState>, Json(req): Json<InvokeReq>) -> im
                 str_arg_from(&args, &["worktreePath", "worktree_path"]),
                 str_arg_from(&args, &["profile"]),
             ) {
-                match send_agent_message_with_profile(app.clone(), process_id, message, worktree_path, profile).await {
+                let model = str_arg_from(&args, &["model"]);
+                let reasoning_effort = str_arg_from(&args, &["reasoningEffort", "reasoning_effort"]);
+                let images = args.get("images").and_then(|v| serde_json::from_value(v.clone()).ok());
+                let handoff = Some(str_arg_from(&args, &["handoff"]).as_deref() == Some("true"));
+                match send_agent_message_with_profile(app.clone(), process_id, message, worktree_path, profile, model, reasoning_effort, images, handoff).await {
                     Ok(v) => json!(v),
                     Err(e) => json!(e),
                 }
             } else { json!("Missing processId/message/worktreePath/profile") }
         }
+        "list_worktree_files" => {
+            if let Some(worktree_path) = str_arg_from(&args, &["worktreePath", "worktree_path"]) {
+                let query = str_arg_from(&args, &["query"]).unwrap_or_default();
+                match list_worktree_files(worktree_path, query).await {
+                    Ok(v) => json!(v),
+                    Err(e) => json!(e),
+                }
+            } else { json!("Missing worktreePath") }
+        }
+        "save_task_attachment" => {
+            if let (Some(task_id), Some(file_name), Some(data)) = (
+                str_arg_from(&args, &["taskId", "task_id"]),
+                str_arg_from(&args, &["fileName", "file_name"]),
+                args.get("data").and_then(|v| serde_json::from_value::<Vec<u8>>(v.clone()).ok()),
+            ) {
+                match save_task_attachment(app.clone(), task_id, file_name, data).await {
+                    Ok(v) => json!(v),
+                    Err(e) => json!(e),
+                }
+            } else { json!("Missing taskId/fileName/data") }
+        }
         "get_process_list" => match get_process_list().await {
             Ok(v) => json!(v),
             Err(_) => json!([]),
@@ -541,12 +572,102 @@ async fn invoke(State(state): State<WebState>, Json(req): Json<InvokeReq>) -> im
         }
         "kill_agent_process" => {
             if let Some(process_id) = str_arg_from(&args, &["processId", "process_id"]) {
-                match kill_agent_process(process_id).await {
+                match kill_agent_process(app.clone(), process_id).await {
+                    Ok(v) => json!(v),
+                    Err(e) => json!(e),
+                }
+            } else { json!("Missing processId") }
+        }
+        "pause_agent_process" => {
+            if let Some(process_id) = str_arg_from(&args, &["processId", "process_id"]) {
+                match pause_agent_process(app.clone(), process_id).await {
                     Ok(v) => json!(v),
                     Err(e) => json!(e),
                 }
             } else { json!("Missing processId") }
         }
+        "resume_agent_process" => {
+            if let Some(process_id) = str_arg_from(&args, &["processId", "process_id"]) {
+                match resume_agent_process(app.clone(), process_id).await {
+                    Ok(v) => json!(v),
+                    Err(e) => json!(e),
+                }
+            } else { json!("Missing processId") }
+        }
+        "move_queued_process" => {
+            let process_id = str_arg_from(&args, &["processId", "process_id"]);
+            let position = str_arg_from(&args, &["position"]).and_then(|v| v.parse::<usize>().ok());
+            if let (Some(process_id), Some(position)) = (process_id, position) {
+                match move_queued_process(app.clone(), process_id, position).await {
+                    Ok(v) => json!(v),
+                    Err(e) => json!(e),
+                }
+            } else { json!("Missing processId/position") }
+        }
+        "respond_to_permission" => {
+            let process_id = str_arg_from(&args, &["processId", "process_id"]);
+            let message_id = str_arg_from(&args, &["messageId", "message_id"]);
+            let approved = str_arg_from(&args, &["approved"]).map(|v| v == "true");
+            if let (Some(process_id), Some(message_id), Some(approved)) = (process_id, message_id, approved) {
+                match respond_to_permission(app.clone(), process_id, message_id, approved).await {
+                    Ok(v) => json!(v),
+                    Err(e) => json!(e),
+                }
+            } else { json!("Missing processId/messageId/approved") }
+        }
+        "get_usage_summary" => {
+            let project_id = str_arg_from(&args, &["projectId", "project_id"]).filter(|s| !s.is_empty());
+            let range = str_arg_from(&args, &["range"]);
+            match get_usage_summary(app.clone(), project_id, range).await {
+                Ok(v) => json!(v),
+                Err(e) => json!(e),
+            }
+        }
+        "load_project_notes" => {
+            if let Some(project_id) = str_arg_from(&args, &["projectId", "project_id"]) {
+                match load_project_notes(app.clone(), project_id).await {
+                    Ok(v) => json!(v),
+                    Err(e) => json!(e),
+                }
+            } else { json!("Missing projectId") }
+        }
+        "save_project_notes" => {
+            if let (Some(project_id), Some(content)) = (
+                str_arg_from(&args, &["projectId", "project_id"]),
+                str_arg_from(&args, &["content"]),
+            ) {
+                let inject = str_arg_from(&args, &["injectIntoPrompts", "inject_into_prompts"]).as_deref() == Some("true");
+                match save_project_notes(app.clone(), project_id, content, inject).await {
+                    Ok(v) => json!(v),
+                    Err(e) => json!(e),
+                }
+            } else { json!("Missing projectId/content") }
+        }
+        "resolve_note_proposal" => {
+            if let (Some(project_id), Some(proposal_id)) = (
+                str_arg_from(&args, &["projectId", "project_id"]),
+                str_arg_from(&args, &["proposalId", "proposal_id"]),
+            ) {
+                let approve = str_arg_from(&args, &["approve"]).as_deref() == Some("true");
+                let content = str_arg_from(&args, &["content"]);
+                match resolve_note_proposal(app.clone(), project_id, proposal_id, approve, content).await {
+                    Ok(v) => json!(v),
+                    Err(e) => json!(e),
+                }
+            } else { json!("Missing projectId/proposalId") }
+        }
+        "preview_prompt_template" => {
+            if let (Some(project_id), Some(template)) = (
+                str_arg_from(&args, &["projectId", "project_id"]),
+                str_arg_from(&args, &["template"]),
+            ) {
+                let task_id = str_arg_from(&args, &["taskId", "task_id"]).filter(|s| !s.is_empty());
+                match preview_prompt_template(app.clone(), project_id, template, task_id).await {
+                    Ok(v) => json!(v),
+                    Err(e) => json!(e),
+                }
+            } else { json!("Missing projectId/template") }
+        }
 
         // Settings and persistence
         "load_agent_settings" => match load_agent_settings(app.clone()).await {
@@ -562,6 +683,10 @@ async fn invoke(State(state): State<WebState>, Json(req): Json<InvokeReq>) -> im
                 Err(_) => json!("Invalid settings"),
             }
         }
+        "list_agent_profiles" => match list_agent_profiles().await {
+            Ok(v) => json!(v),
+            Err(_) => json!([]),
+        },
         "load_task_agent_messages" => {
             if let Some(task_id) = str_arg_from(&args, &["taskId", "task_id"]) {
                 match load_task_agent_messages(app.clone(), task_id).await {
@@ -625,7 +750,8 @@ async fn invoke(State(state): State<WebState>, Json(req): Json<InvokeReq>) -> im
         "get_worktree_diffs" => {
             if let Some(path) = str_arg_from(&args, &["worktreePath", "worktree_path"]) {
                 println!("get_worktree_diffs invoked with path: {}", path);
-                match super::get_worktree_diffs(path.clone()).await {
+                let base_commit = str_arg_from(&args, &["baseCommit", "base_commit"]);
+                match super::get_worktree_diffs(path.clone(), base_commit).await {
                     Ok(v) => {
                         println!("get_worktree_diffs returning {} files", v.len());
                         json!(v)
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// Budget limits; mirrors `agent::budget::AgentBudget` in the backend.
+/// Unset limits on a task fall back to the project's. Turn and minute limits
+/// apply to each run; cost and token limits to all runs of the task (when set
+/// on it) or of the project.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct AgentBudget {
+    pub max_cost_usd: Option<f64>,
+    pub max_tokens: Option<u64>,
+    pub max_turns: Option<u32>,
+    pub max_minutes: Option<u64>,
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+/// How a task branch lands on its base branch; mirrors `git::MergeStrategy`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    #[default]
+    FastForwardOrMerge,
+    FastForwardOnly,
+    MergeCommit,
+    Squash,
+    Rebase,
+}
+
+impl MergeStrategy {
+    pub fn all() -> Vec<MergeStrategy> {
+        vec![MergeStrategy::FastForwardOrMerge, MergeStrategy::FastForwardOnly, MergeStrategy::MergeCommit, MergeStrategy::Squash, MergeStrategy::Rebase]
+    }
+
+    /// Value used in `<select>` options
+    pub fn as_value(&self) -> &'static str {
+        match self {
+            MergeStrategy::FastForwardOrMerge => "fast_forward_or_merge",
+            MergeStrategy::FastForwardOnly => "fast_forward_only",
+            MergeStrategy::MergeCommit => "merge_commit",
+            MergeStrategy::Squash => "squash",
+            MergeStrategy::Rebase => "rebase",
+        }
+    }
+
+    pub fn from_value(value: &str) -> Self {
+        MergeStrategy::all().into_iter().find(|s| s.as_value() == value).unwrap_or_default()
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MergeStrategy::FastForwardOrMerge => "Fast-forward or merge commit",
+            MergeStrategy::FastForwardOnly => "Fast-forward only",
+            MergeStrategy::MergeCommit => "Merge commit",
+            MergeStrategy::Squash => "Squash",
+            MergeStrategy::Rebase => "Rebase",
+        }
+    }
+
+    /// Whether completing a merge with resolved conflicts can use this strategy
+    pub fn takes_resolutions(&self) -> bool {
+        !matches!(self, MergeStrategy::FastForwardOnly | MergeStrategy::Rebase)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            MergeStrategy::FastForwardOrMerge => "Fast-forwards when the base branch has not moved on, otherwise records a merge commit.",
+            MergeStrategy::FastForwardOnly => "Moves the base branch to the task branch; fails once the base branch has moved on.",
+            MergeStrategy::MergeCommit => "Always records a merge commit with both branches as parents.",
+            MergeStrategy::Squash => "Adds one commit with all of the task's changes.",
+            MergeStrategy::Rebase => "Replays the task's commits on top of the base branch.",
+        }
+    }
+}
+
+/// What the merge dialog opens with (`get_merge_defaults`)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MergeDefaults {
+    pub strategy: MergeStrategy, // the project's default
+    pub squash_message: String,  // task title, description and the branch's commit subjects
+}
+
+/// A file the merge into the base branch could not merge automatically; mirrors `git::ConflictFile`.
+/// Sides are `None` when that side deleted the file or it is binary.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ConflictFile {
+    pub path: String,
+    pub ours: Option<String>,   // base branch
+    pub theirs: Option<String>, // task branch
+    pub merged: Option<String>, // contents with conflict markers
+    #[serde(default)]
+    pub binary: bool,
+}
+
+/// Final contents of a conflicted path; `None` deletes it
+#[derive(Debug, Clone, Serialize)]
+pub struct ConflictResolution {
+    pub path: String,
+    pub content: Option<String>,
+}
+
+/// Result of `merge_worktree_to_base`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum MergeOutcome {
+    Merged { message: String },
+    UpToDate { message: String },
+    Conflicted { files: Vec<ConflictFile> },
+}
+
+/// Commits the task branch has that its base branch does not, and the other way round
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+pub struct AheadBehind {
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateState {
+    Updated,
+    UpToDate,
+    Paused,  // stopped on conflicts, waiting to be continued or aborted
+    Aborted, // hit conflicts and was rolled back
+}
+
+/// Result of `update_task_branch` / `continue_task_branch_update`; mirrors `git::BranchUpdate`
+#[derive(Debug, Clone, Deserialize)]
+pub struct BranchUpdate {
+    pub state: UpdateState,
+    pub before: AheadBehind,
+    pub after: AheadBehind,
+    pub conflicts: Vec<String>,
+    pub base_commit: Option<String>, // base branch commit the task branch now builds on
+}
+
+/// Where a task branch stands against its base branch (`get_task_branch_status`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct BranchStatus {
+    pub counts: AheadBehind,
+    pub in_progress: Option<String>, // "rebase" or "merge" paused in the worktree
+    pub conflicts: Vec<String>,
+}
@@ -1,7 +1,21 @@
+pub mod budget;
+pub mod merge;
+pub mod model_choice;
+pub mod notes;
+pub mod policy;
 pub mod project;
+pub mod retry;
 pub mod task;
+pub mod usage;
 
 // Export the Task and TaskStatus types for use throughout the app
 // Project is now being used for the ProjectModal
 pub use project::Project;
-pub use task::{Task, TaskStatus, AgentProfile};
+pub use budget::AgentBudget;
+pub use merge::{AheadBehind, BranchStatus, BranchUpdate, ConflictFile, ConflictResolution, MergeDefaults, MergeOutcome, MergeStrategy, UpdateState};
+pub use model_choice::{ModelChoice, REASONING_EFFORTS};
+pub use notes::ProjectNotes;
+pub use policy::{AgentPolicy, PermissionMode, SandboxLevel};
+pub use retry::RetryPolicy;
+pub use task::{Task, TaskDetails, TaskStatus, AgentProfile, AgentProfileInfo};
+pub use usage::{format_tokens, UsageSummary, UsageTotals};
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// Reasoning effort levels the backend accepts, lowest first
+pub const REASONING_EFFORTS: [&str; 3] = ["low", "medium", "high"];
+
+/// Model and reasoning effort for an agent run; mirrors `agent::model::ModelChoice`.
+/// `None` leaves the choice to the task (for a message) or the CLI (for a task).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ModelChoice {
+    pub model: Option<String>,
+    pub reasoning_effort: Option<String>,
+}
+
+impl ModelChoice {
+    /// Empty input means unset
+    pub fn parse_field(value: &str) -> Option<String> {
+        let trimmed = value.trim();
+        if trimmed.is_empty() { None } else { Some(trimmed.to_string()) }
+    }
+}
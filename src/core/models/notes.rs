@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// An addition to the project notes proposed by an agent; mirrors `agent::notes::NoteProposal`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NoteProposal {
+    pub id: String,
+    pub content: String,
+    pub task_id: String,
+    pub process_id: String,
+    pub created_at: String,
+}
+
+/// Per-project notes shared with agents (`project_notes.json`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ProjectNotes {
+    pub content: String,
+    pub inject_into_prompts: bool, // prepend the notes to each run's first prompt
+    pub proposals: Vec<NoteProposal>,
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+/// Agent permission and tool policy; mirrors `agent::policy::AgentPolicy` in the backend
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionMode {
+    #[default]
+    Bypass,
+    AcceptEdits,
+    Plan,
+    Ask,
+}
+
+impl PermissionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PermissionMode::Bypass => "bypass",
+            PermissionMode::AcceptEdits => "accept_edits",
+            PermissionMode::Plan => "plan",
+            PermissionMode::Ask => "ask",
+        }
+    }
+
+    pub fn from_key(value: &str) -> Self {
+        match value {
+            "accept_edits" => PermissionMode::AcceptEdits,
+            "plan" => PermissionMode::Plan,
+            "ask" => PermissionMode::Ask,
+            _ => PermissionMode::Bypass,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PermissionMode::Bypass => "Bypass (no prompts)",
+            PermissionMode::AcceptEdits => "Accept edits",
+            PermissionMode::Plan => "Plan only",
+            PermissionMode::Ask => "Ask",
+        }
+    }
+
+    pub fn all() -> Vec<PermissionMode> {
+        vec![PermissionMode::Bypass, PermissionMode::AcceptEdits, PermissionMode::Plan, PermissionMode::Ask]
+    }
+
+    /// Whether tool prompts are answered on the board in this mode
+    pub fn routes_permissions(&self) -> bool {
+        matches!(self, PermissionMode::AcceptEdits | PermissionMode::Ask)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxLevel {
+    ReadOnly,
+    WorkspaceWrite,
+    #[default]
+    FullAccess,
+}
+
+impl SandboxLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SandboxLevel::ReadOnly => "read_only",
+            SandboxLevel::WorkspaceWrite => "workspace_write",
+            SandboxLevel::FullAccess => "full_access",
+        }
+    }
+
+    pub fn from_key(value: &str) -> Self {
+        match value {
+            "read_only" => SandboxLevel::ReadOnly,
+            "workspace_write" => SandboxLevel::WorkspaceWrite,
+            _ => SandboxLevel::FullAccess,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SandboxLevel::ReadOnly => "Read only",
+            SandboxLevel::WorkspaceWrite => "Worktree writes",
+            SandboxLevel::FullAccess => "Full access",
+        }
+    }
+
+    pub fn all() -> Vec<SandboxLevel> {
+        vec![SandboxLevel::ReadOnly, SandboxLevel::WorkspaceWrite, SandboxLevel::FullAccess]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AgentPolicy {
+    pub allowed_tools: Vec<String>,
+    pub disallowed_tools: Vec<String>,
+    pub permission_mode: PermissionMode,
+    pub sandbox: SandboxLevel,
+    pub network: bool,
+}
+
+impl Default for AgentPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_tools: ["Read", "Write", "Edit", "MultiEdit"].iter().map(|s| s.to_string()).collect(),
+            disallowed_tools: Vec::new(),
+            permission_mode: PermissionMode::default(),
+            sandbox: SandboxLevel::default(),
+            network: true,
+        }
+    }
+}
+
+/// Splits a comma separated tool list as typed in the policy editor
+pub fn parse_tool_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
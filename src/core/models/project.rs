@@ -2,6 +2,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::budget::AgentBudget;
+use super::merge::MergeStrategy;
+use super::policy::AgentPolicy;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Project {
     pub id: String,
@@ -11,6 +15,16 @@ pub struct Project {
     pub setup_script: Option<String>,
     pub cleanup_script: Option<String>,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub agent_policy: AgentPolicy,
+    #[serde(default)]
+    pub agent_budget: AgentBudget,
+    #[serde(default)]
+    pub max_concurrent_agents: Option<u32>, // None = only the global limit applies
+    #[serde(default)]
+    pub prompt_template: Option<String>, // first prompt of every task run; None = "{title}: {description}"
+    #[serde(default)]
+    pub merge_strategy: MergeStrategy, // default for merges; each merge can pick another
 }
 
 impl Project {
@@ -24,6 +38,11 @@ impl Project {
             setup_script: None,
             cleanup_script: None,
             created_at: Utc::now(),
+            agent_policy: AgentPolicy::default(),
+            agent_budget: AgentBudget::default(),
+            max_concurrent_agents: None,
+            prompt_template: None,
+            merge_strategy: MergeStrategy::default(),
         }
     }
     
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// Automatic retries of failed runs; mirrors `agent::retry::RetryPolicy` in the backend
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub max_attempts: u32, // including the first run; 1 = no retries
+    pub backoff_secs: u64, // doubled for each later attempt
+    pub retryable_patterns: Vec<String>, // empty = retry every failure
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_attempts: 1, backoff_secs: 10, retryable_patterns: Vec::new() }
+    }
+}
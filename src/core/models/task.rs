@@ -2,10 +2,54 @@ use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::budget::AgentBudget;
+use super::model_choice::ModelChoice;
+use super::policy::AgentPolicy;
+use super::retry::RetryPolicy;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum AgentProfile {
     ClaudeCode,
     Codex,
+    Custom(String), // name of a custom agent from agent settings
+}
+
+impl AgentProfile {
+    /// Profile string passed to the backend agent registry
+    pub fn as_profile_str(&self) -> String {
+        match self {
+            AgentProfile::ClaudeCode => "claude".to_string(),
+            AgentProfile::Codex => "codex".to_string(),
+            AgentProfile::Custom(name) => name.clone(),
+        }
+    }
+
+    pub fn from_profile_str(value: &str) -> Self {
+        match value {
+            "claude" => AgentProfile::ClaudeCode,
+            "codex" => AgentProfile::Codex,
+            other => AgentProfile::Custom(other.to_string()),
+        }
+    }
+}
+
+/// Agent profile as reported by `list_agent_profiles`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentProfileInfo {
+    pub name: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub custom: bool,
+}
+
+impl AgentProfileInfo {
+    /// Profiles that are always available, used until the backend list loads
+    pub fn builtin() -> Vec<AgentProfileInfo> {
+        vec![
+            AgentProfileInfo { name: "claude".to_string(), display_name: "Claude Code".to_string(), custom: false },
+            AgentProfileInfo { name: "codex".to_string(), display_name: "Codex".to_string(), custom: false },
+        ]
+    }
 }
 
 fn default_agent_profile() -> AgentProfile {
@@ -21,6 +65,7 @@ pub enum TaskStatus {
     ToDo,
     InProgress,
     InReview,
+    Conflicted, // merge into the base branch hit conflicts; kept until merged or aborted
     Done,
     Cancelled,
 }
@@ -31,6 +76,7 @@ impl TaskStatus {
             TaskStatus::ToDo => "To Do",
             TaskStatus::InProgress => "In Progress",
             TaskStatus::InReview => "In Review", 
+            TaskStatus::Conflicted => "Conflicted",
             TaskStatus::Done => "Done",
             TaskStatus::Cancelled => "Cancelled",
         }
@@ -41,6 +87,7 @@ impl TaskStatus {
             TaskStatus::ToDo,
             TaskStatus::InProgress,
             TaskStatus::InReview,
+            TaskStatus::Conflicted,
             TaskStatus::Done,
             TaskStatus::Cancelled,
         ]
@@ -60,6 +107,32 @@ pub struct Task {
     pub profile: AgentProfile,
     #[serde(default = "default_base_branch")]
     pub base_branch: String,
+    #[serde(default)]
+    pub base_commit: Option<String>, // commit the worktree branch was created from
+    #[serde(default)]
+    pub agent_policy: Option<AgentPolicy>, // replaces the project policy when set
+    #[serde(default)]
+    pub agent_budget: AgentBudget, // limits set here replace the project's
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub model: Option<String>, // None = the CLI's default model
+    #[serde(default)]
+    pub reasoning_effort: Option<String>, // "low" | "medium" | "high"; None = CLI default
+}
+
+/// Fields edited in the Edit Task dialog
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskDetails {
+    pub title: String,
+    pub description: String,
+    pub labels: Vec<String>,
+    pub agent_policy: Option<AgentPolicy>,
+    pub agent_budget: AgentBudget,
+    pub retry_policy: RetryPolicy,
+    pub model_choice: ModelChoice,
 }
 
 impl Task {
@@ -75,6 +148,13 @@ impl Task {
             worktree_path: None,
             profile: default_agent_profile(),
             base_branch: default_base_branch(),
+            base_commit: None,
+            agent_policy: None,
+            agent_budget: AgentBudget::default(),
+            retry_policy: RetryPolicy::default(),
+            labels: Vec::new(),
+            model: None,
+            reasoning_effort: None,
         }
     }
 
@@ -86,6 +166,21 @@ impl Task {
         self.description = new_description;
     }
 
+    pub fn apply_details(&mut self, details: TaskDetails) {
+        self.update_title(details.title);
+        self.update_description(details.description);
+        self.labels = details.labels;
+        self.agent_policy = details.agent_policy;
+        self.agent_budget = details.agent_budget;
+        self.retry_policy = details.retry_policy;
+        self.model = details.model_choice.model;
+        self.reasoning_effort = details.model_choice.reasoning_effort;
+    }
+
+    pub fn model_choice(&self) -> ModelChoice {
+        ModelChoice { model: self.model.clone(), reasoning_effort: self.reasoning_effort.clone() }
+    }
+
     pub fn update_status(&mut self, new_status: TaskStatus) {
         self.status = new_status;
     }
@@ -94,6 +189,10 @@ impl Task {
         self.worktree_path = path;
     }
 
+    pub fn set_base_commit(&mut self, commit: Option<String>) {
+        self.base_commit = commit;
+    }
+
     pub fn set_base_branch(&mut self, branch: String) {
         self.base_branch = branch;
     }
@@ -0,0 +1,46 @@
+use serde::Deserialize;
+
+/// Summed usage of a group of runs; mirrors `agent::accounting::UsageTotals`
+#[derive(Debug, Clone, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct UsageTotals {
+    pub runs: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cached_tokens
+    }
+}
+
+/// One row of a usage breakdown (a task, agent kind, day or project)
+#[derive(Debug, Clone, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct UsageBucket {
+    pub key: String,
+    pub label: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+/// Result of `get_usage_summary`
+#[derive(Debug, Clone, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct UsageSummary {
+    pub project_id: Option<String>,
+    pub range: String,
+    pub totals: UsageTotals,
+    pub by_task: Vec<UsageBucket>,
+    pub by_kind: Vec<UsageBucket>,
+    pub by_day: Vec<UsageBucket>,
+    pub by_project: Vec<UsageBucket>,
+}
+
+/// Compact token count, e.g. `12.3k`
+pub fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1000 { format!("{:.1}k", tokens as f64 / 1000.0) } else { tokens.to_string() }
+}
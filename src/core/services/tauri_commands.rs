@@ -1,5 +1,6 @@
 use wasm_bindgen::prelude::*;
 use serde_wasm_bindgen::to_value;
+use crate::core::models::ModelChoice;
 
 #[wasm_bindgen]
 extern "C" {
@@ -43,10 +44,11 @@ pub async fn save_tasks_data(project_id: &str, tasks: Vec<serde_json::Value>) ->
     })).await
 }
 
-pub async fn create_task_worktree(project_path: &str, task_id: &str) -> Result<JsValue, String> {
+pub async fn create_task_worktree(project_path: &str, task_id: &str, base_ref: &str) -> Result<JsValue, String> {
     execute_tauri_command("create_task_worktree", serde_json::json!({
         "projectPath": project_path,
-        "taskId": task_id
+        "taskId": task_id,
+        "baseRef": base_ref
     })).await
 }
 
@@ -57,16 +59,22 @@ pub async fn remove_task_worktree(worktree_path: &str, project_path: &str) -> Re
     })).await
 }
 
-pub async fn start_agent_process(task_id: &str, task_title: &str, task_description: &str, worktree_path: &str, profile: &str) -> Result<JsValue, String> {
+pub async fn start_agent_process(task_id: &str, task_title: &str, task_description: &str, worktree_path: &str, profile: &str, choice: &ModelChoice) -> Result<JsValue, String> {
     execute_tauri_command("start_agent_process", serde_json::json!({
         "taskId": task_id,
         "taskTitle": task_title,
         "taskDescription": task_description,
         "worktreePath": worktree_path,
-        "profile": profile
+        "profile": profile,
+        "model": choice.model,
+        "reasoningEffort": choice.reasoning_effort
     })).await
 }
 
+pub async fn list_agent_profiles() -> Result<JsValue, String> {
+    execute_tauri_command("list_agent_profiles", serde_json::json!({})).await
+}
+
 pub async fn open_worktree_location(worktree_path: &str) -> Result<JsValue, String> {
     execute_tauri_command("open_worktree_location", serde_json::json!({
         "worktreePath": worktree_path
@@ -1,11 +1,19 @@
 use leptos::task::spawn_local;
+use serde::Deserialize;
 use serde_wasm_bindgen::from_value;
-use crate::core::models::{Task, AgentProfile};
+use crate::core::models::{Task, AgentProfileInfo};
 use super::tauri_commands::*;
 use super::storage::load_projects;
 
-// Create a worktree for a task
-pub async fn create_worktree_for_task(project_id: &str, task_id: &str) -> Result<String, String> {
+/// Worktree created for a task, as returned by `create_task_worktree`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TaskWorktree {
+    pub path: String,
+    pub base_commit: String,
+}
+
+// Create a worktree for a task, branching from `base_ref` (branch, tag or commit)
+pub async fn create_worktree_for_task(project_id: &str, task_id: &str, base_ref: &str) -> Result<TaskWorktree, String> {
     // First, get the project path from storage
     let projects = load_projects().await?;
     let project = projects.iter()
@@ -14,12 +22,16 @@ pub async fn create_worktree_for_task(project_id: &str, task_id: &str) -> Result
 
     web_sys::console::log_1(&format!("Creating worktree for task {}", task_id).into());
 
-    match create_task_worktree(&project.project_path, task_id).await {
+    match create_task_worktree(&project.project_path, task_id, base_ref).await {
         Ok(js_result) => {
-            match from_value::<String>(js_result) {
-                Ok(worktree_path) => {
-                    web_sys::console::log_1(&format!("Worktree created successfully at: {}", worktree_path).into());
-                    Ok(worktree_path)
+            // Errors arrive as plain strings over the LAN bridge
+            if let Some(error) = js_result.as_string() {
+                return Err(error);
+            }
+            match from_value::<TaskWorktree>(js_result) {
+                Ok(worktree) => {
+                    web_sys::console::log_1(&format!("Worktree created successfully at: {} (base {})", worktree.path, worktree.base_commit).into());
+                    Ok(worktree)
                 }
                 Err(e) => Err(format!("Failed to parse worktree creation result: {:?}", e))
             }
@@ -52,14 +64,11 @@ pub async fn remove_worktree_for_task(project_id: &str, worktree_path: &str) ->
 
 // Start agent process for a task
 pub async fn start_agent_for_task(task: &Task, worktree_path: &str) -> Result<String, String> {
-    let profile_str = match task.profile {
-        AgentProfile::Codex => "codex",
-        AgentProfile::ClaudeCode => "claude",
-    };
+    let profile_str = task.profile.as_profile_str();
 
     web_sys::console::log_1(&format!("Starting agent process for task: {} with profile {}", task.id, profile_str).into());
 
-    match start_agent_process(&task.id, &task.title, &task.description, worktree_path, profile_str).await {
+    match start_agent_process(&task.id, &task.title, &task.description, worktree_path, &profile_str, &task.model_choice()).await {
         Ok(js_result) => {
             match from_value::<String>(js_result) {
                 Ok(process_id) => {
@@ -119,4 +128,21 @@ pub fn open_worktree_in_ide_async(worktree_path: String) {
             }
         }
     });
-}
\ No newline at end of file
+}
+// Load the agent profiles (built-in and custom) for profile pickers
+pub async fn load_agent_profiles() -> Vec<AgentProfileInfo> {
+    match list_agent_profiles().await {
+        Ok(js_result) => match from_value::<Vec<AgentProfileInfo>>(js_result) {
+            Ok(profiles) if !profiles.is_empty() => profiles,
+            Ok(_) => AgentProfileInfo::builtin(),
+            Err(e) => {
+                web_sys::console::error_1(&format!("Failed to parse agent profiles: {:?}", e).into());
+                AgentProfileInfo::builtin()
+            }
+        },
+        Err(e) => {
+            web_sys::console::error_1(&format!("Failed to load agent profiles: {}", e).into());
+            AgentProfileInfo::builtin()
+        }
+    }
+}
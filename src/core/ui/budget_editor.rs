@@ -0,0 +1,50 @@
+use leptos::prelude::*;
+use crate::core::models::AgentBudget;
+
+fn parse_limit<T: std::str::FromStr>(value: &str) -> Option<T> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() { None } else { trimmed.parse().ok() }
+}
+
+fn show_limit<T: ToString>(limit: Option<T>) -> String {
+    limit.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Form fields for per-run cost and time limits. Empty fields mean no limit
+/// (or, on a task, the project's limit).
+#[component]
+pub fn AgentBudgetEditor(budget: RwSignal<AgentBudget>) -> impl IntoView {
+    view! {
+        <div class="budget-editor">
+            <div class="form-group">
+                <label>"MAX COST (USD)"</label>
+                <input type="number" min="0" step="0.01" placeholder="No limit"
+                    prop:value=move || show_limit(budget.get().max_cost_usd)
+                    on:change=move |ev| { let v = parse_limit(&event_target_value(&ev)); budget.update(|b| b.max_cost_usd = v); }
+                />
+            </div>
+            <div class="form-group">
+                <label>"MAX TOKENS"</label>
+                <input type="number" min="0" step="1000" placeholder="No limit"
+                    prop:value=move || show_limit(budget.get().max_tokens)
+                    on:change=move |ev| { let v = parse_limit(&event_target_value(&ev)); budget.update(|b| b.max_tokens = v); }
+                />
+            </div>
+            <div class="form-group">
+                <label>"MAX TURNS"</label>
+                <input type="number" min="0" step="1" placeholder="No limit"
+                    prop:value=move || show_limit(budget.get().max_turns)
+                    on:change=move |ev| { let v = parse_limit(&event_target_value(&ev)); budget.update(|b| b.max_turns = v); }
+                />
+            </div>
+            <div class="form-group">
+                <label>"MAX MINUTES"</label>
+                <input type="number" min="0" step="1" placeholder="No limit"
+                    prop:value=move || show_limit(budget.get().max_minutes)
+                    on:change=move |ev| { let v = parse_limit(&event_target_value(&ev)); budget.update(|b| b.max_minutes = v); }
+                />
+                <small class="form-help">"Limits apply to each agent run. A run that crosses one is stopped."</small>
+            </div>
+        </div>
+    }
+}
@@ -1,2 +1,8 @@
-// Placeholder for shared UI components
-// Will be populated as we identify reusable components
\ No newline at end of file
+// Shared UI components used by more than one feature
+pub mod budget_editor;
+pub mod model_picker;
+pub mod policy_editor;
+
+pub use budget_editor::AgentBudgetEditor;
+pub use model_picker::ModelPicker;
+pub use policy_editor::AgentPolicyEditor;
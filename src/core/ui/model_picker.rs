@@ -0,0 +1,49 @@
+use leptos::prelude::*;
+use crate::core::models::{ModelChoice, REASONING_EFFORTS};
+
+/// Model name and reasoning effort inputs. `default_label` names what an empty
+/// field falls back to (e.g. "CLI default" on a task, "Task default" on a reply).
+/// `inline` drops the labels for use next to the reply box.
+#[component]
+pub fn ModelPicker(
+    choice: RwSignal<ModelChoice>,
+    #[prop(into)] default_label: String,
+    #[prop(optional)] inline: bool,
+) -> impl IntoView {
+    let effort_default = default_label.clone();
+    let model_input = view! {
+        <input type="text" class="model-input" placeholder=default_label
+            prop:value=move || choice.get().model.unwrap_or_default()
+            on:change=move |ev| { let v = ModelChoice::parse_field(&event_target_value(&ev)); choice.update(|c| c.model = v); }
+        />
+    };
+    let effort_select = view! {
+        <select class="effort-select" title="Reasoning effort" on:change=move |ev| {
+            let v = ModelChoice::parse_field(&event_target_value(&ev));
+            choice.update(|c| c.reasoning_effort = v);
+        }>
+            <option value="" selected=move || choice.get().reasoning_effort.is_none()>{format!("Effort: {}", effort_default.to_lowercase())}</option>
+            {REASONING_EFFORTS.iter().map(|effort| view! {
+                <option value=*effort selected=move || choice.get().reasoning_effort.as_deref() == Some(*effort)>{format!("Effort: {}", effort)}</option>
+            }).collect::<Vec<_>>()}
+        </select>
+    };
+
+    if inline {
+        view! { <div class="model-picker inline">{model_input}{effort_select}</div> }.into_any()
+    } else {
+        view! {
+            <div class="model-picker">
+                <div class="form-group">
+                    <label>"MODEL"</label>
+                    {model_input}
+                </div>
+                <div class="form-group">
+                    <label>"REASONING EFFORT"</label>
+                    {effort_select}
+                    <small class="form-help">"Claude maps effort to its thinking budget; Codex passes it as model_reasoning_effort."</small>
+                </div>
+            </div>
+        }.into_any()
+    }
+}
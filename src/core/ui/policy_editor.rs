@@ -0,0 +1,81 @@
+use leptos::prelude::*;
+use crate::core::models::{AgentPolicy, PermissionMode, SandboxLevel};
+use crate::core::models::policy::parse_tool_list;
+
+/// Form fields for an agent permission and tool policy
+#[component]
+pub fn AgentPolicyEditor(policy: RwSignal<AgentPolicy>) -> impl IntoView {
+    view! {
+        <div class="policy-editor">
+            <div class="form-group">
+                <label>"PERMISSION MODE"</label>
+                <select on:change=move |ev| {
+                    let mode = PermissionMode::from_key(&event_target_value(&ev));
+                    policy.update(|p| {
+                        // A pre-allowed Bash would never reach the board
+                        if mode.routes_permissions() && !p.permission_mode.routes_permissions() {
+                            p.allowed_tools.retain(|tool| tool != "Bash");
+                        }
+                        p.permission_mode = mode;
+                    });
+                }>
+                    {PermissionMode::all().into_iter().map(|mode| view! {
+                        <option value=mode.as_str() selected=move || policy.get().permission_mode == mode>{mode.label()}</option>
+                    }).collect::<Vec<_>>()}
+                </select>
+                {move || policy.get().permission_mode.routes_permissions().then(|| view! {
+                    <small class="form-help">"Claude asks on the board before tools outside the allowed list. Codex cannot send prompts to the board; it runs without asking, limited by the sandbox."</small>
+                })}
+            </div>
+            <div class="form-group">
+                <label>"SANDBOX"</label>
+                <select on:change=move |ev| {
+                    let level = SandboxLevel::from_key(&event_target_value(&ev));
+                    policy.update(|p| p.sandbox = level);
+                }>
+                    {SandboxLevel::all().into_iter().map(|level| view! {
+                        <option value=level.as_str() selected=move || policy.get().sandbox == level>{level.label()}</option>
+                    }).collect::<Vec<_>>()}
+                </select>
+            </div>
+            <div class="form-group">
+                <label class="checkbox-label">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || policy.get().network
+                        on:change=move |ev| {
+                            let checked = event_target_checked(&ev);
+                            policy.update(|p| p.network = checked);
+                        }
+                    />
+                    " Network access"
+                </label>
+            </div>
+            <div class="form-group">
+                <label>"ALLOWED TOOLS"</label>
+                <input
+                    type="text"
+                    placeholder="Read,Write,Edit,MultiEdit"
+                    prop:value=move || policy.get().allowed_tools.join(",")
+                    on:change=move |ev| {
+                        let tools = parse_tool_list(&event_target_value(&ev));
+                        policy.update(|p| p.allowed_tools = tools);
+                    }
+                />
+            </div>
+            <div class="form-group">
+                <label>"DISALLOWED TOOLS"</label>
+                <input
+                    type="text"
+                    placeholder="e.g. WebFetch,WebSearch"
+                    prop:value=move || policy.get().disallowed_tools.join(",")
+                    on:change=move |ev| {
+                        let tools = parse_tool_list(&event_target_value(&ev));
+                        policy.update(|p| p.disallowed_tools = tools);
+                    }
+                />
+                <small class="form-help">"Tool lists apply to Claude; Codex uses the sandbox and network settings."</small>
+            </div>
+        </div>
+    }
+}
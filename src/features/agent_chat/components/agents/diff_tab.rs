@@ -48,6 +48,7 @@ fn render_patch(patch: &str) -> Vec<leptos::prelude::AnyView> {
 pub fn DiffTab(
     #[prop(into)] task_id: String,
     #[prop(optional)] worktree_path: Option<String>,
+    #[prop(optional)] base_commit: Option<String>,
 ) -> impl IntoView {
     let (diffs, set_diffs) = signal(Vec::<DiffFile>::new());
     let (expanded, set_expanded) = signal(std::collections::HashSet::<String>::new());
@@ -118,9 +119,10 @@ pub fn DiffTab(
                 if path.trim().is_empty() { return; }
                 let set_diffs = set_diffs.clone();
                 let set_error = set_error.clone();
+                let base_commit = base_commit.clone();
                 spawn_local(async move {
                     web_sys::console::log_1(&format!("[DiffTab] loading diffs for {}", path).into());
-                    let args = serde_json::json!({ "worktreePath": path });
+                    let args = serde_json::json!({ "worktreePath": path, "baseCommit": base_commit });
                     if let Ok(js) = to_value(&args) {
                         let resp = invoke("get_worktree_diffs", js).await;
                         if !resp.is_undefined() {
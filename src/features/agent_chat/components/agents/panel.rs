@@ -1,8 +1,41 @@
 use leptos::prelude::*;
-use std::collections::HashMap;
+use leptos::task::spawn_local;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
+use wasm_bindgen::prelude::*;
 use crate::features::agent_chat::models::AgentMessage;
 
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+}
+
+/// Sends an Approve/Deny answer for a permission_request message
+fn respond_to_permission(process_id: String, message_id: String, approved: bool) {
+    spawn_local(async move {
+        let args = serde_json::json!({ "processId": process_id, "messageId": message_id, "approved": approved });
+        if let Ok(js_value) = serde_wasm_bindgen::to_value(&args) {
+            let result = invoke("respond_to_permission", js_value).await;
+            web_sys::console::log_1(&format!("respond_to_permission: {:?}", result.as_string()).into());
+        }
+    });
+}
+
+/// Files (`@path`) and images a user message was sent with, as recorded in its metadata
+fn message_context(msg: &AgentMessage) -> Option<Vec<String>> {
+    let meta = msg.metadata.as_ref()?;
+    let list = |key: &str| -> Vec<String> {
+        meta.get(key)
+            .and_then(|v| v.as_array())
+            .map(|items| items.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default()
+    };
+    let mut items: Vec<String> = list("files").into_iter().map(|f| format!("@{}", f)).collect();
+    items.extend(list("images").into_iter().map(|p| format!("🖼 {}", p.rsplit(['/', '\\']).next().unwrap_or(&p))));
+    (!items.is_empty()).then_some(items)
+}
+
 #[component]
 pub fn AgentsPanel(
     #[prop(into)] task_id: String,
@@ -33,6 +66,18 @@ pub fn AgentsPanel(
                         let pid = proc.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
                         let status = proc.get("status").and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
                         let kind = proc.get("kind").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                        let policy_summary = proc.get("policy_summary").and_then(|v| v.as_str()).map(|s| s.to_string());
+                        let attempt = proc.get("attempt").and_then(|v| v.as_u64()).unwrap_or(1);
+                        let max_attempts = proc.get("max_attempts").and_then(|v| v.as_u64()).unwrap_or(1).max(attempt);
+                        let attempt_label = (max_attempts > 1).then(|| format!("attempt {} of {}", attempt, max_attempts));
+                        let model = proc.get("model").and_then(|v| v.as_str()).map(|s| s.to_string());
+                        let effort = proc.get("reasoning_effort").and_then(|v| v.as_str()).map(|s| s.to_string());
+                        let model_label = match (model, effort) {
+                            (Some(model), Some(effort)) => Some(format!("{} · {}", model, effort)),
+                            (Some(model), None) => Some(model),
+                            (None, Some(effort)) => Some(format!("effort {}", effort)),
+                            (None, None) => None,
+                        };
                         let default_open = idx + 1 == total;
                         let short_id = pid.chars().take(8).collect::<String>();
                         let pid_for_msgs = pid.clone();
@@ -44,14 +89,21 @@ pub fn AgentsPanel(
                             <summary class="process-summary" on:click=move |_| { (loader)(pid_for_click.clone()); }>
                                 <span class="proc-kind">{kind.clone()}</span>
                                 <span class="proc-id">{short_id}</span>
+                                {policy_summary.map(|policy| view! { <span class="proc-policy" title="Agent policy">{policy}</span> })}
+                                {model_label.map(|label| view! { <span class="proc-model" title="Model">{label}</span> })}
+                                {attempt_label.map(|label| view! { <span class="proc-attempt">{label}</span> })}
                                 <span class=move || format!("proc-status {}", status)> {status.clone()} </span>
                             </summary>
                             <div class="message-list" id={format!("agent-messages-{}", pid_for_list)}>
                                 { move || {
                                     if let Some(msgs) = messages_by_process.get().get(&pid_for_msgs).cloned() {
+                                        let answered: HashSet<String> = msgs.iter()
+                                            .filter(|m| m.message_type == "permission_response")
+                                            .filter_map(|m| m.metadata.as_ref()?.get("request_message_id")?.as_str().map(|s| s.to_string()))
+                                            .collect();
                                         msgs.into_iter().map(|msg| {
                                             let icon = match msg.sender.as_str() { "user"=>"🞓","agent"=>"🟆","system"=>"🞧",_=>"" };
-                                            let message_class = format!("message {}", msg.sender);
+                                            let message_class = format!("message {} {}", msg.sender, msg.message_type);
                                             let short_code = pid_for_msgs.chars().take(6).collect::<String>();
                                             view! { <div class=message_class>
                                                 <div class="message-header">
@@ -61,6 +113,19 @@ pub fn AgentsPanel(
                                                     <span class="time">{msg.timestamp.clone()}</span>
                                                 </div>
                                                 <div class="message-content">{ view! { <div>{msg.content.clone()}</div> } }</div>
+                                                {message_context(&msg).map(|items| view! {
+                                                    <div class="message-context">
+                                                        {items.into_iter().map(|item| view! { <span class="context-item" title=item.clone()>{item.clone()}</span> }).collect::<Vec<_>>()}
+                                                    </div>
+                                                })}
+                                                {(msg.message_type == "permission_request" && !answered.contains(&msg.id)).then(|| {
+                                                    let (pid_ok, mid_ok) = (pid_for_msgs.clone(), msg.id.clone());
+                                                    let (pid_no, mid_no) = (pid_for_msgs.clone(), msg.id.clone());
+                                                    view! { <div class="permission-actions">
+                                                        <button class="btn-primary" on:click=move |_| respond_to_permission(pid_ok.clone(), mid_ok.clone(), true)>"Approve"</button>
+                                                        <button class="btn-secondary" on:click=move |_| respond_to_permission(pid_no.clone(), mid_no.clone(), false)>"Deny"</button>
+                                                    </div> }
+                                                })}
                                             </div> }.into_any()
                                         }).collect::<Vec<_>>()
                                     } else {
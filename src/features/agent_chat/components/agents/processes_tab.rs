@@ -1,4 +1,85 @@
 use leptos::prelude::*;
+use leptos::task::spawn_local;
+use wasm_bindgen::prelude::*;
+use crate::core::models::format_tokens;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+}
+
+/// Runs a process control command (pause/resume/kill); the list refreshes on
+/// the resulting agent_process_status event
+fn control_process(command: &'static str, process_id: String) {
+    spawn_local(async move {
+        let args = serde_json::json!({ "processId": process_id });
+        if let Ok(js_value) = serde_wasm_bindgen::to_value(&args) {
+            let result = invoke(command, js_value).await;
+            web_sys::console::log_1(&format!("{}: {:?}", command, result.as_string()).into());
+        }
+    });
+}
+
+fn with_limit(value: String, limit: Option<String>) -> String {
+    match limit {
+        Some(limit) => format!("{} / {}", value, limit),
+        None => value,
+    }
+}
+
+/// Running cost/token/turn/time tally of a process, with its budget limits.
+/// Cost and token limits count every run of the task or project, so those
+/// limits are shown against that total rather than the run's own figure.
+fn usage_tally(proc: &serde_json::Value) -> String {
+    let budget = proc.get("budget").filter(|b| !b.is_null());
+    let limit = |key: &str| budget.and_then(|b| b.get(key)).filter(|v| !v.is_null());
+    let spent = proc.get("budget_spent").filter(|s| !s.is_null());
+    let scope = |key: &str| budget.and_then(|b| b.get(key)).and_then(|v| v.as_str()).unwrap_or("task").to_string();
+
+    let cost = proc.get("total_cost_usd").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let usage = proc.get("usage");
+    let tokens: u64 = ["input_tokens", "output_tokens", "cached_tokens"].iter()
+        .filter_map(|k| usage.and_then(|u| u.get(*k)).and_then(|v| v.as_u64()))
+        .sum();
+    let turns = usage.and_then(|u| u.get("turns")).and_then(|v| v.as_u64()).unwrap_or(0)
+        .max(proc.get("num_turns").and_then(|v| v.as_u64()).unwrap_or(0));
+
+    let start = proc.get("start_time").and_then(|v| v.as_str()).and_then(|s| s.parse::<u64>().ok());
+    let end = proc.get("end_time").and_then(|v| v.as_str()).and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| (js_sys::Date::now() / 1000.0) as u64);
+    // Time spent paused does not count towards the minute limit
+    let paused = proc.get("paused_secs").and_then(|v| v.as_u64()).unwrap_or(0)
+        + proc.get("paused_at").and_then(|v| v.as_u64()).map(|at| end.saturating_sub(at)).unwrap_or(0);
+    let elapsed = start.map(|s| end.saturating_sub(s).saturating_sub(paused)).unwrap_or(0);
+
+    let cost_part = match (limit("max_cost_usd").and_then(|v| v.as_f64()), spent.and_then(|s| s.get("cost_usd")).and_then(|v| v.as_f64())) {
+        (Some(max), Some(total)) => format!("${:.4} ({} ${:.4} / ${:.2})", cost, scope("cost_scope"), total, max),
+        _ => format!("${:.4}", cost),
+    };
+    let tokens_part = match (limit("max_tokens").and_then(|v| v.as_u64()), spent.and_then(|s| s.get("tokens")).and_then(|v| v.as_u64())) {
+        (Some(max), Some(total)) => format!("{} tokens ({} {} / {})", format_tokens(tokens), scope("tokens_scope"), format_tokens(total), format_tokens(max)),
+        _ => format!("{} tokens", format_tokens(tokens)),
+    };
+
+    [
+        cost_part,
+        tokens_part,
+        with_limit(format!("{} turns", turns), limit("max_turns").and_then(|v| v.as_u64()).map(|v| v.to_string())),
+        with_limit(format!("{}m {}s", elapsed / 60, elapsed % 60), limit("max_minutes").and_then(|v| v.as_u64()).map(|v| format!("{}m", v))),
+    ].join(" · ")
+}
+
+/// Moves a queued process to a new queue position (1 = next to start)
+fn move_queued(process_id: String, position: u64) {
+    spawn_local(async move {
+        let args = serde_json::json!({ "processId": process_id, "position": position });
+        if let Ok(js_value) = serde_wasm_bindgen::to_value(&args) {
+            let result = invoke("move_queued_process", js_value).await;
+            web_sys::console::log_1(&format!("move_queued_process: {:?}", result.as_string()).into());
+        }
+    });
+}
 
 #[component]
 pub fn ProcessesTab(
@@ -12,6 +93,15 @@ pub fn ProcessesTab(
         .collect();
     let has_processes = !current_task_processes.is_empty();
 
+    // Ticks once a second so "last output" ages without a list refresh
+    let (now_secs, set_now_secs) = signal((js_sys::Date::now() / 1000.0) as u64);
+    if let Ok(handle) = set_interval_with_handle(
+        move || set_now_secs.set((js_sys::Date::now() / 1000.0) as u64),
+        std::time::Duration::from_secs(1),
+    ) {
+        on_cleanup(move || handle.clear());
+    }
+
     view! {
         <div class="processes-tab">
             <div class="process-list">
@@ -31,8 +121,27 @@ pub fn ProcessesTab(
                                 let status = proc.get("status").and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
                                 let msg_count = proc.get("message_count").and_then(|v| v.as_u64()).unwrap_or(0);
                                 let task_id = proc.get("task_id").and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
+                                let parent_display = proc.get("parent_process_id").and_then(|v| v.as_str())
+                                    .map(|parent| format!("↳ from {}", parent));
+                                let attempt = proc.get("attempt").and_then(|v| v.as_u64()).unwrap_or(1);
+                                let max_attempts = proc.get("max_attempts").and_then(|v| v.as_u64()).unwrap_or(1).max(attempt);
+                                let attempt_display = (max_attempts > 1).then(|| format!("attempt {} of {}", attempt, max_attempts));
+                                let policy_display = proc.get("policy_summary").and_then(|v| v.as_str())
+                                    .map(|policy| format!("Policy: {}", policy));
+                                let exit_display = proc.get("exit_reason").and_then(|v| v.as_str())
+                                    .map(|reason| format!("({})", reason));
+                                let tally = usage_tally(&proc);
                                 let json_content = serde_json::to_string_pretty(&proc).unwrap_or_else(|_| "Invalid JSON".to_string());
 
+                                let is_live = matches!(status.as_str(), "running" | "awaiting_input" | "paused" | "stalled");
+                                let last_output = proc.get("last_output_at").and_then(|v| v.as_str())
+                                    .or_else(|| proc.get("start_time").and_then(|v| v.as_str()))
+                                    .and_then(|s| s.parse::<u64>().ok())
+                                    .filter(|_| is_live);
+                                let is_paused = status == "paused";
+                                let (pid_pause, pid_kill) = (proc_id.clone(), proc_id.clone());
+                                let queue_position = proc.get("queue_position").and_then(|v| v.as_u64()).filter(|_| status == "queued");
+                                let (pid_up, pid_down, pid_cancel) = (proc_id.clone(), proc_id.clone(), proc_id.clone());
                                 let status_class = format!("process-status {}", status);
                                 let proc_id_short = format!("{}...", &proc_id[..8.min(proc_id.len())]);
                                 let task_id_display = format!("Task: {}", task_id);
@@ -44,8 +153,41 @@ pub fn ProcessesTab(
                                         <span class="process-id">{proc_id_short}</span>
                                         <span class="task-id">{task_id_display}</span>
                                         <span class=status_class>{status_display}</span>
+                                        {exit_display.map(|reason| view! { <span class="process-exit">{reason}</span> })}
+                                        {attempt_display.map(|attempt| view! { <span class="process-attempt">{attempt}</span> })}
                                         <span class="message-count">{msg_count_display}</span>
+                                        {policy_display.map(|policy| view! { <span class="process-policy">{policy}</span> })}
+                                        {parent_display.map(|parent| view! {
+                                            <span class="process-parent">{parent}</span>
+                                        })}
                                     </div>
+                                    <div class="process-tally">{tally}</div>
+                                    {last_output.map(|at| view! {
+                                        <div class="process-idle">
+                                            {move || {
+                                                let idle = now_secs.get().saturating_sub(at);
+                                                format!("Last output {}m {}s ago", idle / 60, idle % 60)
+                                            }}
+                                        </div>
+                                    })}
+                                    {queue_position.map(|position| view! {
+                                        <div class="process-controls">
+                                            <span class="queue-position">{format!("#{} in queue", position)}</span>
+                                            <button class="btn-secondary" disabled=position <= 1
+                                                on:click=move |_| move_queued(pid_up.clone(), position - 1)>"↑"</button>
+                                            <button class="btn-secondary" on:click=move |_| move_queued(pid_down.clone(), position + 1)>"↓"</button>
+                                            <button class="btn-secondary" on:click=move |_| control_process("kill_agent_process", pid_cancel.clone())>"Cancel"</button>
+                                        </div>
+                                    })}
+                                    {is_live.then(|| view! {
+                                        <div class="process-controls">
+                                            <button class="btn-secondary" on:click=move |_| {
+                                                let command = if is_paused { "resume_agent_process" } else { "pause_agent_process" };
+                                                control_process(command, pid_pause.clone());
+                                            }>{if is_paused { "Resume" } else { "Pause" }}</button>
+                                            <button class="btn-secondary" on:click=move |_| control_process("kill_agent_process", pid_kill.clone())>"Kill"</button>
+                                        </div>
+                                    })}
                                     <details>
                                         <summary>"Show JSON Details"</summary>
                                         <pre class="json-content">{json_content}</pre>
@@ -0,0 +1,192 @@
+use std::sync::Arc;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use wasm_bindgen::prelude::*;
+use serde_wasm_bindgen::to_value;
+use crate::core::models::{AheadBehind, BranchStatus, BranchUpdate, UpdateState};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+}
+
+fn counts_text(counts: &AheadBehind) -> String {
+    format!("{} ahead, {} behind", counts.ahead, counts.behind)
+}
+
+/// Reports the result of an update step. Returns the new base commit once the
+/// branch is updated so the task can record it.
+fn report_update(result: JsValue, base_branch: &str, notice: RwSignal<Option<String>>) -> Option<String> {
+    let update = match serde_wasm_bindgen::from_value::<BranchUpdate>(result.clone()) {
+        Ok(update) => update,
+        Err(_) => {
+            let error = serde_wasm_bindgen::from_value::<String>(result).unwrap_or_else(|_| "unexpected response".to_string());
+            notice.set(Some(format!("✗ {}", error)));
+            return None;
+        }
+    };
+    match update.state {
+        UpdateState::Updated => {
+            web_sys::window().and_then(|w| w.alert_with_message(&format!(
+                "✓ Task branch updated from '{}'\n\nBefore: {}\nAfter: {}",
+                base_branch, counts_text(&update.before), counts_text(&update.after)
+            )).ok());
+            update.base_commit
+        }
+        UpdateState::UpToDate => {
+            notice.set(Some(format!("Already up to date with '{}'", base_branch)));
+            None
+        }
+        UpdateState::Paused => {
+            notice.set(Some("Paused on conflicts; resolve them in the worktree, then continue".to_string()));
+            None
+        }
+        UpdateState::Aborted => {
+            notice.set(Some(format!("Conflicts in {}; the update was aborted", update.conflicts.join(", "))));
+            None
+        }
+    }
+}
+
+/// Updates a task branch from its base branch (rebase or merge) and shows
+/// how far ahead/behind it is. An update paused on conflicts can be continued
+/// once they are resolved in the worktree, or aborted.
+#[component]
+pub fn BranchUpdater(
+    task_id: String,
+    worktree_path: String,
+    base_branch: String,
+    on_update_base_commit: Arc<dyn Fn(String, String) + Send + Sync + 'static>,
+) -> impl IntoView {
+    let status = RwSignal::new(Option::<BranchStatus>::None);
+    let strategy = RwSignal::new("rebase".to_string());
+    let abort_on_conflict = RwSignal::new(false);
+    let busy = RwSignal::new(false);
+    let notice = RwSignal::new(Option::<String>::None);
+
+    let load_status = {
+        let task_id = task_id.clone();
+        move || {
+            let args = serde_json::json!({ "taskId": task_id });
+            spawn_local(async move {
+                if let Ok(js_value) = to_value(&args) {
+                    status.set(serde_wasm_bindgen::from_value::<BranchStatus>(invoke("get_task_branch_status", js_value).await).ok());
+                }
+            });
+        }
+    };
+    load_status();
+
+    // Runs an update step and records the new base commit when it completes
+    let run_step = {
+        let task_id = task_id.clone();
+        let base_branch = base_branch.clone();
+        let load_status = load_status.clone();
+        move |command: &'static str, args: serde_json::Value| {
+            let task_id = task_id.clone();
+            let base_branch = base_branch.clone();
+            let load_status = load_status.clone();
+            let on_update_base_commit = on_update_base_commit.clone();
+            busy.set(true);
+            notice.set(None);
+            spawn_local(async move {
+                if let Ok(js_value) = to_value(&args) {
+                    let result = invoke(command, js_value).await;
+                    busy.set(false);
+                    match report_update(result, &base_branch, notice) {
+                        Some(base_commit) => on_update_base_commit(task_id, base_commit),
+                        None => load_status(),
+                    }
+                }
+            });
+        }
+    };
+
+    let start_update = {
+        let task_id = task_id.clone();
+        let base_branch = base_branch.clone();
+        let run_step = run_step.clone();
+        move |_| {
+            let how = if strategy.get_untracked() == "merge" { "merging it into the task branch" } else { "rebasing the task branch onto it" };
+            let confirm_msg = format!("Update task/{} from '{}' by {}?", task_id, base_branch, how);
+            if !web_sys::window()
+                .and_then(|w| w.confirm_with_message(&confirm_msg).ok())
+                .unwrap_or(false) {
+                return;
+            }
+            run_step("update_task_branch", serde_json::json!({
+                "taskId": task_id,
+                "strategy": strategy.get_untracked(),
+                "abortOnConflict": abort_on_conflict.get_untracked(),
+            }));
+        }
+    };
+
+    let continue_update = {
+        let task_id = task_id.clone();
+        move |_| run_step("continue_task_branch_update", serde_json::json!({ "taskId": task_id }))
+    };
+
+    let abort_update = move |_| {
+        let args = serde_json::json!({ "worktreePath": worktree_path });
+        let load_status = load_status.clone();
+        spawn_local(async move {
+            if let Ok(js_value) = to_value(&args) {
+                // A string back is the error; nothing comes back on success
+                if let Ok(error) = serde_wasm_bindgen::from_value::<String>(invoke("abort_worktree_merge", js_value).await) {
+                    notice.set(Some(format!("✗ {}", error)));
+                } else {
+                    notice.set(Some("Update aborted".to_string()));
+                }
+            }
+            load_status();
+        });
+    };
+
+    let paused = move || status.with(|s| s.as_ref().is_some_and(|s| s.in_progress.is_some()));
+    let base_for_title = base_branch.clone();
+
+    view! {
+        <button
+            class="action-btn rebase-btn"
+            title=format!("Update from {}", base_branch)
+            disabled=move || busy.get() || paused()
+            on:click=start_update
+        >
+            "🡿" {/* Alternative: 🞴 */}
+        </button>
+        <select
+            class="update-strategy"
+            title="How to bring in the base branch"
+            on:change=move |ev| strategy.set(event_target_value(&ev))
+        >
+            <option value="rebase" selected=move || strategy.get() == "rebase">"Rebase"</option>
+            <option value="merge" selected=move || strategy.get() == "merge">"Merge"</option>
+        </select>
+        <label class="update-abort" title="Roll the update back instead of pausing when it hits conflicts">
+            <input
+                type="checkbox"
+                prop:checked=move || abort_on_conflict.get()
+                on:change=move |ev| abort_on_conflict.set(event_target_checked(&ev))
+            />
+            "Abort on conflict"
+        </label>
+        {move || status.get().map(|s| view! {
+            <span class="branch-counts" title=format!("{} compared to {}", counts_text(&s.counts), base_for_title)>
+                {format!("↑{} ↓{}", s.counts.ahead, s.counts.behind)}
+            </span>
+        })}
+        {move || status.get().and_then(|s| s.in_progress.clone().map(|kind| (kind, s.conflicts))).map(|(kind, conflicts)| view! {
+            <div class="branch-update-paused">
+                <div>{format!("{} paused on conflicts in:", if kind == "merge" { "Merge" } else { "Rebase" })}</div>
+                <ul>
+                    {conflicts.into_iter().map(|path| view! { <li>{path}</li> }).collect_view()}
+                </ul>
+                <button disabled=move || busy.get() on:click=continue_update.clone()>"Continue"</button>
+                <button disabled=move || busy.get() on:click=abort_update.clone()>"Abort"</button>
+            </div>
+        })}
+        {move || notice.get().map(|text| view! { <div class="branch-update-notice">{text}</div> })}
+    }
+}
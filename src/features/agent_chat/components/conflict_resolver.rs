@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use leptos::prelude::*;
+use leptos::html::Dialog;
+use leptos::task::spawn_local;
+use wasm_bindgen::prelude::*;
+use serde_wasm_bindgen::to_value;
+use crate::core::models::{ConflictFile, ConflictResolution, MergeOutcome, MergeStrategy, TaskStatus};
+use super::MergeDialog;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+}
+
+/// A `<<<<<<<` … `>>>>>>>` block of a conflicted file: its byte range and both sides
+struct Hunk {
+    start: usize,
+    end: usize,
+    ours: String,
+    theirs: String,
+}
+
+fn conflict_hunks(text: &str) -> Vec<Hunk> {
+    let mut hunks = Vec::new();
+    let mut offset = 0;
+    // Start of the open block, our side, and their side once `=======` is passed
+    let mut open: Option<(usize, String, Option<String>)> = None;
+    let mut in_ancestor = false;
+    for line in text.split_inclusive('\n') {
+        let marker = line.trim_end_matches(['\r', '\n']);
+        if marker.starts_with("<<<<<<<") {
+            open = Some((offset, String::new(), None));
+            in_ancestor = false;
+        } else if let Some((start, ours, theirs)) = open.as_mut() {
+            if marker.starts_with(">>>>>>>") && theirs.is_some() {
+                let hunk = Hunk { start: *start, end: offset + line.len(), ours: ours.clone(), theirs: theirs.take().unwrap_or_default() };
+                hunks.push(hunk);
+                open = None;
+            } else if marker == "=======" {
+                *theirs = Some(String::new());
+                in_ancestor = false;
+            } else if marker.starts_with("|||||||") {
+                in_ancestor = true;
+            } else if let Some(theirs) = theirs {
+                theirs.push_str(line);
+            } else if !in_ancestor {
+                ours.push_str(line);
+            }
+        }
+        offset += line.len();
+    }
+    hunks
+}
+
+#[derive(Clone, Copy)]
+enum Pick {
+    Ours,
+    Theirs,
+    Both,
+}
+
+/// Replaces the `index`-th conflict block of `text` with the picked side
+fn pick_hunk(text: &str, index: usize, pick: Pick) -> String {
+    let Some(hunk) = conflict_hunks(text).into_iter().nth(index) else {
+        return text.to_string();
+    };
+    let replacement = match pick {
+        Pick::Ours => hunk.ours,
+        Pick::Theirs => hunk.theirs,
+        Pick::Both => format!("{}{}", hunk.ours, hunk.theirs),
+    };
+    format!("{}{}{}", &text[..hunk.start], replacement, &text[hunk.end..])
+}
+
+/// A path counts as resolved once it is deleted or its text has no conflict blocks left
+fn is_resolved(draft: Option<&Option<String>>) -> bool {
+    match draft {
+        Some(Some(text)) => conflict_hunks(text).is_empty(),
+        Some(None) => true,
+        None => false,
+    }
+}
+
+/// Drafts start from the file with conflict markers; delete/modify and binary
+/// conflicts have no text to start from and wait for a side to be picked
+fn initial_drafts(files: &[ConflictFile]) -> HashMap<String, Option<String>> {
+    files.iter()
+        .filter_map(|f| f.merged.clone().map(|merged| (f.path.clone(), Some(merged))))
+        .collect()
+}
+
+/// Conflicts of a task's merge into its base branch. Each hunk can take the
+/// base branch's side, the task's side or both, the text can be edited by
+/// hand, or the whole set handed to the task's agent. Completing merges with
+/// the resolved contents; aborting moves the task back to review.
+#[component]
+pub fn ConflictResolver(
+    task_id: String,
+    worktree_path: String,
+    base_branch: String,
+    project_path: Option<String>,
+    process_id: ReadSignal<Option<String>>,
+    set_process_id: WriteSignal<Option<String>>,
+    on_update_status: Arc<dyn Fn(String, TaskStatus) + Send + Sync + 'static>,
+) -> impl IntoView {
+    let files = RwSignal::new(Vec::<ConflictFile>::new());
+    let drafts = RwSignal::new(HashMap::<String, Option<String>>::new());
+    let notice = RwSignal::new(Option::<String>::None);
+    let busy = RwSignal::new(false);
+    let loaded = RwSignal::new(false);
+
+    let merge_args = serde_json::json!({
+        "worktreePath": worktree_path,
+        "baseBranch": base_branch,
+        "projectPath": project_path,
+    });
+
+    let load_conflicts = {
+        let args = merge_args.clone();
+        move || {
+            let args = args.clone();
+            spawn_local(async move {
+                if let Ok(js_value) = to_value(&args) {
+                    let result = invoke("get_merge_conflicts", js_value).await;
+                    match serde_wasm_bindgen::from_value::<Vec<ConflictFile>>(result.clone()) {
+                        Ok(found) => {
+                            drafts.set(initial_drafts(&found));
+                            files.set(found);
+                        }
+                        Err(_) => {
+                            let error = serde_wasm_bindgen::from_value::<String>(result).unwrap_or_default();
+                            notice.set(Some(format!("Failed to load conflicts: {}", error)));
+                        }
+                    }
+                    loaded.set(true);
+                }
+            });
+        }
+    };
+    load_conflicts();
+
+    // Completing asks for the merge strategy (and squash message) first
+    let merge_dialog_ref = NodeRef::<Dialog>::new();
+    let merge_dialog_open_count = RwSignal::new(0u32);
+    let task_id_for_dialog = task_id.clone();
+    let base_branch_for_dialog = base_branch.clone();
+    let open_merge_dialog = move |_| {
+        merge_dialog_open_count.update(|n| *n += 1);
+        if let Some(dialog) = merge_dialog_ref.get() {
+            let _ = dialog.show_modal();
+        }
+    };
+
+    let complete_merge = Callback::new({
+        let args = merge_args.clone();
+        let task_id = task_id.clone();
+        let on_update_status = on_update_status.clone();
+        move |(strategy, message): (MergeStrategy, Option<String>)| {
+            let resolutions: Vec<ConflictResolution> = files.get_untracked().iter()
+                .filter_map(|f| {
+                    drafts.with_untracked(|d| d.get(&f.path).cloned())
+                        .map(|content| ConflictResolution { path: f.path.clone(), content })
+                })
+                .collect();
+            let mut args = args.clone();
+            args["resolutions"] = serde_json::to_value(&resolutions).unwrap_or_default();
+            args["strategy"] = serde_json::to_value(strategy).unwrap_or_default();
+            args["message"] = serde_json::to_value(message).unwrap_or_default();
+            let task_id = task_id.clone();
+            let on_update_status = on_update_status.clone();
+            busy.set(true);
+            spawn_local(async move {
+                if let Ok(js_value) = to_value(&args) {
+                    let result = invoke("merge_worktree_to_base", js_value).await;
+                    match serde_wasm_bindgen::from_value::<MergeOutcome>(result.clone()) {
+                        Ok(MergeOutcome::Merged { message }) | Ok(MergeOutcome::UpToDate { message }) => {
+                            web_sys::window()
+                                .and_then(|w| w.alert_with_message(&format!("✓ Merge successful!\n\n{}", message)).ok());
+                            on_update_status(task_id, TaskStatus::Done);
+                        }
+                        Ok(MergeOutcome::Conflicted { files: current }) => {
+                            // The branches moved since the conflicts were loaded
+                            notice.set(Some(format!("The branches changed; {} file(s) conflict now", current.len())));
+                            drafts.set(initial_drafts(&current));
+                            files.set(current);
+                        }
+                        Err(_) => {
+                            let error = serde_wasm_bindgen::from_value::<String>(result).unwrap_or_else(|_| "unexpected response".to_string());
+                            notice.set(Some(format!("✗ Merge failed: {}", error)));
+                        }
+                    }
+                }
+                busy.set(false);
+            });
+        }
+    });
+
+    let resolve_with_agent = {
+        let worktree_path = worktree_path.clone();
+        let base_branch = base_branch.clone();
+        move |_| {
+            let Some(pid) = process_id.get_untracked() else { return; };
+            let args = serde_json::json!({
+                "processId": pid,
+                "worktreePath": worktree_path,
+                "baseBranch": base_branch,
+                "paths": files.get_untracked().iter().map(|f| f.path.clone()).collect::<Vec<_>>(),
+            });
+            busy.set(true);
+            spawn_local(async move {
+                if let Ok(js_value) = to_value(&args) {
+                    if let Ok(new_pid) = serde_wasm_bindgen::from_value::<String>(invoke("resolve_conflicts_with_agent", js_value).await) {
+                        set_process_id.set(Some(new_pid));
+                        notice.set(Some("Asked the agent to resolve the conflicts; refresh once it has committed the merge".to_string()));
+                    }
+                }
+                busy.set(false);
+            });
+        }
+    };
+
+    let abort_merge = {
+        let worktree_path = worktree_path.clone();
+        move |_| {
+            let args = serde_json::json!({ "worktreePath": worktree_path });
+            let task_id = task_id.clone();
+            let on_update_status = on_update_status.clone();
+            spawn_local(async move {
+                if let Ok(js_value) = to_value(&args) {
+                    // A string back is the error; nothing comes back on success
+                    if let Ok(error) = serde_wasm_bindgen::from_value::<String>(invoke("abort_worktree_merge", js_value).await) {
+                        notice.set(Some(format!("Failed to abort the merge in the worktree: {}", error)));
+                        return;
+                    }
+                }
+                on_update_status(task_id, TaskStatus::InReview);
+            });
+        }
+    };
+
+    let all_resolved = move || {
+        let files = files.get();
+        drafts.with(|d| files.iter().all(|f| is_resolved(d.get(&f.path))))
+    };
+
+    let base_label = base_branch.clone();
+    view! {
+        <div class="conflict-resolver">
+            <div class="conflict-summary">
+                {move || if !loaded.get() {
+                    "Checking for conflicts...".to_string()
+                } else if files.get().is_empty() {
+                    format!("No conflicts left with '{}'; the merge can be completed", base_label)
+                } else {
+                    format!("{} file(s) conflict with '{}'", files.get().len(), base_label)
+                }}
+            </div>
+            {move || notice.get().map(|text| view! { <div class="conflict-notice">{text}</div> })}
+
+            {move || {
+                let base_branch = base_branch.clone();
+                files.get().into_iter().map(|file| {
+                    let path = file.path.clone();
+                    let path_for_state = path.clone();
+                    let path_for_hunks = path.clone();
+                    let path_for_editor = path.clone();
+                    let path_for_input = path.clone();
+                    let path_for_choice = path.clone();
+                    let base_branch = base_branch.clone();
+                    view! {
+                        <div class="conflict-file">
+                            <div class="conflict-file-header">
+                                <span class="conflict-path">{path}</span>
+                                <span class="conflict-state">
+                                    {move || drafts.with(|d| match d.get(&path_for_state) {
+                                        Some(None) => "deleted".to_string(),
+                                        Some(Some(text)) => match conflict_hunks(text).len() {
+                                            0 => "resolved".to_string(),
+                                            n => format!("{} conflict(s) left", n),
+                                        },
+                                        None => "unresolved".to_string(),
+                                    })}
+                                </span>
+                            </div>
+                            {if file.merged.is_some() {
+                                view! {
+                                    {move || {
+                                        let text = drafts.with(|d| d.get(&path_for_hunks).cloned().flatten()).unwrap_or_default();
+                                        let path = path_for_hunks.clone();
+                                        let base_branch = base_branch.clone();
+                                        conflict_hunks(&text).into_iter().enumerate().map(|(index, hunk)| {
+                                            let pick = {
+                                                let path = path.clone();
+                                                move |side: Pick| drafts.update(|d| {
+                                                    if let Some(Some(text)) = d.get_mut(&path) {
+                                                        *text = pick_hunk(text, index, side);
+                                                    }
+                                                })
+                                            };
+                                            let pick_ours = pick.clone();
+                                            let pick_theirs = pick.clone();
+                                            view! {
+                                                <div class="conflict-hunk">
+                                                    <div class="hunk-side hunk-ours">
+                                                        <div class="hunk-label">{base_branch.clone()}</div>
+                                                        <pre>{hunk.ours}</pre>
+                                                    </div>
+                                                    <div class="hunk-side hunk-theirs">
+                                                        <div class="hunk-label">"task"</div>
+                                                        <pre>{hunk.theirs}</pre>
+                                                    </div>
+                                                    <div class="hunk-actions">
+                                                        <button on:click=move |_| pick_ours(Pick::Ours)>{format!("Use {}", base_branch)}</button>
+                                                        <button on:click=move |_| pick_theirs(Pick::Theirs)>"Use task"</button>
+                                                        <button on:click=move |_| pick(Pick::Both)>"Use both"</button>
+                                                    </div>
+                                                </div>
+                                            }
+                                        }).collect_view()
+                                    }}
+                                    <textarea
+                                        class="conflict-editor"
+                                        rows="10"
+                                        prop:value=move || drafts.with(|d| d.get(&path_for_editor).cloned().flatten()).unwrap_or_default()
+                                        on:input=move |ev| {
+                                            let value = event_target_value(&ev);
+                                            drafts.update(|d| { d.insert(path_for_input.clone(), Some(value)); });
+                                        }
+                                    ></textarea>
+                                }.into_any()
+                            } else if file.binary {
+                                view! {
+                                    <div class="conflict-binary">"Binary file; keep one side with the agent or by hand in the worktree"</div>
+                                }.into_any()
+                            } else {
+                                // One side deleted the file: keep the other side's version or delete it
+                                let choose = move |content: Option<String>| {
+                                    let path = path_for_choice.clone();
+                                    move |_| drafts.update(|d| { d.insert(path.clone(), content.clone()); })
+                                };
+                                let ours_label = if file.ours.is_some() { format!("Keep {}'s version", base_branch) } else { format!("Delete (as on {})", base_branch) };
+                                let theirs_label = if file.theirs.is_some() { "Keep the task's version" } else { "Delete (as on the task)" };
+                                view! {
+                                    <div class="hunk-actions">
+                                        <button on:click=choose(file.ours.clone())>{ours_label}</button>
+                                        <button on:click=choose(file.theirs.clone())>{theirs_label}</button>
+                                    </div>
+                                }.into_any()
+                            }}
+                        </div>
+                    }
+                }).collect_view()
+            }}
+
+            <div class="conflict-actions">
+                <button
+                    class="conflict-complete-btn"
+                    disabled=move || busy.get() || !loaded.get() || !all_resolved()
+                    on:click=open_merge_dialog
+                >"Complete merge"</button>
+                <button
+                    class="conflict-agent-btn"
+                    title="Send a \"resolve these conflicts\" prompt to the task's agent"
+                    disabled=move || busy.get() || process_id.get().is_none() || files.get().is_empty()
+                    on:click=resolve_with_agent
+                >"Resolve with agent"</button>
+                <button on:click=move |_| load_conflicts()>"Refresh"</button>
+                <button class="conflict-abort-btn" on:click=abort_merge>"Abort merge"</button>
+            </div>
+            <MergeDialog
+                dialog_ref=merge_dialog_ref
+                task_id=task_id_for_dialog
+                base_branch=base_branch_for_dialog
+                open_count=merge_dialog_open_count
+                on_confirm=complete_merge
+                resolving=true
+            />
+        </div>
+    }
+}
@@ -0,0 +1,100 @@
+use leptos::prelude::*;
+use leptos::html::Dialog;
+use leptos::task::spawn_local;
+use serde_wasm_bindgen::to_value;
+use wasm_bindgen::prelude::*;
+use crate::core::models::{MergeDefaults, MergeStrategy};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+}
+
+/// Asks how to merge a task before `merge_worktree_to_base` runs: the strategy
+/// (the project's default unless changed here) and, for squash merges, the
+/// commit message. Bump `open_count` when opening so the defaults are re-read.
+/// With `resolving` set only strategies that take conflict resolutions are offered.
+#[component]
+pub fn MergeDialog(
+    dialog_ref: NodeRef<Dialog>,
+    #[prop(into)] task_id: String,
+    #[prop(into)] base_branch: String,
+    open_count: RwSignal<u32>,
+    #[prop(into)] on_confirm: Callback<(MergeStrategy, Option<String>)>,
+    #[prop(optional)] resolving: bool,
+) -> impl IntoView {
+    let strategy = RwSignal::new(MergeStrategy::default());
+    let squash_message = RwSignal::new(String::new());
+
+    Effect::new(move |_| {
+        if open_count.get() == 0 {
+            return;
+        }
+        let args = serde_json::json!({ "taskId": task_id });
+        spawn_local(async move {
+            if let Ok(js_value) = to_value(&args) {
+                let defaults = serde_wasm_bindgen::from_value::<MergeDefaults>(invoke("get_merge_defaults", js_value).await)
+                    .unwrap_or_default();
+                strategy.set(if resolving && !defaults.strategy.takes_resolutions() {
+                    MergeStrategy::MergeCommit
+                } else {
+                    defaults.strategy
+                });
+                squash_message.set(defaults.squash_message);
+            }
+        });
+    });
+
+    let close_dialog = move || {
+        if let Some(dialog) = dialog_ref.get() {
+            dialog.close();
+        }
+    };
+
+    let confirm = move |_| {
+        let chosen = strategy.get_untracked();
+        let message = (chosen == MergeStrategy::Squash).then(|| squash_message.get_untracked());
+        if message.as_deref().is_some_and(|m| m.trim().is_empty()) {
+            web_sys::window()
+                .and_then(|w| w.alert_with_message("Squash commit message cannot be empty").ok());
+            return;
+        }
+        close_dialog();
+        on_confirm.run((chosen, message));
+    };
+
+    view! {
+        <dialog node_ref=dialog_ref class="merge-dialog">
+            <div class="merge-dialog-content">
+                <div class="commit-dialog-header">
+                    <h3>{format!("MERGE TO {}", base_branch.to_uppercase())}</h3>
+                    <button type="button" class="modal-close" on:click=move |_| close_dialog()>"×"</button>
+                </div>
+                <div class="form-group">
+                    <label>"Strategy"</label>
+                    <select on:change=move |ev| strategy.set(MergeStrategy::from_value(&event_target_value(&ev)))>
+                        {MergeStrategy::all().into_iter().filter(|s| !resolving || s.takes_resolutions()).map(|s| view! {
+                            <option value=s.as_value() selected=move || strategy.get() == s>{s.label()}</option>
+                        }).collect_view()}
+                    </select>
+                    <small class="form-help">{move || strategy.get().description()}</small>
+                </div>
+                <Show when=move || strategy.get() == MergeStrategy::Squash>
+                    <div class="form-group">
+                        <label>"Squash commit message"</label>
+                        <textarea
+                            rows="10"
+                            prop:value=move || squash_message.get()
+                            on:input=move |ev| squash_message.set(event_target_value(&ev))
+                        ></textarea>
+                    </div>
+                </Show>
+                <div class="modal-actions">
+                    <button type="button" class="btn-secondary" on:click=move |_| close_dialog()>"Cancel"</button>
+                    <button type="button" class="btn-primary" on:click=confirm>"Merge"</button>
+                </div>
+            </div>
+        </dialog>
+    }
+}
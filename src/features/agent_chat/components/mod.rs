@@ -3,9 +3,17 @@ pub mod agents;
 pub mod sidebar_header;
 pub mod task_details_section;
 pub mod commit_dialog;
+pub mod reply_context;
+pub mod conflict_resolver;
+pub mod branch_update;
+pub mod merge_dialog;
 
 pub use task_sidebar::*;
 pub use agents::*;
 pub use sidebar_header::*;
 pub use task_details_section::*;
-pub use commit_dialog::*;
\ No newline at end of file
+pub use commit_dialog::*;
+pub use reply_context::*;
+pub use conflict_resolver::*;
+pub use branch_update::*;
+pub use merge_dialog::*;
\ No newline at end of file
@@ -0,0 +1,149 @@
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use serde_wasm_bindgen::to_value;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+}
+
+/// The `@` word being typed at the end of `text`: its byte offset and query
+fn active_mention(text: &str) -> Option<(usize, String)> {
+    if text.ends_with(char::is_whitespace) {
+        return None;
+    }
+    let start = text.rfind(char::is_whitespace).map(|i| i + 1).unwrap_or(0);
+    text[start..].strip_prefix('@').map(|query| (start, query.to_string()))
+}
+
+/// Suggests worktree files while an `@path` word is typed into the reply box;
+/// picking one completes the word
+#[component]
+pub fn FileMentions(
+    worktree_path: Option<String>,
+    message_input: ReadSignal<String>,
+    set_message_input: WriteSignal<String>,
+) -> impl IntoView {
+    let (suggestions, set_suggestions) = signal(Vec::<String>::new());
+
+    Effect::new(move |_| {
+        let text = message_input.get();
+        let (Some(worktree_path), Some((_, query))) = (worktree_path.clone(), active_mention(&text)) else {
+            set_suggestions.set(Vec::new());
+            return;
+        };
+        spawn_local(async move {
+            let args = serde_json::json!({ "worktreePath": worktree_path, "query": query });
+            if let Ok(js_value) = to_value(&args) {
+                let result = invoke("list_worktree_files", js_value).await;
+                // Drop answers for a word that has been typed over since
+                if active_mention(&message_input.get_untracked()).map(|(_, q)| q) != Some(query) {
+                    return;
+                }
+                set_suggestions.set(serde_wasm_bindgen::from_value::<Vec<String>>(result).unwrap_or_default());
+            }
+        });
+    });
+
+    view! {
+        <Show when=move || !suggestions.get().is_empty()>
+            <div class="mention-suggestions">
+                {move || suggestions.get().into_iter().take(8).map(|path| {
+                    let label = path.clone();
+                    view! {
+                        <button type="button" class="mention-suggestion" on:click=move |_| {
+                            let text = message_input.get_untracked();
+                            if let Some((start, _)) = active_mention(&text) {
+                                set_message_input.set(format!("{}@{} ", &text[..start], path));
+                            }
+                            set_suggestions.set(Vec::new());
+                        }>{label}</button>
+                    }
+                }).collect::<Vec<_>>()}
+            </div>
+        </Show>
+    }
+}
+
+/// Image files among the items of a paste or drop
+pub fn image_files(data: Option<web_sys::DataTransfer>) -> Vec<web_sys::File> {
+    let Some(files) = data.and_then(|d| d.files()) else { return Vec::new(); };
+    (0..files.length())
+        .filter_map(|i| files.get(i))
+        .filter(|f| f.type_().starts_with("image/"))
+        .collect()
+}
+
+/// Saves images into the task's attachments directory and adds their paths to `images`
+pub fn attach_images(task_id: String, files: Vec<web_sys::File>, images: RwSignal<Vec<String>>) {
+    for file in files {
+        let task_id = task_id.clone();
+        spawn_local(async move {
+            let buffer = match wasm_bindgen_futures::JsFuture::from(file.array_buffer()).await {
+                Ok(buffer) => buffer,
+                Err(e) => {
+                    web_sys::console::error_1(&format!("Failed to read pasted image: {:?}", e).into());
+                    return;
+                }
+            };
+            let data = js_sys::Uint8Array::new(&buffer).to_vec();
+            // Pasted screenshots have no useful name, only a type
+            let file_name = match file.name() {
+                name if name.contains('.') => name,
+                _ => format!("pasted.{}", file.type_().trim_start_matches("image/")),
+            };
+            let args = serde_json::json!({ "taskId": task_id, "fileName": file_name, "data": data });
+            if let Ok(js_value) = to_value(&args) {
+                let result = invoke("save_task_attachment", js_value).await;
+                // The LAN bridge returns errors as plain strings; a saved image is an absolute path
+                match result.as_string() {
+                    Some(path) if path.starts_with('/') || path.get(1..3) == Some(":\\") => {
+                        images.update(|list| list.push(path));
+                    }
+                    other => web_sys::console::error_1(&format!("Failed to attach image: {:?}", other).into()),
+                }
+            }
+        });
+    }
+}
+
+/// Images waiting to be sent with the next reply
+#[component]
+pub fn AttachedImages(images: RwSignal<Vec<String>>) -> impl IntoView {
+    view! {
+        <Show when=move || !images.get().is_empty()>
+            <div class="attached-images">
+                {move || images.get().into_iter().enumerate().map(|(index, path)| {
+                    let name = path.rsplit(['/', '\\']).next().unwrap_or(&path).to_string();
+                    view! {
+                        <span class="attached-image" title=path>
+                            {format!("🖼 {}", name)}
+                            <button type="button" title="Remove" on:click=move |_| images.update(|list| { if index < list.len() { list.remove(index); } })>"×"</button>
+                        </span>
+                    }
+                }).collect::<Vec<_>>()}
+            </div>
+        </Show>
+    }
+}
+
+/// Pasted images are taken from the clipboard instead of being pasted as text
+pub fn on_paste_images(ev: web_sys::Event, task_id: String, images: RwSignal<Vec<String>>) {
+    let Ok(ev) = ev.dyn_into::<web_sys::ClipboardEvent>() else { return; };
+    let files = image_files(ev.clipboard_data());
+    if !files.is_empty() {
+        ev.prevent_default();
+        attach_images(task_id, files, images);
+    }
+}
+
+pub fn on_drop_images(ev: web_sys::DragEvent, task_id: String, images: RwSignal<Vec<String>>) {
+    let files = image_files(ev.data_transfer());
+    if !files.is_empty() {
+        ev.prevent_default();
+        attach_images(task_id, files, images);
+    }
+}
@@ -1,5 +1,7 @@
 use leptos::prelude::*;
-use crate::core::models::{Task, TaskStatus, AgentProfile};
+use leptos::task::spawn_local;
+use crate::core::models::{Task, TaskStatus, AgentProfile, AgentProfileInfo};
+use crate::core::services::load_agent_profiles;
 
 #[component]
 pub fn TaskDetailsSection(
@@ -13,6 +15,12 @@ pub fn TaskDetailsSection(
     let task_status = task.status.clone();
     let task_id = task.id.clone();
 
+    // Agent profiles (built-in + custom from settings) for the profile picker
+    let (agent_profiles, set_agent_profiles) = signal(AgentProfileInfo::builtin());
+    spawn_local(async move {
+        set_agent_profiles.set(load_agent_profiles().await);
+    });
+
     // State for showing/hiding full description
     let (show_full_description, set_show_full_description) = signal(false);
 
@@ -64,14 +72,22 @@ pub fn TaskDetailsSection(
                                     <label>"Profile:"</label>
                                     <select on:change=move |ev| {
                                         let value = event_target_value(&ev);
-                                        let profile = match value.as_str() {
-                                            "codex" => AgentProfile::Codex,
-                                            _ => AgentProfile::ClaudeCode,
-                                        };
-                                        set_selected_profile.set(profile);
+                                        set_selected_profile.set(AgentProfile::from_profile_str(&value));
                                     }>
-                                        <option value="claude" selected=matches!(selected_profile.get(), AgentProfile::ClaudeCode)>"Claude Code"</option>
-                                        <option value="codex" selected=matches!(selected_profile.get(), AgentProfile::Codex)>"Codex"</option>
+                                        {move || {
+                                            let selected = selected_profile.get().as_profile_str();
+                                            let mut profiles = agent_profiles.get();
+                                            // A profile removed from the settings still shows as the task's choice
+                                            if !profiles.iter().any(|p| p.name == selected) {
+                                                profiles.push(AgentProfileInfo { name: selected.clone(), display_name: selected.clone(), custom: true });
+                                            }
+                                            profiles.into_iter().map(|p| {
+                                                let is_selected = p.name == selected;
+                                                view! {
+                                                    <option value=p.name.clone() selected=is_selected>{p.display_name.clone()}</option>
+                                                }
+                                            }).collect_view()
+                                        }}
                                     </select>
                                 </div>
                                 <div class="config-row">
@@ -1,7 +1,8 @@
 use leptos::prelude::*;
 use leptos::html::Dialog;
 use leptos::task::spawn_local;
-use crate::core::models::{Task, TaskStatus, AgentProfile};
+use crate::core::models::{Task, TaskStatus, AgentProfile, AgentProfileInfo, ModelChoice, MergeOutcome, MergeStrategy};
+use crate::core::ui::ModelPicker;
 use std::sync::Arc;
 use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
@@ -14,6 +15,7 @@ pub use crate::features::agent_chat::models::AgentMessage;
 
 // Import CommitDialog
 use crate::features::agent_chat::components::CommitDialog;
+use crate::features::agent_chat::components::{AttachedImages, BranchUpdater, ConflictResolver, FileMentions, MergeDialog, on_drop_images, on_paste_images};
 
 #[wasm_bindgen]
 extern "C" {
@@ -33,6 +35,7 @@ pub fn TaskSidebar(
     #[prop(into)] on_open_ide: Option<Box<dyn Fn(String) + 'static>>,
     on_update_profile: Box<dyn Fn(String, AgentProfile) + 'static>,
     on_update_base_branch: Box<dyn Fn(String, String) + 'static>,
+    #[prop(into)] on_update_base_commit: Arc<dyn Fn(String, String) + Send + Sync + 'static>,
     #[prop(into, optional)] _active_process_id: Option<RwSignal<Option<String>>>,
 ) -> impl IntoView {
     // State for showing/hiding full description
@@ -49,6 +52,14 @@ pub fn TaskSidebar(
     let (messages_by_process, set_messages_by_process) = signal(HashMap::<String, Vec<AgentMessage>>::new());
     let (message_input, set_message_input) = signal(String::new());
     let (is_sending_message, set_is_sending_message) = signal(false);
+    // Model override for replies; unset fields use the task's model
+    let reply_choice = RwSignal::new(ModelChoice::default());
+    // Images pasted or dropped into the reply box, sent with the next reply
+    let reply_images = RwSignal::new(Vec::<String>::new());
+    // Agent for the next reply when it differs from the current run's, and
+    // whether to hand off with a summary instead of replaying the transcript
+    let reply_profile = RwSignal::new(Option::<String>::None);
+    let reply_handoff = RwSignal::new(true);
 
     // Clone task data for use in closures
     let task_title = task.title.clone();
@@ -56,11 +67,18 @@ pub fn TaskSidebar(
     let task_status = task.status.clone();
     let task_id = task.id.clone();
     let _task_worktree_path = task.worktree_path.clone();
+    let task_base_commit = task.base_commit.clone();
     let worktree_available = _task_worktree_path.is_some();
 
     // Local selected profile (default from task)
     let (selected_profile, set_selected_profile) = signal(task.profile.clone());
 
+    // Agent profiles (built-in + custom from settings) for the profile picker
+    let (agent_profiles, set_agent_profiles) = signal(AgentProfileInfo::builtin());
+    spawn_local(async move {
+        set_agent_profiles.set(crate::core::services::load_agent_profiles().await);
+    });
+
     // Local selected base branch (default from task)
     let (selected_base_branch, set_selected_base_branch) = signal(task.base_branch.clone());
 
@@ -435,6 +453,7 @@ pub fn TaskSidebar(
         // Pre-clone props for listener closures to avoid moving the originals
         let on_update_status_for_listeners = on_update_status.clone();
         let task_id_for_status_for_listeners = task_id_for_events.clone();
+        let task_status_for_listeners = task.status.clone();
 
         spawn_local(async move {
             // Listen for agent message update events AND process status updates
@@ -448,6 +467,11 @@ pub fn TaskSidebar(
             let task_id_msg = task_id_for_events.clone();
             let load_msg = load_agent_messages_for_events.clone();
             let set_id_msg = set_current_process_id_for_events.clone();
+            let load_all_processes_for_msg = load_all_processes;
+            let last_list_refresh = std::rc::Rc::new(std::cell::Cell::new(0.0_f64));
+            // Text streamed so far per message id, dropped once the finished message arrives
+            let drafts = std::rc::Rc::new(std::cell::RefCell::new(HashMap::<String, String>::new()));
+            let drafts_for_msg = drafts.clone();
             let message_handler = wasm_bindgen::closure::Closure::wrap(Box::new(move |event: JsValue| {
                 web_sys::console::log_1(&"📥 Received agent_message_update event".into());
                 if let Ok(event_data) = serde_wasm_bindgen::from_value::<serde_json::Value>(event) {
@@ -464,6 +488,17 @@ pub fn TaskSidebar(
                                     // Refresh messages for this process
                                     load_msg(process_id.to_string());
                                     // Message persistence now handled inside load_agent_messages after refresh
+                                    if let Some(message_id) = payload.get("message").and_then(|m| m.get("id")).and_then(|v| v.as_str()) {
+                                        drafts_for_msg.borrow_mut().remove(message_id);
+                                    }
+                                    // Usage-bearing messages also refresh the running tally in the Processes tab;
+                                    // other output refreshes it (and "last output") at most every 5s
+                                    let message_type = payload.get("message").and_then(|m| m.get("message_type")).and_then(|v| v.as_str()).unwrap_or("");
+                                    let now = js_sys::Date::now();
+                                    if matches!(message_type, "token_count" | "result" | "budget_exceeded") || now - last_list_refresh.get() > 5000.0 {
+                                        last_list_refresh.set(now);
+                                        load_all_processes_for_msg();
+                                    }
                                 }
                             }
                         }
@@ -471,6 +506,45 @@ pub fn TaskSidebar(
                 }
             }) as Box<dyn FnMut(JsValue)>);
 
+            // Handler for streamed assistant text: shown as a draft message until the
+            // finished message (same id) replaces it on the next refresh
+            let task_id_delta = task_id_for_events.clone();
+            let set_messages_for_delta = set_messages_by_process;
+            let delta_handler = wasm_bindgen::closure::Closure::wrap(Box::new(move |event: JsValue| {
+                let Ok(event_data) = serde_wasm_bindgen::from_value::<serde_json::Value>(event) else { return; };
+                let Some(payload) = event_data.get("payload") else { return; };
+                if payload.get("task_id").and_then(|v| v.as_str()) != Some(task_id_delta.as_str()) {
+                    return;
+                }
+                let (Some(process_id), Some(message_id), Some(delta)) = (
+                    payload.get("process_id").and_then(|v| v.as_str()),
+                    payload.get("message_id").and_then(|v| v.as_str()),
+                    payload.get("delta").and_then(|v| v.as_str()),
+                ) else { return; };
+
+                let text = {
+                    let mut drafts = drafts.borrow_mut();
+                    let draft = drafts.entry(message_id.to_string()).or_default();
+                    draft.push_str(delta);
+                    draft.clone()
+                };
+                set_messages_for_delta.update(|map| {
+                    let messages = map.entry(process_id.to_string()).or_default();
+                    match messages.iter_mut().find(|m| m.id == message_id) {
+                        Some(existing) => existing.content = text,
+                        None => messages.push(AgentMessage {
+                            id: message_id.to_string(),
+                            sender: "agent".to_string(),
+                            content: text,
+                            timestamp: ((js_sys::Date::now() / 1000.0) as u64).to_string(),
+                            message_type: "streaming".to_string(),
+                            metadata: None,
+                        }),
+                    }
+                });
+                scroll_to_bottom(&format!("agent-messages-{}", process_id));
+            }) as Box<dyn FnMut(JsValue)>);
+
             // Handler for process status updates
             let task_id_status = task_id_for_events.clone();
             let load_status = load_agent_messages_for_events.clone();
@@ -479,6 +553,7 @@ pub fn TaskSidebar(
             // Clones for status-driven task transitions
             let on_update_status_cb = on_update_status_for_listeners.clone();
             let task_id_for_status_cb = task_id_for_status_for_listeners.clone();
+            let task_status_for_status_cb = task_status_for_listeners.clone();
             let status_handler = wasm_bindgen::closure::Closure::wrap(Box::new(move |event: JsValue| {
                 web_sys::console::log_1(&"📊 Received agent_process_status event".into());
                 if let Ok(event_data) = serde_wasm_bindgen::from_value::<serde_json::Value>(event) {
@@ -497,8 +572,10 @@ pub fn TaskSidebar(
                                     load_status(process_id.to_string());
                                     // CRITICAL: Also refresh process list to trigger UI re-render
                                     load_all_processes_for_status();
-                                    // When a process completes, move task to InReview
-                                    if status == "completed" || status == "complete" || status == "finished" {
+                                    // When a process completes, move task to InReview; a conflicted
+                                    // task waits for its merge to be completed or aborted
+                                    if (status == "completed" || status == "complete" || status == "finished")
+                                        && task_status_for_status_cb != TaskStatus::Conflicted {
                                         on_update_status_cb(task_id_for_status_cb.clone(), TaskStatus::InReview);
                                     }
                                 }
@@ -521,9 +598,16 @@ pub fn TaskSidebar(
                 status_handler.as_ref().unchecked_ref()
             );
 
+            let _ = listen_js.call2(
+                &JsValue::NULL,
+                &JsValue::from_str("agent_message_delta"),
+                delta_handler.as_ref().unchecked_ref()
+            );
+
             // Keep the closures alive
             message_handler.forget();
             status_handler.forget();
+            delta_handler.forget();
         });
     }
 
@@ -625,14 +709,17 @@ pub fn TaskSidebar(
                                             <label>"Profile:"</label>
                                             <select on:change=move |ev| {
                                                 let value = event_target_value(&ev);
-                                                let profile = match value.as_str() {
-                                                    "codex" => AgentProfile::Codex,
-                                                    _ => AgentProfile::ClaudeCode,
-                                                };
-                                                set_selected_profile.set(profile);
+                                                set_selected_profile.set(AgentProfile::from_profile_str(&value));
                                             }>
-                                                <option value="claude" selected=matches!(selected_profile.get(), AgentProfile::ClaudeCode)>"Claude Code"</option>
-                                                <option value="codex" selected=matches!(selected_profile.get(), AgentProfile::Codex)>"Codex"</option>
+                                                {move || {
+                                                    let selected = selected_profile.get().as_profile_str();
+                                                    agent_profiles.get().into_iter().map(|p| {
+                                                        let is_selected = p.name == selected;
+                                                        view! {
+                                                            <option value=p.name.clone() selected=is_selected>{p.display_name.clone()}</option>
+                                                        }
+                                                    }).collect_view()
+                                                }}
                                             </select>
                                         </div>
                                         <button
@@ -669,7 +756,67 @@ pub fn TaskSidebar(
                                     let base_branch_for_merge = task.base_branch.clone();
                                     let task_id_for_merge = task.id.clone();
                                     let project_path_for_merge = project_path.clone();
-                                    let on_update_status_for_merge = on_update_status.clone();
+                                    let merge_dialog_ref = NodeRef::<Dialog>::new();
+                                    let merge_dialog_open_count = RwSignal::new(0u32);
+                                    let run_merge = Callback::new({
+                                        let worktree = worktree_path.clone();
+                                        let base_br = task.base_branch.clone();
+                                        let proj_path = project_path.clone().unwrap_or_default();
+                                        let task_id = task.id.clone();
+                                        let update_status = on_update_status.clone();
+                                        let dialog = commit_dialog_ref;
+                                        move |(strategy, message): (MergeStrategy, Option<String>)| {
+                                            let worktree = worktree.clone();
+                                            let base_br = base_br.clone();
+                                            let proj_path = proj_path.clone();
+                                            let task_id = task_id.clone();
+                                            let update_status = update_status.clone();
+                                            spawn_local(async move {
+                                                let args = serde_json::json!({
+                                                    "worktreePath": worktree,
+                                                    "baseBranch": base_br,
+                                                    "projectPath": proj_path,
+                                                    "strategy": strategy,
+                                                    "message": message
+                                                });
+
+                                                if let Ok(js_value) = to_value(&args) {
+                                                    let js_result = invoke("merge_worktree_to_base", js_value).await;
+                                                    web_sys::console::log_1(&"Got merge response from Tauri".into());
+                                                    match serde_wasm_bindgen::from_value::<MergeOutcome>(js_result.clone()) {
+                                                        Ok(MergeOutcome::UpToDate { .. }) => {
+                                                            // Open commit dialog FIRST
+                                                            if let Some(dialog_elem) = dialog.get() {
+                                                                let _ = dialog_elem.show_modal();
+                                                            }
+                                                            // Then show alert
+                                                            web_sys::window()
+                                                                .and_then(|w| w.alert_with_message("⚠ No changes to merge.\n\nPlease commit your changes first, then try merging again.").ok());
+                                                        }
+                                                        Ok(MergeOutcome::Merged { message }) => {
+                                                            web_sys::console::log_1(&format!("Merge successful! Updating task {} to Done", task_id).into());
+                                                            web_sys::window()
+                                                                .and_then(|w| w.alert_with_message(&format!("✓ Merge successful!\n\n{}", message)).ok());
+                                                            update_status(task_id.clone(), TaskStatus::Done);
+                                                        }
+                                                        Ok(MergeOutcome::Conflicted { files }) => {
+                                                            // Nothing was merged; the Conflicts tab takes over until the merge is completed or aborted
+                                                            web_sys::window()
+                                                                .and_then(|w| w.alert_with_message(&format!("⚠ Merge conflicts in {} file(s).\n\nResolve them in the Conflicts tab.", files.len())).ok());
+                                                            update_status(task_id.clone(), TaskStatus::Conflicted);
+                                                        }
+                                                        Err(_) => {
+                                                            // Errors (e.g. a dirty checkout of the base branch) come back as plain strings over the LAN bridge
+                                                            let error = serde_wasm_bindgen::from_value::<String>(js_result)
+                                                                .unwrap_or_else(|_| "unexpected response format".to_string());
+                                                            web_sys::window()
+                                                                .and_then(|w| w.alert_with_message(&format!("✗ Merge failed\n\n{}", error)).ok());
+                                                        }
+                                                    }
+                                                }
+                                            });
+                                        }
+                                    });
                                     view! {
                                         <div class="worktree-actions">
                                             <button
@@ -728,29 +875,14 @@ pub fn TaskSidebar(
                                                 title={format!("Merge to {}", base_branch_for_merge)}
                                                 on:click={
                                                     let worktree = worktree_path_for_merge.clone();
-                                                    let base_br = base_branch_for_merge.clone();
-                                                    let proj_path = project_path_for_merge.clone();
-                                                    let task_id = task_id_for_merge.clone();
-                                                    let update_status = on_update_status_for_merge.clone();
+                                                    let has_project_path = project_path_for_merge.is_some();
                                                     let dialog = commit_dialog_ref.clone();
                                                     move |_| {
                                                         let worktree = worktree.clone();
-                                                        let base_br = base_br.clone();
-                                                        let task_id = task_id.clone();
-                                                        let update_status = update_status.clone();
-                                                        let proj_path = proj_path.clone();
                                                         let dialog = dialog.clone();
 
-                                                        // Show confirmation dialog
-                                                        let confirm_msg = format!("Merge task branch to '{}'?\n\nThis will merge your changes into the base branch.", base_br);
-                                                        if !web_sys::window()
-                                                            .and_then(|w| w.confirm_with_message(&confirm_msg).ok())
-                                                            .unwrap_or(false) {
-                                                            return;
-                                                        }
-
                                                         spawn_local(async move {
-                                                            if let Some(proj_path) = proj_path {
+                                                            if has_project_path {
                                                                 // First check if there are uncommitted changes
                                                                 let status_args = serde_json::json!({ "worktreePath": worktree.clone() });
                                                                 if let Ok(status_js) = to_value(&status_args) {
@@ -773,50 +905,10 @@ pub fn TaskSidebar(
                                                                     }
                                                                 }
 
-                                                                // No uncommitted changes, proceed with merge
-                                                                let args = serde_json::json!({
-                                                                    "worktreePath": worktree,
-                                                                    "baseBranch": base_br,
-                                                                    "projectPath": proj_path
-                                                                });
-
-                                                                if let Ok(js_value) = to_value(&args) {
-                                                                    match invoke("merge_worktree_to_base", js_value).await {
-                                                                        js_result if !js_result.is_undefined() => {
-                                                                            web_sys::console::log_1(&"Got merge response from Tauri".into());
-                                                                            // Try to deserialize as plain string first (success case)
-                                                                            if let Ok(success_msg) = serde_wasm_bindgen::from_value::<String>(js_result.clone()) {
-                                                                                web_sys::console::log_1(&format!("Merge success_msg: {}", success_msg).into());
-                                                                                // Check if "up to date"
-                                                                                if success_msg.contains("Already up to date") || success_msg.contains("up-to-date") || success_msg.contains("already up-to-date") {
-                                                                                            // Open commit dialog FIRST
-                                                                                            if let Some(dialog_elem) = dialog.get() {
-                                                                                                let _ = dialog_elem.show_modal();
-                                                                                            }
-                                                                                            // Then show alert
-                                                                                            web_sys::window()
-                                                                                                .and_then(|w| w.alert_with_message("⚠ No changes to merge.\n\nPlease commit your changes first, then try merging again.").ok());
-                                                                                        } else {
-                                                                                            web_sys::console::log_1(&format!("Merge successful! Updating task {} to Done", task_id).into());
-                                                                                            web_sys::window()
-                                                                                                .and_then(|w| w.alert_with_message(&format!("✓ Merge successful!\n\n{}", success_msg)).ok());
-                                                                                            // Update task status to Done
-                                                                                            web_sys::console::log_1(&"Calling update_status...".into());
-                                                                                            update_status(task_id.clone(), TaskStatus::Done);
-                                                                                            web_sys::console::log_1(&"update_status called".into());
-                                                                                        }
-                                                                            } else {
-                                                                                // Failed to deserialize - likely an error
-                                                                                web_sys::console::error_1(&"Failed to deserialize merge response".into());
-                                                                                web_sys::window()
-                                                                                    .and_then(|w| w.alert_with_message("✗ Merge failed - unexpected response format").ok());
-                                                                            }
-                                                                        }
-                                                                        _ => {
-                                                                            web_sys::window()
-                                                                                .and_then(|w| w.alert_with_message("Failed to invoke merge command").ok());
-                                                                        }
-                                                                    }
+                                                                // No uncommitted changes; pick the strategy (and squash message) in the merge dialog
+                                                                merge_dialog_open_count.update(|n| *n += 1);
+                                                                if let Some(merge_dialog) = merge_dialog_ref.get() {
+                                                                    let _ = merge_dialog.show_modal();
                                                                 }
                                                             } else {
                                                                 web_sys::window()
@@ -828,13 +920,19 @@ pub fn TaskSidebar(
                                             >
                                                 "🡺" {/* Alternative: 🞈 */}
                                             </button>
-                                            <button
-                                                class="action-btn rebase-btn"
-                                                title="Rebase (Interactive)"
-                                                disabled=true
-                                            >
-                                                "🡿" {/* Alternative: 🞴 */}
-                                            </button>
+                                            <BranchUpdater
+                                                task_id=task_id_for_merge.clone()
+                                                worktree_path=worktree_path_for_merge.clone()
+                                                base_branch=base_branch_for_merge.clone()
+                                                on_update_base_commit=on_update_base_commit.clone()
+                                            />
+                                            <MergeDialog
+                                                dialog_ref=merge_dialog_ref
+                                                task_id=task_id_for_merge.clone()
+                                                base_branch=base_branch_for_merge.clone()
+                                                open_count=merge_dialog_open_count
+                                                on_confirm=run_merge
+                                            />
                                         </div>
                                     }
                                 })}
@@ -847,8 +945,13 @@ pub fn TaskSidebar(
                 {match task_status {
                     TaskStatus::ToDo => view! {}.into_any(),
                     _ => {
-                        // Tab state management
-                        let (active_tab, set_active_tab) = signal("agents".to_string());
+                        // Tab state management; a conflicted task opens on its conflicts
+                        let is_conflicted = task_status == TaskStatus::Conflicted;
+                        let (active_tab, set_active_tab) = signal(if is_conflicted { "conflicts" } else { "agents" }.to_string());
+                        let worktree_path_for_conflicts = _task_worktree_path.clone().unwrap_or_default();
+                        let base_branch_for_conflicts = task.base_branch.clone();
+                        let project_path_for_conflicts = project_path.clone();
+                        let on_update_status_for_conflicts = on_update_status.clone();
 
                         view! {
                             <div class="tabbed-interface">
@@ -902,6 +1005,12 @@ pub fn TaskSidebar(
                                             }
                                         }
                                     >"Processes"</button>
+                                    {is_conflicted.then(|| view! {
+                                        <button
+                                            class=move || format!("tab-header conflicts-tab {}", if active_tab.get() == "conflicts" { "active" } else { "" })
+                                            on:click=move |_| set_active_tab.set("conflicts".to_string())
+                                        >"Conflicts"</button>
+                                    })}
                                 </div>
 
                                 {/* Tab Content */}
@@ -952,14 +1061,42 @@ pub fn TaskSidebar(
                                                      />
 
                                                     {/* Chat Input */}
-                                                    <div class="chat-input-section">
+                                                    <div
+                                                        class="chat-input-section"
+                                                        on:dragover=move |ev| ev.prevent_default()
+                                                        on:drop={
+                                                            let task_id_for_drop = task_id_for_closure.clone();
+                                                            move |ev| on_drop_images(ev, task_id_for_drop.clone(), reply_images)
+                                                        }
+                                                    >
+                                                        <ModelPicker choice=reply_choice default_label="Task default" inline=true />
+                                                        <FileMentions worktree_path=_task_worktree_path.clone() message_input=message_input set_message_input=set_message_input />
+                                                        <AttachedImages images=reply_images />
+                                                        <Show when=move || reply_profile.get().is_some()>
+                                                            <label class="handoff-toggle" title="Ask the current agent for a summary (goal, done, files, open issues) and start the new one with it">
+                                                                <input type="checkbox" prop:checked=move || reply_handoff.get() on:change=move |ev| reply_handoff.set(event_target_checked(&ev)) />
+                                                                " Hand off with a summary"
+                                                            </label>
+                                                        </Show>
                                                         <div class="input-container">
-                                                            <button class="profile-btn" title="Agent profile from previous run" disabled=true>
-                                                                {match selected_profile.get() {
-                                                                    AgentProfile::ClaudeCode => "Claude",
-                                                                    AgentProfile::Codex => "Codex",
+                                                            <select
+                                                                class="profile-btn"
+                                                                title="Agent for the reply; another agent than the current run's continues the conversation"
+                                                                on:change=move |ev| {
+                                                                    let value = event_target_value(&ev);
+                                                                    let current = current_kind(current_process_id.get_untracked(), &all_processes.get_untracked());
+                                                                    reply_profile.set(Some(value).filter(|v| Some(v) != current.as_ref()));
+                                                                }
+                                                            >
+                                                                {move || {
+                                                                    let current = current_kind(current_process_id.get(), &all_processes.get());
+                                                                    let selected = reply_profile.get().or(current).unwrap_or_else(|| selected_profile.get().as_profile_str());
+                                                                    agent_profiles.get().into_iter().map(|p| {
+                                                                        let is_selected = p.name == selected;
+                                                                        view! { <option value=p.name.clone() selected=is_selected>{p.display_name.clone()}</option> }
+                                                                    }).collect_view()
                                                                 }}
-                                                            </button>
+                                                            </select>
                                                             <input
                                                                 type="text"
                                                                 placeholder={
@@ -971,6 +1108,10 @@ pub fn TaskSidebar(
                                                                 }
                                                                 class="message-input"
                                                                 on:input=move |ev| set_message_input.set(event_target_value(&ev))
+                                                                on:paste={
+                                                                    let task_id_for_paste = task_id_for_closure.clone();
+                                                                    move |ev| on_paste_images(ev, task_id_for_paste.clone(), reply_images)
+                                                                }
                                                                 on:keydown={
                                                                     let current_process_id = current_process_id.clone();
                                                                     let message_input = message_input.clone();
@@ -997,10 +1138,7 @@ pub fn TaskSidebar(
                                                                             let lam = load_agent_messages.clone();
                                                                             let tid_for_proc = task_id_for_keydown.clone();
                                                                             let now = chrono::Utc::now().to_rfc3339();
-                                                                            let kind_str = match selected_profile.get_untracked() {
-                                                                                AgentProfile::ClaudeCode => "claude".to_string(),
-                                                                                AgentProfile::Codex => "codex".to_string(),
-                                                                            };
+                                                                            let kind_str = selected_profile.get_untracked().as_profile_str();
                                                                     let set_all = set_all_processes.clone();
                                                                             let tid_for_proc_value = tid_for_proc.clone();
                                                                             let now_value = now.clone();
@@ -1009,14 +1147,13 @@ pub fn TaskSidebar(
                                                                             let on_update_status_key2 = on_update_status_key.clone();
                                                                             let task_status_at_render2 = task_status_at_render.clone();
                                                                             let task_id_for_keydown2 = task_id_for_keydown.clone();
+                                                                            let choice = reply_choice.get_untracked();
+                                                                            let images = reply_images.get_untracked();
+                                                                            let (profile, handoff) = (reply_profile.get_untracked(), reply_handoff.get_untracked());
                                                                             spawn_local(async move {
-                                                                                let args = serde_json::json!({
-                                                                                    "processId": pid,
-                                                                                    "message": msg,
-                                                                                    "worktreePath": worktree_path,
-                                                                                });
+                                                                                let (command, args) = reply_request(pid, msg, worktree_path, choice, images, profile, handoff);
                                                                                 if let Ok(js_value) = to_value(&args) {
-                                                                                    let resp = invoke("send_agent_message", js_value).await;
+                                                                                    let resp = invoke(command, js_value).await;
                                                                                     if !resp.is_undefined() {
                                                                                     if let Ok(new_pid) = serde_wasm_bindgen::from_value::<String>(resp) {
                                                                                         set_current_process_id.set(Some(new_pid.clone()));
@@ -1025,6 +1162,8 @@ pub fn TaskSidebar(
                                                                                             on_update_status_key2(task_id_for_keydown2.clone(), TaskStatus::InProgress);
                                                                                         }
                                                                                         set_message_input.set(String::new());
+                                                                                        reply_images.set(Vec::new());
+                                                                                        reply_profile.set(None);
                                                                                         // Optimistically add new process to the list so its group appears immediately
                                                                                         set_all.update(|procs| {
                                                                                             procs.push(serde_json::json!({
@@ -1065,10 +1204,7 @@ pub fn TaskSidebar(
                                                                     let load_all3 = load_all_processes.clone();
                                                                     let task_id_for_click = task_id_for_closure.clone();
                                                                     let now_click = chrono::Utc::now().to_rfc3339();
-                                                                    let kind_click = match selected_profile.get_untracked() {
-                                                                        AgentProfile::ClaudeCode => "claude".to_string(),
-                                                                        AgentProfile::Codex => "codex".to_string(),
-                                                                    };
+                                                                    let kind_click = selected_profile.get_untracked().as_profile_str();
                                                                     let on_update_status_click = on_update_status.clone();
                                                                     let task_status_at_render_click = task_status.clone();
                                                                     move |_| {
@@ -1087,14 +1223,13 @@ pub fn TaskSidebar(
                                                                         let on_update_status_click2 = on_update_status_click.clone();
                                                                         let task_status_at_render_click2 = task_status_at_render_click.clone();
                                                                         let task_id_for_click2 = task_id_for_click.clone();
+                                                                        let choice = reply_choice.get_untracked();
+                                                                        let images = reply_images.get_untracked();
+                                                                        let (profile, handoff) = (reply_profile.get_untracked(), reply_handoff.get_untracked());
                                                                         spawn_local(async move {
-                                                                            let args = serde_json::json!({
-                                                                                "processId": pid,
-                                                                                "message": msg,
-                                                                                "worktreePath": worktree_path,
-                                                                            });
+                                                                            let (command, args) = reply_request(pid, msg, worktree_path, choice, images, profile, handoff);
                                                                             if let Ok(js_value) = to_value(&args) {
-                                                                                let resp = invoke("send_agent_message", js_value).await;
+                                                                                let resp = invoke(command, js_value).await;
                                                                                 // Expect a new process_id string
                                                                                 if !resp.is_undefined() {
                                                                                     if let Ok(new_pid) = serde_wasm_bindgen::from_value::<String>(resp) {
@@ -1105,6 +1240,8 @@ pub fn TaskSidebar(
                                                                                         }
                                                                                         // Clear input and load messages for the new process immediately
                                                                                         set_message_input.set(String::new());
+                                                                                        reply_images.set(Vec::new());
+                                                                                        reply_profile.set(None);
                                                                                         // Optimistically add new process so its group appears instantly
                                                                                         set_all.update(|procs| {
                                                                                             procs.push(serde_json::json!({
@@ -1134,7 +1271,18 @@ pub fn TaskSidebar(
                                                 </div>
                                             }
                                         }.into_any(),
-                                        "diff" => view! { <super::agents::DiffTab task_id=task_id_for_closure.clone() worktree_path=_task_worktree_path.clone().unwrap_or_default().into() /> }.into_any(),
+                                        "conflicts" => view! {
+                                            <ConflictResolver
+                                                task_id=task_id_for_closure.clone()
+                                                worktree_path=worktree_path_for_conflicts.clone()
+                                                base_branch=base_branch_for_conflicts.clone()
+                                                project_path=project_path_for_conflicts.clone()
+                                                process_id=current_process_id
+                                                set_process_id=set_current_process_id
+                                                on_update_status=on_update_status_for_conflicts.clone()
+                                            />
+                                        }.into_any(),
+                                        "diff" => view! { <super::agents::DiffTab task_id=task_id_for_closure.clone() worktree_path=_task_worktree_path.clone().unwrap_or_default() base_commit=task_base_commit.clone().unwrap_or_default() /> }.into_any(),
                                         "processes" => view! { <super::agents::ProcessesTab processes=all_processes.get() task_id=task_id_for_closure.clone() /> }.into_any(),
                                         _ => view! {}.into_any()
                                     } }
@@ -1158,3 +1306,41 @@ pub fn TaskSidebar(
         />
     }
 }
+
+/// Kind of the agent behind the selected process
+fn current_kind(process_id: Option<String>, processes: &[serde_json::Value]) -> Option<String> {
+    let process_id = process_id?;
+    processes.iter()
+        .find(|p| p.get("id").and_then(|v| v.as_str()) == Some(process_id.as_str()))
+        .and_then(|p| p.get("kind").and_then(|v| v.as_str()))
+        .map(|s| s.to_string())
+}
+
+/// Command and arguments for a reply; a reply to another agent goes through
+/// `send_agent_message_with_profile`, as a handoff when `handoff` is set
+fn reply_request(
+    process_id: String,
+    message: String,
+    worktree_path: String,
+    choice: ModelChoice,
+    images: Vec<String>,
+    profile: Option<String>,
+    handoff: bool,
+) -> (&'static str, serde_json::Value) {
+    let mut args = serde_json::json!({
+        "processId": process_id,
+        "message": message,
+        "worktreePath": worktree_path,
+        "model": choice.model,
+        "reasoningEffort": choice.reasoning_effort,
+        "images": images,
+    });
+    match profile {
+        Some(profile) => {
+            args["profile"] = serde_json::json!(profile);
+            args["handoff"] = serde_json::json!(handoff);
+            ("send_agent_message_with_profile", args)
+        }
+        None => ("send_agent_message", args),
+    }
+}
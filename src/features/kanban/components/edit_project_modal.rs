@@ -1,7 +1,10 @@
 use leptos::prelude::*;
 use leptos::{ev, html::Dialog};
 use leptos::task::spawn_local;
-use crate::core::models::Project;
+use crate::core::models::{AgentBudget, AgentPolicy, MergeStrategy, Project, ProjectNotes};
+use crate::core::ui::{AgentBudgetEditor, AgentPolicyEditor};
+use super::ProjectNotesEditor;
+use super::project_notes_editor::{load_project_notes, save_project_notes};
 use wasm_bindgen::prelude::*;
 use serde_wasm_bindgen::to_value;
 
@@ -20,6 +23,13 @@ pub fn EditProjectModal(
     let (project_name, set_project_name) = signal(String::new());
     let (project_path, set_project_path) = signal(String::new());
     let (loading, set_loading) = signal(true);
+    let agent_policy = RwSignal::new(AgentPolicy::default());
+    let agent_budget = RwSignal::new(AgentBudget::default());
+    let (max_concurrent, set_max_concurrent) = signal(String::new()); // empty = no project limit
+    let merge_strategy = RwSignal::new(MergeStrategy::default());
+    let (prompt_template, set_prompt_template) = signal(String::new()); // empty = "{title}: {description}"
+    let (template_preview, set_template_preview) = signal(None::<String>);
+    let project_notes = RwSignal::new(ProjectNotes::default());
     
     // Load project data whenever the modal opens
     // We'll create a reactive load function that can be called
@@ -38,6 +48,7 @@ pub fn EditProjectModal(
             set_loading.set(true);
             
             spawn_local(async move {
+                project_notes.set(load_project_notes(&project_id).await);
                 let empty_args = serde_json::json!({});
                 if let Ok(js_value) = to_value(&empty_args) {
                     match invoke("load_projects_data", js_value).await {
@@ -47,6 +58,12 @@ pub fn EditProjectModal(
                                     if let Some(project) = stored_projects.iter().find(|p| p.id == project_id) {
                                         set_project_name.set(project.name.clone());
                                         set_project_path.set(project.project_path.clone());
+                                        agent_policy.set(project.agent_policy.clone());
+                                        agent_budget.set(project.agent_budget.clone());
+                                        set_max_concurrent.set(project.max_concurrent_agents.map(|n| n.to_string()).unwrap_or_default());
+                                        merge_strategy.set(project.merge_strategy);
+                                        set_prompt_template.set(project.prompt_template.clone().unwrap_or_default());
+                                        set_template_preview.set(None);
                                     }
                                 }
                             }
@@ -92,6 +109,12 @@ pub fn EditProjectModal(
             let project_id = project_id.clone();
             let name = project_name.get().trim().to_string();
             let path = project_path.get().trim().to_string();
+            let policy = agent_policy.get_untracked();
+            let budget = agent_budget.get_untracked();
+            let max_concurrent_agents = max_concurrent.get_untracked().trim().parse::<u32>().ok().filter(|n| *n > 0);
+            let strategy = merge_strategy.get_untracked();
+            let template = Some(prompt_template.get_untracked()).filter(|t| !t.trim().is_empty());
+            let notes = project_notes.get_untracked();
             let on_update = on_update.clone();
             let dialog_ref = dialog_ref.clone();
             let set_project_name = set_project_name.clone();
@@ -99,6 +122,9 @@ pub fn EditProjectModal(
             let set_loading = set_loading.clone();
             
             spawn_local(async move {
+                if save_project_notes(&project_id, &notes).await.is_none() {
+                    web_sys::console::error_1(&"Failed to save project notes".into());
+                }
                 let empty_args = serde_json::json!({});
                 if let Ok(js_value) = to_value(&empty_args) {
                     match invoke("load_projects_data", js_value).await {
@@ -108,6 +134,11 @@ pub fn EditProjectModal(
                                     if let Some(project) = stored_projects.iter_mut().find(|p| p.id == project_id) {
                                         project.name = name;
                                         project.project_path = path;
+                                        project.agent_policy = policy;
+                                        project.agent_budget = budget;
+                                        project.max_concurrent_agents = max_concurrent_agents;
+                                        project.merge_strategy = strategy;
+                                        project.prompt_template = template;
                                         let updated_project = project.clone();
                                         
                                         // Save updated projects using proper command
@@ -141,6 +172,22 @@ pub fn EditProjectModal(
         }
     };
     
+    // Renders the unsaved template with example task values
+    let preview_template = {
+        let project_id = project_id.clone();
+        move |_| {
+            let args = serde_json::json!({ "projectId": project_id, "template": prompt_template.get_untracked() });
+            spawn_local(async move {
+                if let Ok(js_value) = to_value(&args) {
+                    let result = invoke("preview_prompt_template", js_value).await;
+                    if let Ok(rendered) = serde_wasm_bindgen::from_value::<String>(result) {
+                        set_template_preview.set(Some(rendered));
+                    }
+                }
+            });
+        }
+    };
+
     view! {
         <dialog node_ref=dialog_ref class="modal">
             <div class="modal-content">
@@ -195,6 +242,68 @@ pub fn EditProjectModal(
                                         <small class="form-help">"The directory where your project is located"</small>
                                     </div>
 
+                                    <div class="form-group">
+                                        <label for="edit-project-max-agents">"Max Concurrent Agents"</label>
+                                        <input
+                                            id="edit-project-max-agents"
+                                            type="number"
+                                            min="1"
+                                            prop:value=max_concurrent
+                                            on:input=move |ev| set_max_concurrent.set(event_target_value(&ev))
+                                            placeholder="no project limit"
+                                        />
+                                        <small class="form-help">"Extra runs wait in the queue. The global limit in Settings still applies."</small>
+                                    </div>
+
+                                    <div class="form-group">
+                                        <label for="edit-project-merge-strategy">"Merge Strategy"</label>
+                                        <select
+                                            id="edit-project-merge-strategy"
+                                            on:change=move |ev| merge_strategy.set(MergeStrategy::from_value(&event_target_value(&ev)))
+                                        >
+                                            {MergeStrategy::all().into_iter().map(|s| view! {
+                                                <option value=s.as_value() selected=move || merge_strategy.get() == s>{s.label()}</option>
+                                            }).collect_view()}
+                                        </select>
+                                        <small class="form-help">"Default for merging tasks into their base branch; each merge can pick another."</small>
+                                    </div>
+
+                                    <details class="policy-section">
+                                        <summary>{move || {
+                                            let pending = project_notes.get().proposals.len();
+                                            if pending > 0 { format!("Project Notes ({} to review)", pending) } else { "Project Notes".to_string() }
+                                        }}</summary>
+                                        <small class="form-help">"Facts agents should know about this project."</small>
+                                        <ProjectNotesEditor project_id=project_id.clone() notes=project_notes />
+                                    </details>
+
+                                    <details class="policy-section">
+                                        <summary>"Prompt Template"</summary>
+                                        <small class="form-help">"First message of every agent run in this project. Placeholders: {{title}}, {{description}}, {{base_branch}}, {{branch}}, {{project_name}}, {{labels}}, {{file:PATH}} (relative to the worktree). Empty sends \"title: description\"."</small>
+                                        <textarea
+                                            rows="6"
+                                            placeholder="{{title}}\n\n{{description}}\n\nRun cargo test before finishing."
+                                            prop:value=prompt_template
+                                            on:input=move |ev| set_prompt_template.set(event_target_value(&ev))
+                                        ></textarea>
+                                        <button type="button" class="btn-secondary" on:click=preview_template.clone()>"Preview"</button>
+                                        {move || template_preview.get().map(|rendered| view! {
+                                            <pre class="template-preview">{rendered}</pre>
+                                        })}
+                                    </details>
+
+                                    <details class="policy-section">
+                                        <summary>"Agent Policy"</summary>
+                                        <small class="form-help">"Permissions and tools for agents in this project. Tasks can override it."</small>
+                                        <AgentPolicyEditor policy=agent_policy />
+                                    </details>
+
+                                    <details class="policy-section">
+                                        <summary>"Agent Budget"</summary>
+                                        <small class="form-help">"Cost and token limits cap all runs in this project; turn and minute limits apply to each run. Tasks can set their own."</small>
+                                        <AgentBudgetEditor budget=agent_budget />
+                                    </details>
+
                                     <div class="modal-actions">
                                         <button type="button" class="btn-secondary" on:click=close_modal>"Cancel"</button>
                                         <button type="submit" class="btn-primary">"Save Changes"</button>
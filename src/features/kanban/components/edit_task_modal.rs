@@ -1,15 +1,23 @@
 use leptos::prelude::*;
 use leptos::{ev, html::Dialog};
-use crate::core::models::Task;
+use crate::core::models::{Task, TaskDetails};
+use crate::core::ui::{AgentBudgetEditor, AgentPolicyEditor, ModelPicker};
+use super::RetryPolicyEditor;
 
 #[component]
 pub fn EditTaskModal(
     #[prop(into)] task: Task,
-    #[prop(into)] on_edit: Box<dyn Fn(String, String, String) + 'static>, // task_id, title, description
+    #[prop(into)] on_edit: Box<dyn Fn(String, TaskDetails) + 'static>, // task_id, edited fields
     dialog_ref: NodeRef<Dialog>,
 ) -> impl IntoView {
     let (title, set_title) = signal(task.title.clone());
     let (description, set_description) = signal(task.description.clone());
+    let (labels, set_labels) = signal(task.labels.join(", "));
+    let (override_policy, set_override_policy) = signal(task.agent_policy.is_some());
+    let agent_policy = RwSignal::new(task.agent_policy.clone().unwrap_or_default());
+    let agent_budget = RwSignal::new(task.agent_budget.clone());
+    let retry_policy = RwSignal::new(task.retry_policy.clone());
+    let model_choice = RwSignal::new(task.model_choice());
 
     let task_id = task.id.clone();
     
@@ -17,7 +25,20 @@ pub fn EditTaskModal(
         ev.prevent_default();
         
         // Call the parent's callback function with the updated task data
-        on_edit(task_id.clone(), title.get_untracked(), description.get_untracked());
+        let details = TaskDetails {
+            title: title.get_untracked(),
+            description: description.get_untracked(),
+            labels: labels.get_untracked()
+                .split(',')
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty())
+                .collect(),
+            agent_policy: if override_policy.get_untracked() { Some(agent_policy.get_untracked()) } else { None },
+            agent_budget: agent_budget.get_untracked(),
+            retry_policy: retry_policy.get_untracked(),
+            model_choice: model_choice.get_untracked(),
+        };
+        on_edit(task_id.clone(), details);
         
         // Close the HTML dialog element by calling its close() method
         if let Some(dialog) = dialog_ref.get() {
@@ -85,6 +106,44 @@ pub fn EditTaskModal(
                             prop:value=move || description.get()
                         ></textarea>
                     </div>
+                    <div class="form-group">
+                        <label>"LABELS"</label>
+                        <input
+                            type="text"
+                            placeholder="bug, frontend..."
+                            on:input=move |ev| set_labels.set(event_target_value(&ev))
+                            prop:value=move || labels.get()
+                        />
+                        <small class="form-help">"Comma separated. Available to prompt templates as {{labels}}."</small>
+                    </div>
+                    <div class="form-group">
+                        <label class="checkbox-label">
+                            <input
+                                type="checkbox"
+                                prop:checked=move || override_policy.get()
+                                on:change=move |ev| set_override_policy.set(event_target_checked(&ev))
+                            />
+                            " Override project agent policy"
+                        </label>
+                    </div>
+                    <Show when=move || override_policy.get()>
+                        <AgentPolicyEditor policy=agent_policy />
+                    </Show>
+                    <details class="policy-section">
+                        <summary>"Model"</summary>
+                        <small class="form-help">"Used for every run of this task unless a reply picks another."</small>
+                        <ModelPicker choice=model_choice default_label="CLI default" />
+                    </details>
+                    <details class="policy-section">
+                        <summary>"Budget"</summary>
+                        <small class="form-help">"Cost and token limits set here cap all runs of this task. Empty fields use the project budget."</small>
+                        <AgentBudgetEditor budget=agent_budget />
+                    </details>
+                    <details class="policy-section">
+                        <summary>"Retries"</summary>
+                        <small class="form-help">"Failed agent runs start again automatically, resuming the session where possible."</small>
+                        <RetryPolicyEditor policy=retry_policy />
+                    </details>
                     <div class="modal-actions">
                         <button type="button" class="btn-secondary" on:click=close_modal_cancel>"CANCEL"</button>
                         <button type="submit" class="btn-primary">"SAVE CHANGES"</button>
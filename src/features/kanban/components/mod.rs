@@ -6,7 +6,9 @@ pub mod task_menu;
 pub mod kanban_page;
 pub mod task_modal;
 pub mod edit_task_modal;
+pub mod retry_policy_editor;
 pub mod edit_project_modal;
+pub mod project_notes_editor;
 pub mod settings_modal;
 
 pub use header::KanbanHeader;
@@ -17,6 +19,8 @@ pub use task_menu::TaskMenu;
 pub use kanban_page::KanbanPage;
 pub use task_modal::TaskModal;
 pub use edit_task_modal::EditTaskModal;
+pub use retry_policy_editor::RetryPolicyEditor;
 pub use edit_project_modal::EditProjectModal;
+pub use project_notes_editor::ProjectNotesEditor;
 pub use settings_modal::SettingsModal;
 
@@ -0,0 +1,109 @@
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use crate::core::models::ProjectNotes;
+use wasm_bindgen::prelude::*;
+use serde_wasm_bindgen::to_value;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+}
+
+/// Saves the notes document and its injection switch
+pub async fn save_project_notes(project_id: &str, notes: &ProjectNotes) -> Option<ProjectNotes> {
+    let args = serde_json::json!({
+        "projectId": project_id,
+        "content": notes.content,
+        "injectIntoPrompts": notes.inject_into_prompts,
+    });
+    let js_value = to_value(&args).ok()?;
+    serde_wasm_bindgen::from_value::<ProjectNotes>(invoke("save_project_notes", js_value).await).ok()
+}
+
+/// Loads a project's notes and pending proposals
+pub async fn load_project_notes(project_id: &str) -> ProjectNotes {
+    let args = serde_json::json!({ "projectId": project_id });
+    match to_value(&args) {
+        Ok(js_value) => serde_wasm_bindgen::from_value::<ProjectNotes>(invoke("load_project_notes", js_value).await).unwrap_or_default(),
+        Err(_) => ProjectNotes::default(),
+    }
+}
+
+/// Notes document for a project plus the additions agents proposed for it.
+/// Approving saves the current text first, so edits in progress are kept.
+#[component]
+pub fn ProjectNotesEditor(#[prop(into)] project_id: String, notes: RwSignal<ProjectNotes>) -> impl IntoView {
+    let resolve = move |proposal_id: String, approve: bool| {
+        let project_id = project_id.clone();
+        spawn_local(async move {
+            let current = notes.get_untracked();
+            if approve && save_project_notes(&project_id, &current).await.is_none() {
+                web_sys::console::error_1(&"Failed to save project notes before approving".into());
+                return;
+            }
+            let args = serde_json::json!({
+                "projectId": project_id,
+                "proposalId": proposal_id,
+                "approve": approve,
+            });
+            if let Ok(js_value) = to_value(&args) {
+                let result = invoke("resolve_note_proposal", js_value).await;
+                match serde_wasm_bindgen::from_value::<ProjectNotes>(result) {
+                    Ok(updated) => notes.set(updated),
+                    Err(e) => web_sys::console::error_1(&format!("Failed to resolve note proposal: {:?}", e).into()),
+                }
+            }
+        });
+    };
+
+    view! {
+        <div class="notes-editor">
+            <div class="form-group">
+                <textarea
+                    rows="8"
+                    placeholder="Build with `cargo build --workspace`. The API client lives in src/core/services..."
+                    prop:value=move || notes.get().content
+                    on:input=move |ev| { let v = event_target_value(&ev); notes.update(|n| n.content = v); }
+                ></textarea>
+            </div>
+            <div class="form-group">
+                <label class="checkbox-label">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || notes.get().inject_into_prompts
+                        on:change=move |ev| { let v = event_target_checked(&ev); notes.update(|n| n.inject_into_prompts = v); }
+                    />
+                    " Prepend to every agent's first prompt"
+                </label>
+                <small class="form-help">"Agents are asked to wrap new learnings in <project-note> tags; they show up below for review."</small>
+            </div>
+            {move || {
+                let proposals = notes.get().proposals;
+                (!proposals.is_empty()).then(|| {
+                    let resolve = resolve.clone();
+                    view! {
+                        <div class="note-proposals">
+                            <h4>{format!("Proposed additions ({})", proposals.len())}</h4>
+                            {proposals.into_iter().map(|proposal| {
+                                let (approve_id, reject_id) = (proposal.id.clone(), proposal.id.clone());
+                                let (resolve_ok, resolve_no) = (resolve.clone(), resolve.clone());
+                                let source = format!("from task {}", proposal.task_id.chars().take(8).collect::<String>());
+                                view! {
+                                    <div class="note-proposal">
+                                        <pre>{proposal.content}</pre>
+                                        <div class="note-proposal-actions">
+                                            <span class="note-proposal-source">{source}</span>
+                                            <button type="button" class="btn-primary" on:click=move |_| resolve_ok(approve_id.clone(), true)>"Approve"</button>
+                                            <button type="button" class="btn-secondary" on:click=move |_| resolve_no(reject_id.clone(), false)>"Reject"</button>
+                                        </div>
+                                    </div>
+                                }
+                            }).collect::<Vec<_>>()}
+                        </div>
+                    }
+                })
+            }}
+        </div>
+    }
+}
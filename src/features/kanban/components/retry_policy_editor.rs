@@ -0,0 +1,46 @@
+use leptos::prelude::*;
+use crate::core::models::RetryPolicy;
+
+/// Form fields for a task's automatic retry policy
+#[component]
+pub fn RetryPolicyEditor(policy: RwSignal<RetryPolicy>) -> impl IntoView {
+    view! {
+        <div class="retry-editor">
+            <div class="form-group">
+                <label>"MAX ATTEMPTS"</label>
+                <input type="number" min="1" step="1"
+                    prop:value=move || policy.get().max_attempts.to_string()
+                    on:change=move |ev| {
+                        let v = event_target_value(&ev).trim().parse::<u32>().unwrap_or(1).max(1);
+                        policy.update(|p| p.max_attempts = v);
+                    }
+                />
+            </div>
+            <div class="form-group">
+                <label>"BACKOFF (SECONDS)"</label>
+                <input type="number" min="0" step="1"
+                    prop:value=move || policy.get().backoff_secs.to_string()
+                    on:change=move |ev| {
+                        let v = event_target_value(&ev).trim().parse::<u64>().unwrap_or(0);
+                        policy.update(|p| p.backoff_secs = v);
+                    }
+                />
+                <small class="form-help">"Doubled for each further attempt."</small>
+            </div>
+            <div class="form-group">
+                <label>"RETRYABLE ERRORS (ONE PER LINE)"</label>
+                <textarea rows="3" placeholder="overloaded\nrate limit\nECONNRESET"
+                    prop:value=move || policy.get().retryable_patterns.join("\n")
+                    on:change=move |ev| {
+                        let patterns: Vec<String> = event_target_value(&ev).lines()
+                            .map(|l| l.trim().to_string())
+                            .filter(|l| !l.is_empty())
+                            .collect();
+                        policy.update(|p| p.retryable_patterns = patterns);
+                    }
+                ></textarea>
+                <small class="form-help">"Matched against the run's error output. Empty retries every failure."</small>
+            </div>
+        </div>
+    }
+}
@@ -8,7 +8,51 @@ use serde_wasm_bindgen::to_value;
 struct CodexSettings { command: Option<String>, args: Option<Vec<String>> }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-struct AgentSettings { codex: Option<CodexSettings> }
+struct ClaudeSettings { command: Option<String>, args: Option<Vec<String>>, model: Option<String> }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct CustomAgentSettings {
+    name: String,
+    display_name: Option<String>,
+    command: String,
+    #[serde(default)]
+    args: Vec<String>, // template; {prompt} and {worktree} are substituted
+    #[serde(default = "default_prompt_mode")]
+    prompt_mode: String, // "argv" | "stdin"
+    #[serde(default = "default_output_format")]
+    output_format: String, // "claude-stream-json" | "codex-jsonl" | "text"
+}
+
+fn default_prompt_mode() -> String { "argv".to_string() }
+fn default_output_format() -> String { "text".to_string() }
+
+impl Default for CustomAgentSettings {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            display_name: None,
+            command: String::new(),
+            args: vec![],
+            prompt_mode: default_prompt_mode(),
+            output_format: default_output_format(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct AgentSettings {
+    codex: Option<CodexSettings>,
+    #[serde(default)]
+    claude: Option<ClaudeSettings>,
+    #[serde(default)]
+    custom_agents: Vec<CustomAgentSettings>,
+    #[serde(default)]
+    max_concurrent_agents: Option<u32>,
+    #[serde(default)]
+    stall_timeout_secs: Option<u64>,
+    #[serde(default)]
+    retry_on_stall: bool,
+}
 
 #[wasm_bindgen]
 extern "C" {
@@ -20,6 +64,13 @@ extern "C" {
 pub fn SettingsModal(dialog_ref: NodeRef<Dialog>) -> impl IntoView {
     let (codex_command, set_codex_command) = signal(String::new());
     let (codex_args, set_codex_args) = signal(String::new()); // space-separated
+    let (claude_command, set_claude_command) = signal(String::new());
+    let (claude_args, set_claude_args) = signal(String::new()); // space-separated
+    let (claude_model, set_claude_model) = signal(String::new());
+    let custom_agents = RwSignal::new(Vec::<CustomAgentSettings>::new());
+    let (max_concurrent, set_max_concurrent) = signal(String::new()); // empty = unlimited
+    let (stall_timeout, set_stall_timeout) = signal(String::new()); // seconds; empty = off
+    let (retry_on_stall, set_retry_on_stall) = signal(false);
 
     // Load settings on open invocation
     let load_settings = {
@@ -36,6 +87,15 @@ pub fn SettingsModal(dialog_ref: NodeRef<Dialog>) -> impl IntoView {
                                 if let Some(cmd) = codex.command { set_codex_command.set(cmd); }
                                 if let Some(a) = codex.args { set_codex_args.set(a.join(" ")); }
                             }
+                            if let Some(claude) = settings.claude {
+                                if let Some(cmd) = claude.command { set_claude_command.set(cmd); }
+                                if let Some(a) = claude.args { set_claude_args.set(a.join(" ")); }
+                                if let Some(m) = claude.model { set_claude_model.set(m); }
+                            }
+                            custom_agents.set(settings.custom_agents);
+                            set_max_concurrent.set(settings.max_concurrent_agents.map(|n| n.to_string()).unwrap_or_default());
+                            set_stall_timeout.set(settings.stall_timeout_secs.map(|n| n.to_string()).unwrap_or_default());
+                            set_retry_on_stall.set(settings.retry_on_stall);
                         }
                     }
                 }
@@ -49,8 +109,29 @@ pub fn SettingsModal(dialog_ref: NodeRef<Dialog>) -> impl IntoView {
         let command = codex_command.get();
         let args_line = codex_args.get();
         let args_vec: Vec<String> = if args_line.trim().is_empty() { vec![] } else { args_line.split_whitespace().map(|s| s.to_string()).collect() };
+        let claude_cmd = claude_command.get();
+        let claude_args_line = claude_args.get();
+        let claude_args_vec: Vec<String> = claude_args_line.split_whitespace().map(|s| s.to_string()).collect();
+        let model = claude_model.get();
+        let agents: Vec<CustomAgentSettings> = custom_agents.get_untracked().into_iter()
+            .filter(|a| !a.name.trim().is_empty() && !a.command.trim().is_empty())
+            .collect();
+        let max_concurrent_agents = max_concurrent.get().trim().parse::<u32>().ok().filter(|n| *n > 0);
+        let stall_timeout_secs = stall_timeout.get().trim().parse::<u64>().ok().filter(|n| *n > 0);
+        let retry_on_stall = retry_on_stall.get();
         leptos::task::spawn_local(async move {
-            let payload = AgentSettings { codex: Some(CodexSettings { command: if command.is_empty() { None } else { Some(command) }, args: Some(args_vec) }) };
+            let payload = AgentSettings {
+                codex: Some(CodexSettings { command: if command.is_empty() { None } else { Some(command) }, args: Some(args_vec) }),
+                claude: Some(ClaudeSettings {
+                    command: if claude_cmd.trim().is_empty() { None } else { Some(claude_cmd.trim().to_string()) },
+                    args: Some(claude_args_vec),
+                    model: if model.trim().is_empty() { None } else { Some(model.trim().to_string()) },
+                }),
+                custom_agents: agents,
+                max_concurrent_agents,
+                stall_timeout_secs,
+                retry_on_stall,
+            };
             if let Ok(js) = to_value(&payload) {
                 let _ = invoke("save_agent_settings", js).await;
             }
@@ -65,6 +146,36 @@ pub fn SettingsModal(dialog_ref: NodeRef<Dialog>) -> impl IntoView {
                     <h2>"Settings"</h2>
                     <button class="modal-close" on:click=close_modal>"x"</button>
                 </div>
+                <div class="modal-section">
+                    <h3>"Runs"</h3>
+                    <div class="form-group">
+                        <label>"Max concurrent agents (all projects)"</label>
+                        <input type="number" min="1" placeholder="unlimited" prop:value=move || max_concurrent.get() on:input=move |ev| set_max_concurrent.set(event_target_value(&ev)) />
+                    </div>
+                    <div class="form-group">
+                        <label>"Stall after (seconds without output)"</label>
+                        <input type="number" min="1" placeholder="off" prop:value=move || stall_timeout.get() on:input=move |ev| set_stall_timeout.set(event_target_value(&ev)) />
+                    </div>
+                    <label class="checkbox-label">
+                        <input type="checkbox" prop:checked=move || retry_on_stall.get() on:change=move |ev| set_retry_on_stall.set(event_target_checked(&ev)) />
+                        "Kill and restart stalled runs (once)"
+                    </label>
+                </div>
+                <div class="modal-section">
+                    <h3>"Claude Code Agent"</h3>
+                    <div class="form-group">
+                        <label>"Command"</label>
+                        <input type="text" placeholder="claude or /path/to/claude" prop:value=move || claude_command.get() on:input=move |ev| set_claude_command.set(event_target_value(&ev)) />
+                    </div>
+                    <div class="form-group">
+                        <label>"Extra Flags (space separated)"</label>
+                        <input type="text" placeholder="--max-turns 20" prop:value=move || claude_args.get() on:input=move |ev| set_claude_args.set(event_target_value(&ev)) />
+                    </div>
+                    <div class="form-group">
+                        <label>"Model"</label>
+                        <input type="text" placeholder="default" prop:value=move || claude_model.get() on:input=move |ev| set_claude_model.set(event_target_value(&ev)) />
+                    </div>
+                </div>
                 <div class="modal-section">
                     <h3>"Codex Agent"</h3>
                     <div class="form-group">
@@ -76,6 +187,52 @@ pub fn SettingsModal(dialog_ref: NodeRef<Dialog>) -> impl IntoView {
                         <input type="text" placeholder="-- --output-format json" prop:value=move || codex_args.get() on:input=move |ev| set_codex_args.set(event_target_value(&ev)) />
                     </div>
                 </div>
+                <div class="modal-section">
+                    <h3>"Custom Agents"</h3>
+                    {move || {
+                        custom_agents.get().into_iter().enumerate().map(|(idx, agent)| {
+                            let update = move |f: &dyn Fn(&mut CustomAgentSettings)| {
+                                custom_agents.update(|list| if let Some(a) = list.get_mut(idx) { f(a) });
+                            };
+                            view! {
+                                <div class="custom-agent">
+                                    <div class="form-group">
+                                        <label>"Name"</label>
+                                        <input type="text" placeholder="aider" prop:value=agent.name.clone()
+                                            on:change=move |ev| { let v = event_target_value(&ev); update(&|a| a.name = v.trim().to_string()) } />
+                                    </div>
+                                    <div class="form-group">
+                                        <label>"Command"</label>
+                                        <input type="text" placeholder="aider" prop:value=agent.command.clone()
+                                            on:change=move |ev| { let v = event_target_value(&ev); update(&|a| a.command = v.trim().to_string()) } />
+                                    </div>
+                                    <div class="form-group">
+                                        <label>"Args Template (space separated, {prompt} / {worktree})"</label>
+                                        <input type="text" placeholder="--yes --message {prompt}" prop:value=agent.args.join(" ")
+                                            on:change=move |ev| { let v = event_target_value(&ev); update(&|a| a.args = v.split_whitespace().map(|s| s.to_string()).collect()) } />
+                                    </div>
+                                    <div class="form-group">
+                                        <label>"Prompt Mode"</label>
+                                        <select on:change=move |ev| { let v = event_target_value(&ev); update(&|a| a.prompt_mode = v.clone()) }>
+                                            <option value="argv" selected=agent.prompt_mode == "argv">"argv"</option>
+                                            <option value="stdin" selected=agent.prompt_mode == "stdin">"stdin"</option>
+                                        </select>
+                                    </div>
+                                    <div class="form-group">
+                                        <label>"Output Format"</label>
+                                        <select on:change=move |ev| { let v = event_target_value(&ev); update(&|a| a.output_format = v.clone()) }>
+                                            <option value="text" selected=agent.output_format == "text">"Plain text lines"</option>
+                                            <option value="claude-stream-json" selected=agent.output_format == "claude-stream-json">"Claude stream-json"</option>
+                                            <option value="codex-jsonl" selected=agent.output_format == "codex-jsonl">"Codex JSONL"</option>
+                                        </select>
+                                    </div>
+                                    <button class="btn-secondary" on:click=move |_| custom_agents.update(|list| { if idx < list.len() { list.remove(idx); } })>"Remove"</button>
+                                </div>
+                            }
+                        }).collect_view()
+                    }}
+                    <button class="btn-secondary" on:click=move |_| custom_agents.update(|list| list.push(CustomAgentSettings::default()))>"+ Add Agent"</button>
+                </div>
                 <div class="modal-actions">
                     <button class="btn-secondary" on:click=close_modal>"Cancel"</button>
                     <button class="btn-primary" on:click=save_settings>"Save"</button>
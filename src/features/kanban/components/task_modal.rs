@@ -1,6 +1,8 @@
 use leptos::prelude::*;
 use leptos::{ev, html::Dialog};
-use crate::core::models::{Task, TaskStatus, AgentProfile};
+use leptos::task::spawn_local;
+use crate::core::models::{Task, TaskStatus, AgentProfile, AgentProfileInfo};
+use crate::core::services::load_agent_profiles;
 use uuid::Uuid;
 use chrono::Utc;
 
@@ -12,6 +14,13 @@ pub fn TaskModal(
 ) -> impl IntoView {
     let (title, set_title) = signal(String::new());
     let (description, set_description) = signal(String::new());
+    let (profile, set_profile) = signal(AgentProfile::ClaudeCode);
+
+    // Built-in and custom agent profiles for the picker
+    let (agent_profiles, set_agent_profiles) = signal(AgentProfileInfo::builtin());
+    spawn_local(async move {
+        set_agent_profiles.set(load_agent_profiles().await);
+    });
 
     // Clone the callback and project_id so they can be moved into the closure
     // The on_create callback is Box<dyn Fn(Task)> which doesn't implement Clone,
@@ -31,8 +40,15 @@ pub fn TaskModal(
             status: TaskStatus::ToDo,                 // New tasks always start in ToDo column
             created_at: Utc::now().to_rfc3339(),      // Timestamp for when task was created (as string)
             worktree_path: None,                      // No worktree initially
-            profile: AgentProfile::ClaudeCode,        // Default to Claude Code
+            profile: profile.get_untracked(),         // Selected agent profile (Claude Code by default)
             base_branch: "main".to_string(),          // Default base branch
+            base_commit: None,                        // Recorded when the worktree is created
+            agent_policy: None,                       // Inherit the project policy
+            agent_budget: Default::default(),         // Inherit the project budget
+            retry_policy: Default::default(),         // No automatic retries
+            labels: Vec::new(),                       // Labels are added when editing
+            model: None,                              // CLI default model
+            reasoning_effort: None,                   // CLI default effort
         };
         
         // DEBUG: Test task serialization before calling the callback
@@ -51,6 +67,7 @@ pub fn TaskModal(
         // Reset form fields to empty state after successful submission
         set_title.set(String::new());
         set_description.set(String::new());
+        set_profile.set(AgentProfile::ClaudeCode);
         
         // Close the HTML dialog element by calling its close() method
         if let Some(dialog) = dialog_ref.get() {
@@ -93,6 +110,20 @@ pub fn TaskModal(
                             prop:value=move || description.get()
                         ></textarea>
                     </div>
+                    <div class="form-group">
+                        <label>"AGENT"</label>
+                        <select on:change=move |ev| set_profile.set(AgentProfile::from_profile_str(&event_target_value(&ev)))>
+                            {move || {
+                                let selected = profile.get().as_profile_str();
+                                agent_profiles.get().into_iter().map(|p| {
+                                    let is_selected = p.name == selected;
+                                    view! {
+                                        <option value=p.name.clone() selected=is_selected>{p.display_name.clone()}</option>
+                                    }
+                                }).collect_view()
+                            }}
+                        </select>
+                    </div>
                     <div class="modal-actions">
                         <button type="button" class="btn-secondary" on:click=close_modal>"CANCEL"</button>
                         <button type="submit" class="btn-primary">"CREATE"</button>
@@ -1,10 +1,10 @@
 use leptos::prelude::*;
 use leptos::html::Dialog;
 use std::sync::Arc;
-use crate::core::models::{Task, TaskStatus, AgentProfile};
+use crate::core::models::{Task, TaskDetails, TaskStatus, AgentProfile};
 use crate::core::services::{open_worktree_location_async, open_worktree_in_ide_async};
 use crate::features::agent_chat::TaskSidebar;
-use crate::features::kanban::services::{delete_task, update_task_details, update_task_profile, update_task_base_branch, update_task_status};
+use crate::features::kanban::services::{delete_task, update_task_details, update_task_profile, update_task_base_branch, update_task_base_commit, update_task_status};
 
 // Hook for managing task sidebar state and callbacks
 pub fn use_task_sidebar(
@@ -103,6 +103,14 @@ fn create_task_sidebar(
         }) as Box<dyn Fn(String, String) + 'static>
     };
 
+    let sidebar_base_commit_callback: Arc<dyn Fn(String, String) + Send + Sync> = {
+        let project_id_clone = project_id.clone();
+        let tasks_signal_clone = tasks_signal;
+        Arc::new(move |task_id: String, base_commit: String| {
+            update_task_base_commit(task_id, base_commit, project_id_clone.clone(), tasks_signal_clone);
+        })
+    };
+
     let sidebar_view = if let Some(path) = project_path {
         view! {
             <TaskSidebar
@@ -116,6 +124,7 @@ fn create_task_sidebar(
                 on_open_ide=Some(sidebar_ide_callback)
                 on_update_profile=sidebar_profile_callback
                 on_update_base_branch=sidebar_base_branch_callback
+                on_update_base_commit=sidebar_base_commit_callback
             />
         }
     } else {
@@ -130,6 +139,7 @@ fn create_task_sidebar(
                 on_open_ide=Some(sidebar_ide_callback)
                 on_update_profile=sidebar_profile_callback
                 on_update_base_branch=sidebar_base_branch_callback
+                on_update_base_commit=sidebar_base_commit_callback
             />
         }
     };
@@ -141,8 +151,8 @@ fn create_task_sidebar(
 pub fn create_edit_task_callback(
     project_id: String,
     tasks_signal: RwSignal<Vec<Task>>,
-) -> Box<dyn Fn(String, String, String) + 'static> {
-    Box::new(move |task_id: String, new_title: String, new_description: String| {
-        update_task_details(task_id, new_title, new_description, project_id.clone(), tasks_signal);
+) -> Box<dyn Fn(String, TaskDetails) + 'static> {
+    Box::new(move |task_id: String, details: TaskDetails| {
+        update_task_details(task_id, details, project_id.clone(), tasks_signal);
     })
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use leptos::prelude::*;
 use leptos::task::spawn_local;
-use crate::core::models::{Task, TaskStatus, AgentProfile};
+use crate::core::models::{Task, TaskDetails, TaskStatus, AgentProfile};
 use crate::core::services::load_tasks;
 use crate::features::kanban::services::{
     create_task_handler, update_task_status, delete_task,
@@ -12,7 +12,7 @@ pub struct TasksHook {
     pub tasks: ReadSignal<Vec<Task>>,
     pub create_task: Box<dyn Fn(Task) + 'static>,
     pub update_status: Box<dyn Fn(String, TaskStatus) + 'static>,
-    pub update_details: Box<dyn Fn(String, String, String) + 'static>,
+    pub update_details: Box<dyn Fn(String, TaskDetails) + 'static>,
     pub update_profile: Box<dyn Fn(String, AgentProfile) + 'static>,
     pub delete_task: Box<dyn Fn(String) + 'static>,
     pub cancel_task: Box<dyn Fn(String) + 'static>,
@@ -50,9 +50,9 @@ pub fn use_tasks(project_id: String) -> TasksHook {
 
     let update_details = {
         let project_id = project_id.clone();
-        Box::new(move |task_id: String, title: String, description: String| {
-            update_task_details(task_id, title, description, project_id.clone(), tasks);
-        }) as Box<dyn Fn(String, String, String) + 'static>
+        Box::new(move |task_id: String, details: TaskDetails| {
+            update_task_details(task_id, details, project_id.clone(), tasks);
+        }) as Box<dyn Fn(String, TaskDetails) + 'static>
     };
 
     let update_profile = {
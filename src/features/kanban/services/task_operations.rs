@@ -1,6 +1,6 @@
 use leptos::prelude::*;
 use leptos::task::spawn_local;
-use crate::core::models::{Task, TaskStatus, AgentProfile};
+use crate::core::models::{Task, TaskDetails, TaskStatus, AgentProfile};
 use crate::core::services::{save_tasks_async, create_worktree_for_task, remove_worktree_for_task, start_agent_for_task};
 
 // Create a new task and save it
@@ -70,10 +70,18 @@ pub fn update_task_status(
             let project_id_clone = project_id.clone();
             let tasks_signal_clone = tasks_signal.clone();
 
+            let base_branch = tasks_signal.with_untracked(|tasks| {
+                tasks.iter()
+                    .find(|t| t.id == task_id)
+                    .map(|t| t.base_branch.clone())
+                    .unwrap_or_default()
+            });
+
             spawn_local(async move {
-                // Create worktree
-                match create_worktree_for_task(&project_id_clone, &task_id_clone).await {
-                    Ok(worktree_path) => {
+                // Create worktree from the task's base branch
+                match create_worktree_for_task(&project_id_clone, &task_id_clone, &base_branch).await {
+                    Ok(worktree) => {
+                        let worktree_path = worktree.path;
                         // Start agent process
                         let task_for_agent = {
                             let tasks = tasks_signal_clone.get_untracked();
@@ -86,8 +94,8 @@ pub fn update_task_status(
                             }
                         }
 
-                        // Update task with worktree path and save
-                        update_task_worktree_path(task_id_clone.clone(), Some(worktree_path), project_id_clone, tasks_signal_clone);
+                        // Update task with worktree path and base commit and save
+                        update_task_worktree(task_id_clone.clone(), Some(worktree_path), Some(worktree.base_commit), project_id_clone, tasks_signal_clone);
                     }
                     Err(e) => {
                         web_sys::console::error_1(&format!("Failed to create worktree: {}", e).into());
@@ -97,8 +105,9 @@ pub fn update_task_status(
         }
     }
 
-    // If task is moving away from InProgress/InReview (to Done/Cancelled), remove worktree
-    if (new_status == TaskStatus::Done || new_status == TaskStatus::Cancelled) && (old_status == TaskStatus::InProgress || old_status == TaskStatus::InReview) {
+    // If task is moving away from InProgress/InReview/Conflicted (to Done/Cancelled), remove worktree
+    if (new_status == TaskStatus::Done || new_status == TaskStatus::Cancelled)
+        && matches!(old_status, TaskStatus::InProgress | TaskStatus::InReview | TaskStatus::Conflicted) {
         if let Some(worktree_path) = worktree_path_opt {
             let worktree_path_clone = worktree_path.clone();
             let task_id_clone = task_id.clone();
@@ -108,7 +117,7 @@ pub fn update_task_status(
             spawn_local(async move {
                 match remove_worktree_for_task(&project_id_clone, &worktree_path_clone).await {
                     Ok(_) => {
-                        update_task_worktree_path(task_id_clone, None, project_id_clone, tasks_signal_clone);
+                        update_task_worktree(task_id_clone, None, None, project_id_clone, tasks_signal_clone);
                     }
                     Err(e) => {
                         web_sys::console::error_1(&format!("Failed to remove worktree: {}", e).into());
@@ -123,10 +132,11 @@ pub fn update_task_status(
     save_tasks_async(project_id, current_tasks);
 }
 
-// Update task worktree path
-fn update_task_worktree_path(
+// Update task worktree path and the commit its branch started from
+fn update_task_worktree(
     task_id: String,
     worktree_path: Option<String>,
+    base_commit: Option<String>,
     project_id: String,
     tasks_signal: RwSignal<Vec<Task>>,
 ) {
@@ -135,6 +145,7 @@ fn update_task_worktree_path(
         if let Some(index) = tasks.iter().position(|t| t.id == task_id) {
             let mut task = tasks[index].clone();
             task.set_worktree_path(worktree_path);
+            task.set_base_commit(base_commit);
             tasks[index] = task;
         }
     });
@@ -158,11 +169,10 @@ pub fn delete_task(
     save_tasks_async(project_id, current_tasks);
 }
 
-// Update task details (title and description)
+// Update task details (title, description and agent settings)
 pub fn update_task_details(
     task_id: String,
-    new_title: String,
-    new_description: String,
+    details: TaskDetails,
     project_id: String,
     tasks_signal: RwSignal<Vec<Task>>,
 ) {
@@ -170,8 +180,7 @@ pub fn update_task_details(
     tasks_signal.update(|tasks| {
         if let Some(index) = tasks.iter().position(|t| t.id == task_id) {
             let mut task = tasks[index].clone();
-            task.update_title(new_title);
-            task.update_description(new_description);
+            task.apply_details(details);
             tasks[index] = task;
         }
     });
@@ -220,6 +229,25 @@ pub fn update_task_base_branch(
     save_tasks_async(project_id, current_tasks);
 }
 
+// Update the base commit after the task branch was updated from its base branch
+pub fn update_task_base_commit(
+    task_id: String,
+    base_commit: String,
+    project_id: String,
+    tasks_signal: RwSignal<Vec<Task>>,
+) {
+    tasks_signal.update(|tasks| {
+        if let Some(index) = tasks.iter().position(|t| t.id == task_id) {
+            let mut task = tasks[index].clone();
+            task.set_base_commit(Some(base_commit));
+            tasks[index] = task;
+        }
+    });
+
+    let current_tasks = tasks_signal.get_untracked();
+    save_tasks_async(project_id, current_tasks);
+}
+
 // Cancel a task (set status to Cancelled)
 pub fn cancel_task(
     task_id: String,
@@ -1,3 +1,5 @@
 pub mod project_modal;
+pub mod usage_panel;
 
-pub use project_modal::ProjectModal;
\ No newline at end of file
+pub use project_modal::ProjectModal;
+pub use usage_panel::UsagePanel;
\ No newline at end of file
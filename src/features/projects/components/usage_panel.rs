@@ -0,0 +1,104 @@
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use serde_wasm_bindgen::to_value;
+use wasm_bindgen::prelude::*;
+use crate::core::models::{format_tokens, UsageSummary, UsageTotals};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+}
+
+const RANGES: [(&str, &str); 4] = [("today", "Today"), ("7d", "7 days"), ("30d", "30 days"), ("all", "All time")];
+
+fn totals_line(totals: &UsageTotals) -> String {
+    format!(
+        "${:.2} · {} tokens ({} in / {} out / {} cached) · {} runs",
+        totals.cost_usd,
+        format_tokens(totals.total_tokens()),
+        format_tokens(totals.input_tokens),
+        format_tokens(totals.output_tokens),
+        format_tokens(totals.cached_tokens),
+        totals.runs
+    )
+}
+
+/// Agent spend per board on the Projects page
+#[component]
+pub fn UsagePanel() -> impl IntoView {
+    let (range, set_range) = signal("7d".to_string());
+    let (summary, set_summary) = signal(None::<UsageSummary>);
+
+    Effect::new(move |_| {
+        let range = range.get();
+        spawn_local(async move {
+            let args = serde_json::json!({ "projectId": null, "range": range });
+            if let Ok(js_value) = to_value(&args) {
+                let result = invoke("get_usage_summary", js_value).await;
+                match serde_wasm_bindgen::from_value::<UsageSummary>(result) {
+                    Ok(loaded) => set_summary.set(Some(loaded)),
+                    Err(e) => web_sys::console::log_1(&format!("Failed to load usage summary: {:?}", e).into()),
+                }
+            }
+        });
+    });
+
+    view! {
+        <section class="usage-panel">
+            <div class="usage-panel-header">
+                <h2>"Agent Spend"</h2>
+                <div class="usage-ranges">
+                    {RANGES.iter().map(|(key, label)| {
+                        let key = key.to_string();
+                        let key_for_class = key.clone();
+                        view! {
+                            <button
+                                class=move || if range.get() == key_for_class { "usage-range active" } else { "usage-range" }
+                                on:click=move |_| set_range.set(key.clone())
+                            >
+                                {*label}
+                            </button>
+                        }
+                    }).collect::<Vec<_>>()}
+                </div>
+            </div>
+            {move || match summary.get() {
+                None => view! { <p class="usage-empty">"Loading usage…"</p> }.into_any(),
+                Some(summary) if summary.totals.runs == 0 => view! {
+                    <p class="usage-empty">"No agent usage in this range"</p>
+                }.into_any(),
+                Some(summary) => {
+                    let kinds = summary.by_kind.iter()
+                        .map(|bucket| format!("{} ${:.2}", bucket.label, bucket.totals.cost_usd))
+                        .collect::<Vec<_>>()
+                        .join(" · ");
+                    view! {
+                        <div class="usage-total">{totals_line(&summary.totals)}</div>
+                        <div class="usage-kinds">{kinds}</div>
+                        <table class="usage-table">
+                            <thead>
+                                <tr>
+                                    <th>"Board"</th>
+                                    <th>"Cost"</th>
+                                    <th>"Tokens"</th>
+                                    <th>"Runs"</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {summary.by_project.into_iter().map(|bucket| view! {
+                                    <tr>
+                                        <td>{bucket.label}</td>
+                                        <td>{format!("${:.2}", bucket.totals.cost_usd)}</td>
+                                        <td>{format_tokens(bucket.totals.total_tokens())}</td>
+                                        <td>{bucket.totals.runs}</td>
+                                    </tr>
+                                }).collect::<Vec<_>>()}
+                            </tbody>
+                        </table>
+                    }.into_any()
+                }
+            }}
+        </section>
+    }
+}
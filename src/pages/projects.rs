@@ -2,7 +2,7 @@ use leptos::prelude::*;
 use leptos::html::Dialog;
 use leptos::task::spawn_local;
 use crate::app::AppView;
-use crate::features::projects::ProjectModal;
+use crate::features::projects::{ProjectModal, UsagePanel};
 use crate::core::models::{Project, Task, TaskStatus};
 use serde_wasm_bindgen::to_value;
 use wasm_bindgen::prelude::*;
@@ -147,6 +147,8 @@ pub fn Projects() -> impl IntoView {
                     }
                 }}
             </div>
+
+            <UsagePanel />
             
             <ProjectModal 
                 on_create=create_project